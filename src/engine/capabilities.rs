@@ -0,0 +1,358 @@
+use crate::errors::MontycatClientError;
+use serde::Deserialize;
+use std::fmt;
+
+/// A Montycat server version, as reported by the server handshake.
+///
+/// # Examples
+///
+/// ```rust, ignore
+/// let version = ServerVersion::parse("v1.0.4").unwrap();
+/// assert!(version >= ServerVersion::new(1, 0, 4));
+/// ```
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ServerVersion {
+    pub major: u32,
+    pub minor: u32,
+    pub patch: u32,
+}
+
+impl ServerVersion {
+    pub const fn new(major: u32, minor: u32, patch: u32) -> Self {
+        Self {
+            major,
+            minor,
+            patch,
+        }
+    }
+
+    /// Parses a `major.minor.patch` version, with an optional leading `v`.
+    /// Missing minor or patch components default to 0, and pre-release or build suffixes are ignored.
+    ///
+    /// # Returns
+    ///
+    /// * `Option<ServerVersion>` - The parsed version, or None if the string is not a version
+    ///
+    pub fn parse(version: &str) -> Option<Self> {
+        let version: &str = version.trim();
+        let version: &str = version.strip_prefix('v').unwrap_or(version);
+        let version: &str = version.split(['-', '+']).next().unwrap_or_default();
+
+        let mut parts = version.split('.');
+        let major: u32 = parts.next()?.parse().ok()?;
+        let minor: u32 = parts.next().map_or(Some(0), |p| p.parse().ok())?;
+        let patch: u32 = parts.next().map_or(Some(0), |p| p.parse().ok())?;
+
+        if parts.next().is_some() {
+            return None;
+        }
+
+        Some(Self::new(major, minor, patch))
+    }
+}
+
+impl fmt::Display for ServerVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}.{}", self.major, self.minor, self.patch)
+    }
+}
+
+/// Server features the client checks for before sending a request that needs them.
+///
+/// # Variants
+///
+/// - `Volumes`: Filtering `get_bulk` and `get_keys` by volume.
+/// - `LatestVolume`: Restricting `get_bulk` and `get_keys` to the latest volume.
+/// - `PointersMetadata`: Returning pointer metadata instead of pointer values.
+/// - `MessagePack`: The MessagePack codec. Only used when the server advertises it explicitly.
+/// - `NestedSchemas`: Schema fields sent with the shapes of nested structs, enums and collections.
+///   Only used when the server advertises it explicitly.
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ServerFeature {
+    Volumes,
+    LatestVolume,
+    PointersMetadata,
+    MessagePack,
+    NestedSchemas,
+}
+
+impl ServerFeature {
+    /// Returns the feature name used in the server handshake.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ServerFeature::Volumes => "volumes",
+            ServerFeature::LatestVolume => "latest_volume",
+            ServerFeature::PointersMetadata => "pointers_metadata",
+            ServerFeature::MessagePack => "msgpack",
+            ServerFeature::NestedSchemas => "nested_schemas",
+        }
+    }
+
+    /// Returns the first server version that supports the feature, or None for features
+    /// that are only used when the server advertises them in its feature list.
+    pub fn min_version(&self) -> Option<ServerVersion> {
+        match self {
+            ServerFeature::Volumes | ServerFeature::LatestVolume => {
+                Some(ServerVersion::new(1, 0, 4))
+            }
+            ServerFeature::PointersMetadata => Some(ServerVersion::new(1, 0, 0)),
+            ServerFeature::MessagePack | ServerFeature::NestedSchemas => None,
+        }
+    }
+}
+
+/// What the connected server supports, detected once per engine and cached.
+///
+/// Capabilities come from the `get-server-version` handshake. The server can answer with a version
+/// string, or with an object holding a `version` and an explicit `features` list, which takes precedence
+/// over the version. When the server rejects the handshake or answers it with something the client
+/// cannot parse, the conservative `ServerCapabilities::fallback()` is cached, so only features every
+/// server release supports are used. When the handshake fails at the transport level, the fallback
+/// is used for that request only and the handshake is retried on the next one. Features without a
+/// minimum version, such as `MessagePack` and `NestedSchemas`, must always be advertised.
+///
+/// # Examples
+///
+/// ```rust, ignore
+/// let capabilities: &ServerCapabilities = engine.server_capabilities().await?;
+/// if capabilities.supports(ServerFeature::Volumes) {
+///     // ...
+/// }
+/// ```
+///
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ServerCapabilities {
+    version: Option<ServerVersion>,
+    features: Option<Vec<String>>,
+}
+
+/// Payload of the `get-server-version` handshake response.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum VersionPayload {
+    Version(String),
+    Detailed {
+        version: Option<String>,
+        features: Option<Vec<String>>,
+    },
+}
+
+/// Envelope of the `get-server-version` handshake response.
+#[derive(Deserialize)]
+struct VersionResponse {
+    status: bool,
+    payload: Option<VersionPayload>,
+}
+
+impl ServerCapabilities {
+    /// Capabilities without a version or feature list, which allow every feature with a minimum
+    /// version. Useful with `Engine::with_server_capabilities` for servers that cannot be detected.
+    pub fn unknown() -> Self {
+        Self::default()
+    }
+
+    /// Conservative capabilities used when the server rejects the handshake, answers it with
+    /// something the client cannot parse, or cannot be reached. Only features every server
+    /// release supports are allowed.
+    pub fn fallback() -> Self {
+        Self {
+            version: None,
+            features: Some(vec![ServerFeature::PointersMetadata.as_str().to_string()]),
+        }
+    }
+
+    /// Capabilities derived from a server version.
+    pub fn for_version(version: ServerVersion) -> Self {
+        Self {
+            version: Some(version),
+            features: None,
+        }
+    }
+
+    /// Capabilities from an explicit feature list, e.g. `["volumes", "latest_volume"]`.
+    pub fn with_features<I, S>(version: Option<ServerVersion>, features: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        Self {
+            version,
+            features: Some(features.into_iter().map(Into::into).collect()),
+        }
+    }
+
+    /// Returns the server version, if the server reported one.
+    pub fn version(&self) -> Option<ServerVersion> {
+        self.version
+    }
+
+    /// Returns true if the server answered the handshake with a version or a feature list.
+    pub fn is_known(&self) -> bool {
+        self.version.is_some() || self.features.is_some()
+    }
+
+    /// Returns true if the server supports `feature`. Features with a minimum version are assumed
+    /// to be supported when the capabilities are unknown.
+    pub fn supports(&self, feature: ServerFeature) -> bool {
+        if let Some(features) = &self.features {
+            return features.iter().any(|f| f == feature.as_str());
+        }
+
+        match (self.version, feature.min_version()) {
+            (_, None) => false,
+            (Some(version), Some(min_version)) => version >= min_version,
+            (None, Some(_)) => true,
+        }
+    }
+
+    /// Checks that the server supports `feature`.
+    ///
+    /// # Errors
+    ///
+    /// Returns `MontycatClientError::ClientUnsupported` naming the feature, the version that
+    /// introduced it and the connected server version.
+    ///
+    pub fn require(&self, feature: ServerFeature) -> Result<(), MontycatClientError> {
+        if self.supports(feature) {
+            return Ok(());
+        }
+
+        let server: String = self
+            .version
+            .map_or_else(|| "unknown version".to_string(), |v| v.to_string());
+
+        let message: String = match feature.min_version() {
+            Some(min_version) => format!(
+                "{} requires Montycat server {} or later (connected server: {})",
+                feature.as_str(),
+                min_version,
+                server
+            ),
+            None => format!(
+                "{} is not supported by the connected server ({})",
+                feature.as_str(),
+                server
+            ),
+        };
+
+        Err(MontycatClientError::ClientUnsupported(message))
+    }
+
+    /// Builds capabilities from the handshake response. Anything but a successful answer with a
+    /// parsable version or a feature list yields `ServerCapabilities::fallback()`.
+    pub(crate) fn from_response(bytes: &[u8]) -> Self {
+        let Ok(response) = serde_json::from_slice::<VersionResponse>(bytes) else {
+            return Self::fallback();
+        };
+
+        if !response.status {
+            return Self::fallback();
+        }
+
+        let capabilities: Self = match response.payload {
+            Some(VersionPayload::Version(version)) => Self {
+                version: ServerVersion::parse(&version),
+                features: None,
+            },
+            Some(VersionPayload::Detailed { version, features }) => Self {
+                version: version.as_deref().and_then(ServerVersion::parse),
+                features,
+            },
+            None => Self::unknown(),
+        };
+
+        if capabilities.is_known() {
+            capabilities
+        } else {
+            Self::fallback()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_server_version() {
+        assert_eq!(
+            ServerVersion::parse("1.0.4"),
+            Some(ServerVersion::new(1, 0, 4))
+        );
+        assert_eq!(
+            ServerVersion::parse("v2.1"),
+            Some(ServerVersion::new(2, 1, 0))
+        );
+        assert_eq!(
+            ServerVersion::parse("1.2.3-beta+7"),
+            Some(ServerVersion::new(1, 2, 3))
+        );
+        assert_eq!(ServerVersion::parse("latest"), None);
+        assert_eq!(ServerVersion::parse("1.2.3.4"), None);
+    }
+
+    #[test]
+    fn test_capabilities_from_version_response() {
+        let capabilities =
+            ServerCapabilities::from_response(br#"{"status":true,"payload":"1.0.3","error":null}"#);
+
+        assert_eq!(capabilities.version(), Some(ServerVersion::new(1, 0, 3)));
+        assert!(capabilities.supports(ServerFeature::PointersMetadata));
+        assert!(!capabilities.supports(ServerFeature::Volumes));
+        assert!(!capabilities.supports(ServerFeature::LatestVolume));
+    }
+
+    #[test]
+    fn test_capabilities_feature_list_takes_precedence() {
+        let capabilities = ServerCapabilities::from_response(
+            br#"{"status":true,"payload":{"version":"9.0.0","features":["volumes"]},"error":null}"#,
+        );
+
+        assert!(capabilities.supports(ServerFeature::Volumes));
+        assert!(!capabilities.supports(ServerFeature::LatestVolume));
+    }
+
+    #[test]
+    fn test_rejected_or_unparsable_handshake_falls_back() {
+        let rejected = ServerCapabilities::from_response(
+            br#"{"status":false,"payload":null,"error":"Unknown command"}"#,
+        );
+        let garbage = ServerCapabilities::from_response(b"not json");
+        let bad_version = ServerCapabilities::from_response(
+            br#"{"status":true,"payload":"latest","error":null}"#,
+        );
+        let empty = ServerCapabilities::from_response(
+            br#"{"status":true,"payload":{"version":"latest"},"error":null}"#,
+        );
+
+        for capabilities in [rejected, garbage, bad_version, empty] {
+            assert_eq!(capabilities, ServerCapabilities::fallback());
+            assert!(!capabilities.supports(ServerFeature::Volumes));
+            assert!(!capabilities.supports(ServerFeature::MessagePack));
+        }
+    }
+
+    #[test]
+    fn test_fallback_only_allows_baseline_features() {
+        let capabilities = ServerCapabilities::fallback();
+
+        assert!(capabilities.supports(ServerFeature::PointersMetadata));
+        assert!(!capabilities.supports(ServerFeature::Volumes));
+        assert!(!capabilities.supports(ServerFeature::LatestVolume));
+        assert!(!capabilities.supports(ServerFeature::MessagePack));
+    }
+
+    #[test]
+    fn test_require_reports_versions() {
+        let capabilities = ServerCapabilities::for_version(ServerVersion::new(1, 0, 2));
+        let error = capabilities.require(ServerFeature::Volumes).unwrap_err();
+
+        assert!(matches!(error, MontycatClientError::ClientUnsupported(_)));
+        assert_eq!(
+            error.message(),
+            "volumes requires Montycat server 1.0.4 or later (connected server: 1.0.2)"
+        );
+    }
+}
@@ -0,0 +1,411 @@
+use crate::errors::MontycatClientError;
+use async_trait::async_trait;
+use serde::{Deserialize, Deserializer};
+use std::{fmt, future::Future, path::PathBuf, pin::Pin, sync::Arc};
+use zeroize::Zeroize;
+
+/// A string holding secret material, such as a password.
+///
+/// The content is redacted when formatted with `Debug` and zeroized when the value is dropped.
+/// Use `expose_secret` to read it.
+///
+/// # Examples
+///
+/// ```rust, ignore
+/// let secret = SecretString::from("pass");
+/// assert_eq!(format!("{:?}", secret), "SecretString([REDACTED])");
+/// assert_eq!(secret.expose_secret(), "pass");
+/// ```
+///
+#[derive(Clone, Default, PartialEq, Eq)]
+pub struct SecretString(String);
+
+impl SecretString {
+    /// Creates a new secret from a string.
+    pub fn new(secret: String) -> Self {
+        Self(secret)
+    }
+
+    /// Returns the secret content.
+    pub fn expose_secret(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Debug for SecretString {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("SecretString([REDACTED])")
+    }
+}
+
+impl Drop for SecretString {
+    fn drop(&mut self) {
+        self.0.zeroize();
+    }
+}
+
+impl From<String> for SecretString {
+    fn from(secret: String) -> Self {
+        Self(secret)
+    }
+}
+
+impl From<&str> for SecretString {
+    fn from(secret: &str) -> Self {
+        Self(secret.to_owned())
+    }
+}
+
+impl<'de> Deserialize<'de> for SecretString {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        String::deserialize(deserializer).map(Self)
+    }
+}
+
+/// A username and password pair used to authenticate against the Montycat server.
+///
+/// # Fields
+/// - `username`: The username for authentication.
+/// - `password`: The password for authentication, redacted in `Debug` output.
+///
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Credentials {
+    pub username: String,
+    pub password: SecretString,
+}
+
+impl Credentials {
+    /// Creates a new credentials pair.
+    ///
+    /// # Examples
+    ///
+    /// ```rust, ignore
+    /// let credentials = Credentials::new("user", "pass");
+    /// ```
+    ///
+    pub fn new(username: impl Into<String>, password: impl Into<SecretString>) -> Self {
+        Self {
+            username: username.into(),
+            password: password.into(),
+        }
+    }
+}
+
+/// Source of credentials consulted by the `Engine` before every request.
+///
+/// Implementations are provided for static credentials (`StaticCredentials`), environment
+/// variables (`EnvCredentials`), secret files (`FileCredentials`) and async callbacks
+/// (`CallbackCredentials`).
+///
+/// Credentials are returned behind an `Arc` so that providers holding them in memory
+/// can hand them out without copying.
+///
+/// # Examples
+///
+/// ```rust, ignore
+/// #[derive(Debug)]
+/// struct Vault;
+///
+/// #[async_trait]
+/// impl CredentialProvider for Vault {
+///     async fn credentials(&self) -> Result<Arc<Credentials>, MontycatClientError> {
+///         Ok(Arc::new(Credentials::new("user", fetch_password().await)))
+///     }
+/// }
+///
+/// engine.set_credential_provider(Arc::new(Vault));
+/// ```
+///
+#[async_trait]
+pub trait CredentialProvider: Send + Sync + fmt::Debug {
+    async fn credentials(&self) -> Result<Arc<Credentials>, MontycatClientError>;
+}
+
+/// Credentials fixed at construction time.
+#[derive(Debug, Clone)]
+pub struct StaticCredentials {
+    credentials: Arc<Credentials>,
+}
+
+impl StaticCredentials {
+    /// Creates a provider that always returns the given credentials.
+    pub fn new(credentials: Credentials) -> Self {
+        Self {
+            credentials: Arc::new(credentials),
+        }
+    }
+}
+
+#[async_trait]
+impl CredentialProvider for StaticCredentials {
+    async fn credentials(&self) -> Result<Arc<Credentials>, MontycatClientError> {
+        Ok(Arc::clone(&self.credentials))
+    }
+}
+
+/// Credentials read from environment variables on every request.
+///
+/// # Fields
+/// - `username_var`: The variable holding the username.
+/// - `password_var`: The variable holding the password.
+///
+#[derive(Debug, Clone)]
+pub struct EnvCredentials {
+    pub username_var: String,
+    pub password_var: String,
+}
+
+impl EnvCredentials {
+    /// Creates a provider reading the given variables.
+    ///
+    /// # Examples
+    ///
+    /// ```rust, ignore
+    /// let provider = EnvCredentials::new("MONTYCAT_USERNAME", "MONTYCAT_PASSWORD");
+    /// ```
+    ///
+    pub fn new(username_var: impl Into<String>, password_var: impl Into<String>) -> Self {
+        Self {
+            username_var: username_var.into(),
+            password_var: password_var.into(),
+        }
+    }
+}
+
+#[async_trait]
+impl CredentialProvider for EnvCredentials {
+    async fn credentials(&self) -> Result<Arc<Credentials>, MontycatClientError> {
+        let read = |name: &str| {
+            std::env::var(name).map_err(|_| {
+                MontycatClientError::ClientGenericError(format!(
+                    "Environment variable {} is not set",
+                    name
+                ))
+            })
+        };
+
+        Ok(Arc::new(Credentials::new(
+            read(&self.username_var)?,
+            read(&self.password_var)?,
+        )))
+    }
+}
+
+/// Credentials whose password is read from a file on every request, such as a mounted secret.
+///
+/// Trailing line breaks are stripped from the file content.
+///
+/// # Fields
+/// - `username`: The username for authentication.
+/// - `password_path`: The file holding the password.
+///
+#[derive(Debug, Clone)]
+pub struct FileCredentials {
+    pub username: String,
+    pub password_path: PathBuf,
+}
+
+impl FileCredentials {
+    /// Creates a provider reading the password from `password_path`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust, ignore
+    /// let provider = FileCredentials::new("user", "/run/secrets/montycat_password");
+    /// ```
+    ///
+    pub fn new(username: impl Into<String>, password_path: impl Into<PathBuf>) -> Self {
+        Self {
+            username: username.into(),
+            password_path: password_path.into(),
+        }
+    }
+}
+
+#[async_trait]
+impl CredentialProvider for FileCredentials {
+    async fn credentials(&self) -> Result<Arc<Credentials>, MontycatClientError> {
+        let mut content: String = tokio::fs::read_to_string(&self.password_path)
+            .await
+            .map_err(|e| {
+                MontycatClientError::ClientGenericError(format!(
+                    "Cannot read password file {}: {}",
+                    self.password_path.display(),
+                    e
+                ))
+            })?;
+
+        let trimmed_len: usize = content.trim_end_matches(['\r', '\n']).len();
+        content.truncate(trimmed_len);
+
+        Ok(Arc::new(Credentials::new(self.username.clone(), content)))
+    }
+}
+
+tokio::task_local! {
+    /// Credential overrides active for the current task, keyed by the credential slot they apply to.
+    static CREDENTIAL_OVERRIDES: Vec<(usize, Arc<Credentials>)>;
+}
+
+/// Runs `future` with `credentials` overriding the provider of the credential slot `slot_id`.
+/// Overrides for other slots that are already active in the current task are kept.
+pub(crate) async fn scope_override<F>(
+    slot_id: usize,
+    credentials: Credentials,
+    future: F,
+) -> F::Output
+where
+    F: Future,
+{
+    let mut overrides: Vec<(usize, Arc<Credentials>)> = CREDENTIAL_OVERRIDES
+        .try_with(|active| active.clone())
+        .unwrap_or_default();
+    overrides.retain(|(id, _)| *id != slot_id);
+    overrides.push((slot_id, Arc::new(credentials)));
+
+    CREDENTIAL_OVERRIDES.scope(overrides, future).await
+}
+
+/// Returns the override for the credential slot `slot_id` active in the current task, if any.
+pub(crate) fn current_override(slot_id: usize) -> Option<Arc<Credentials>> {
+    CREDENTIAL_OVERRIDES
+        .try_with(|active| {
+            active
+                .iter()
+                .find(|(id, _)| *id == slot_id)
+                .map(|(_, credentials)| Arc::clone(credentials))
+        })
+        .ok()
+        .flatten()
+}
+
+type CredentialFuture =
+    Pin<Box<dyn Future<Output = Result<Credentials, MontycatClientError>> + Send>>;
+
+/// Credentials produced by an async callback, e.g. a call to a secret manager.
+///
+/// # Examples
+///
+/// ```rust, ignore
+/// let provider = CallbackCredentials::new(|| async {
+///     let password = vault_client.read("montycat").await?;
+///     Ok(Credentials::new("user", password))
+/// });
+/// ```
+///
+#[derive(Clone)]
+pub struct CallbackCredentials {
+    callback: Arc<dyn Fn() -> CredentialFuture + Send + Sync>,
+}
+
+impl CallbackCredentials {
+    /// Creates a provider calling `callback` before every request.
+    pub fn new<F, Fut>(callback: F) -> Self
+    where
+        F: Fn() -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<Credentials, MontycatClientError>> + Send + 'static,
+    {
+        Self {
+            callback: Arc::new(move || Box::pin(callback())),
+        }
+    }
+}
+
+impl fmt::Debug for CallbackCredentials {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CallbackCredentials")
+            .finish_non_exhaustive()
+    }
+}
+
+#[async_trait]
+impl CredentialProvider for CallbackCredentials {
+    async fn credentials(&self) -> Result<Arc<Credentials>, MontycatClientError> {
+        (self.callback)().await.map(Arc::new)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_secret_string_redacts_debug() {
+        let secret = SecretString::from("hunter2");
+        assert_eq!(format!("{:?}", secret), "SecretString([REDACTED])");
+        assert_eq!(secret.expose_secret(), "hunter2");
+    }
+
+    #[test]
+    fn test_credentials_debug_hides_password() {
+        let credentials = Credentials::new("user", "hunter2");
+        let debug = format!("{:?}", credentials);
+        assert!(debug.contains("user"));
+        assert!(!debug.contains("hunter2"));
+    }
+
+    #[test]
+    fn test_static_credentials() {
+        let provider = StaticCredentials::new(Credentials::new("user", "pass"));
+        let credentials = tokio_test::block_on(provider.credentials()).unwrap();
+        assert_eq!(credentials.username, "user");
+        assert_eq!(credentials.password.expose_secret(), "pass");
+    }
+
+    #[test]
+    fn test_env_credentials_missing_variable() {
+        let provider = EnvCredentials::new(
+            "MONTYCAT_TEST_UNSET_USERNAME",
+            "MONTYCAT_TEST_UNSET_PASSWORD",
+        );
+        let result = tokio_test::block_on(provider.credentials());
+        assert!(result.is_err());
+        assert!(
+            result
+                .unwrap_err()
+                .message()
+                .contains("MONTYCAT_TEST_UNSET_USERNAME")
+        );
+    }
+
+    #[test]
+    fn test_file_credentials_strips_line_break() {
+        let path =
+            std::env::temp_dir().join(format!("montycat_password_test_{}", std::process::id()));
+        std::fs::write(&path, "s3cret\n").unwrap();
+
+        let provider = FileCredentials::new("user", &path);
+        let credentials = tokio_test::block_on(provider.credentials()).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(credentials.username, "user");
+        assert_eq!(credentials.password.expose_secret(), "s3cret");
+    }
+
+    #[test]
+    fn test_scope_override() {
+        let credentials = tokio_test::block_on(scope_override(
+            1,
+            Credentials::new("tenant", "tenantpass"),
+            async { (current_override(1), current_override(2)) },
+        ));
+
+        assert_eq!(
+            credentials.0.as_deref(),
+            Some(&Credentials::new("tenant", "tenantpass"))
+        );
+        assert_eq!(credentials.1, None);
+        assert_eq!(current_override(1), None);
+    }
+
+    #[test]
+    fn test_callback_credentials() {
+        let provider = CallbackCredentials::new(|| async { Ok(Credentials::new("cb", "cbpass")) });
+        let credentials = tokio_test::block_on(provider.credentials()).unwrap();
+        assert_eq!(credentials.username, "cb");
+        assert_eq!(credentials.password.expose_secret(), "cbpass");
+    }
+}
@@ -1,2 +1,5 @@
+pub mod capabilities;
+pub mod config;
+pub mod credentials;
 pub mod structure;
 pub mod utils;
@@ -1,4 +1,4 @@
-use crate::MontycatClientError;
+use crate::{MontycatClientError, codec::Codec};
 #[cfg(feature = "tls")]
 use rustls_pki_types::ServerName;
 use std::{sync::Arc, time::Duration};
@@ -14,7 +14,7 @@ use tokio_rustls::{
     rustls::{ClientConfig, RootCertStore},
 };
 
-pub(crate) type StreamCallback = Arc<dyn Fn(&mut [u8]) + Send + Sync>;
+pub type StreamCallback = Arc<dyn Fn(&mut [u8]) + Send + Sync>;
 
 const CHUNK_SIZE: usize = 1024 * 256;
 
@@ -68,7 +68,8 @@ impl Connection {
 
 /// Sends data to the Montycat server and handles the response.
 /// Supports both plain TCP and TLS connections based on the `use_tls` flag.
-/// Can handle both standard requests and subscription requests.
+/// Can handle both standard requests and subscription requests; a request is treated as a
+/// subscription when a callback is given.
 ///
 /// # Arguments
 ///
@@ -78,11 +79,13 @@ impl Connection {
 /// - `callback: Option<Arc<dyn Fn(&Vec<u8>) + Send + Sync>>`: An optional callback function to handle incoming data for subscriptions.
 /// - `stop_event: Option<&mut Receiver<bool>>`: An optional stop event to terminate subscriptions.
 /// - `use_tls: bool`: A flag indicating whether to use TLS for the connection.
+/// - `codec: Codec`: The codec `query` is encoded with, which determines how the response is framed.
 ///
 /// # Returns
 ///
 /// - `Result<Option<Vec<u8>>, MontycatClientError>`:
-///   - For standard requests, returns `Ok(Some(response_bytes))` containing the server's response.
+///   - For standard requests, returns `Ok(Some(response_bytes))` containing the server's response,
+///     without the frame header for length-prefixed codecs.
 ///   - For subscription requests, returns `Ok(None)` after the subscription is terminated.
 ///   - Returns an error of type `MontycatClientError` if any issues occur during the process.
 ///
@@ -93,6 +96,7 @@ pub(crate) async fn send_data(
     callback: Option<StreamCallback>,
     stop_event: Option<&mut Receiver<bool>>,
     use_tls: bool,
+    codec: Codec,
 ) -> Result<Option<Vec<u8>>, MontycatClientError> {
     let host: String = host.to_string();
     let plain_stream: TcpStream = TcpStream::connect((host.as_ref(), port))
@@ -169,7 +173,7 @@ pub(crate) async fn send_data(
 
     let mut buf = vec![];
 
-    let is_subscription = query.windows(9).any(|w| w == b"subscribe");
+    let is_subscription: bool = callback.is_some();
 
     if is_subscription {
         loop {
@@ -222,7 +226,7 @@ pub(crate) async fn send_data(
             }
 
             buf.extend_from_slice(&chunk[..n]);
-            if buf.contains(&b'\n') {
+            if codec.complete_frame(&buf).is_some() {
                 break;
             }
         }
@@ -231,6 +235,10 @@ pub(crate) async fn send_data(
             .shutdown()
             .await
             .map_err(|e| MontycatClientError::ClientEngineError(e.to_string()))?;
-        Ok(Some(buf))
+
+        match codec.complete_frame(&buf) {
+            Some(frame) if frame.len() != buf.len() => Ok(Some(frame.to_vec())),
+            _ => Ok(Some(buf)),
+        }
     }
 }
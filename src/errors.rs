@@ -13,6 +13,12 @@ use serde::{Deserialize, Serialize};
 /// - `ClientAsyncRuntimeError(String)` : Represents errors related to the async runtime.
 /// - `ClientUnsupportedFieldType(String)` : Error for unsupported field types.
 /// - `ClientMultipleSchemasFound` : Error when multiple schemas are found in bulk values.
+/// - `ClientUnsupported(String)` : Error when the connected server does not support a requested feature.
+/// - `ClientServerError(String)` : Error reported by the server in a response with `status: false`.
+/// - `ClientCustomKeyCollision(String)` : Error when a read returns a value stored under another custom key with the same hash.
+/// - `ClientDeleteRestricted(String)` : Error when a delete with `DeletePolicy::Restrict` finds values depending on the key.
+/// - `ClientKeyRegistryOutOfSync { error, response }` : Error when an insert or delete succeeded but its key registry write failed.
+///   `response` holds the response of the insert or delete.
 ///
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum MontycatClientError {
@@ -26,6 +32,14 @@ pub enum MontycatClientError {
     ClientAsyncRuntimeError(String),
     ClientUnsupportedFieldType(String),
     ClientMultipleSchemasFound,
+    ClientUnsupported(String),
+    ClientServerError(String),
+    ClientCustomKeyCollision(String),
+    ClientDeleteRestricted(String),
+    ClientKeyRegistryOutOfSync {
+        error: String,
+        response: Option<Vec<u8>>,
+    },
 }
 
 impl MontycatClientError {
@@ -54,6 +68,17 @@ impl MontycatClientError {
             MontycatClientError::ClientUnsupportedFieldType(ty) => {
                 format!("Unsupported field type: {}", ty)
             }
+            MontycatClientError::ClientUnsupported(msg) => msg.to_owned(),
+            MontycatClientError::ClientServerError(msg) => msg.to_owned(),
+            MontycatClientError::ClientCustomKeyCollision(stored) => format!(
+                "Custom key collision: the value belongs to custom key '{}', which has the same hash",
+                stored
+            ),
+            MontycatClientError::ClientDeleteRestricted(msg) => msg.to_owned(),
+            MontycatClientError::ClientKeyRegistryOutOfSync { error, .. } => format!(
+                "The write succeeded, but the key registry was not updated: {}",
+                error
+            ),
         }
     }
 }
@@ -131,6 +156,32 @@ mod tests {
         assert_eq!(error.message(), "Unsupported field type: ComplexType");
     }
 
+    #[test]
+    fn test_client_server_error_message() {
+        let error = MontycatClientError::ClientServerError("Key not found".to_string());
+        assert_eq!(error.message(), "Key not found");
+    }
+
+    #[test]
+    fn test_client_custom_key_collision_message() {
+        let error = MontycatClientError::ClientCustomKeyCollision("monty".to_string());
+        assert_eq!(
+            error.message(),
+            "Custom key collision: the value belongs to custom key 'monty', which has the same hash"
+        );
+    }
+
+    #[test]
+    fn test_client_delete_restricted_message() {
+        let error = MontycatClientError::ClientDeleteRestricted(
+            "2 values depend on key 'u1' in keyspace 'users'".to_string(),
+        );
+        assert_eq!(
+            error.message(),
+            "2 values depend on key 'u1' in keyspace 'users'"
+        );
+    }
+
     #[test]
     fn test_error_serialization() {
         let error = MontycatClientError::ClientStoreNotSet;
@@ -152,4 +203,16 @@ mod tests {
         let deserialized: MontycatClientError = serde_json::from_str(&serialized).unwrap();
         assert_eq!(deserialized.message(), "test error");
     }
+
+    #[test]
+    fn test_client_key_registry_out_of_sync_message() {
+        let error = MontycatClientError::ClientKeyRegistryOutOfSync {
+            error: "registering custom key 'alice': Keyspace not found".to_string(),
+            response: None,
+        };
+        assert_eq!(
+            error.message(),
+            "The write succeeded, but the key registry was not updated: registering custom key 'alice': Keyspace not found"
+        );
+    }
 }
@@ -0,0 +1,606 @@
+use super::{
+    pubtrait::Keyspace,
+    structures::{inmemory::InMemoryKeyspace, persistent::PersistentKeyspace},
+};
+use crate::{Limit, MontycatClientError, engine::structure::Engine};
+use async_trait::async_trait;
+use serde_json::Value;
+use std::collections::HashMap;
+
+/// Object-safe counterpart of `Keyspace` for callers that need `dyn` dispatch.
+///
+/// `Keyspace` returns `impl Future` from its methods and has generic methods, so it cannot be used
+/// as a trait object. `DynKeyspace` exposes the same operations with boxed futures, takes values
+/// and search criteria as `serde_json::Value` and returns the raw response bytes. It is implemented
+/// for `PersistentKeyspace` and `InMemoryKeyspace`, so services can be written against
+/// `Arc<dyn DynKeyspace>` and swap one kind for the other.
+///
+/// Each call allocates one boxed future. Prefer `Keyspace` on hot paths.
+///
+/// # Examples
+///
+/// ```rust, ignore
+/// use montycat::{DynKeyspace, InMemoryKeyspace, PersistentKeyspace};
+///
+/// let keyspaces: Vec<Box<dyn DynKeyspace>> = vec![
+///     Box::new(PersistentKeyspace::new("employees", &engine)),
+///     Box::new(InMemoryKeyspace::new("sessions", &engine)),
+/// ];
+///
+/// for keyspace in &keyspaces {
+///     keyspace.insert_value_no_schema(None, json!({ "name": "Alice" })).await?;
+///     let len = keyspace.get_len().await?;
+/// }
+/// ```
+///
+/// # Notes
+///
+/// Method names match `Keyspace` and the inherent keyspace methods. When both traits are in scope,
+/// calls on a concrete keyspace type are ambiguous, so import only the trait you use,
+/// or call `Keyspace::get_len(&keyspace)`.
+///
+/// Values written to an `InMemoryKeyspace` through this trait do not expire.
+///
+#[async_trait]
+pub trait DynKeyspace: Send + Sync {
+    fn get_engine(&self) -> &Engine;
+    fn get_name(&self) -> &str;
+    fn get_persistent(&self) -> bool;
+    fn get_distributed(&self) -> bool;
+
+    /// See `Keyspace::remove_keyspace`.
+    async fn remove_keyspace(&self) -> Result<Option<Vec<u8>>, MontycatClientError>;
+
+    /// See `Keyspace::get_value`.
+    async fn get_value(
+        &self,
+        key: Option<&str>,
+        custom_key: Option<&str>,
+        with_pointers: bool,
+        key_included: bool,
+        with_pointers_metadata: bool,
+    ) -> Result<Option<Vec<u8>>, MontycatClientError>;
+
+    /// See `Keyspace::delete_key`.
+    async fn delete_key(
+        &self,
+        key: Option<&str>,
+        custom_key: Option<&str>,
+    ) -> Result<Option<Vec<u8>>, MontycatClientError>;
+
+    /// See `Keyspace::list_all_depending_keys`.
+    async fn list_all_depending_keys(
+        &self,
+        key: &str,
+        custom_key: Option<&str>,
+    ) -> Result<Option<Vec<u8>>, MontycatClientError>;
+
+    /// See `Keyspace::get_bulk`.
+    #[allow(clippy::too_many_arguments)]
+    async fn get_bulk(
+        &self,
+        bulk_keys: Option<Vec<String>>,
+        bulk_custom_keys: Option<Vec<String>>,
+        with_pointers: bool,
+        key_included: bool,
+        with_pointers_metadata: bool,
+        limit: Option<Limit>,
+        volumes: Option<Vec<String>>,
+        latest_volume: Option<bool>,
+    ) -> Result<Option<Vec<u8>>, MontycatClientError>;
+
+    /// See `Keyspace::delete_bulk`.
+    async fn delete_bulk(
+        &self,
+        bulk_keys: Option<Vec<String>>,
+        bulk_custom_keys: Option<Vec<String>>,
+    ) -> Result<Option<Vec<u8>>, MontycatClientError>;
+
+    /// See `Keyspace::get_len`.
+    async fn get_len(&self) -> Result<Option<Vec<u8>>, MontycatClientError>;
+
+    /// See `Keyspace::enforce_schema`.
+    async fn enforce_schema(
+        &self,
+        schema_params: (HashMap<&str, &str>, &str),
+    ) -> Result<Option<Vec<u8>>, MontycatClientError>;
+
+    /// See `Keyspace::remove_enforced_schema`.
+    async fn remove_enforced_schema(
+        &self,
+        schema_name: (HashMap<&str, &str>, &str),
+    ) -> Result<Option<Vec<u8>>, MontycatClientError>;
+
+    /// See `Keyspace::list_all_schemas_in_keyspace`.
+    async fn list_all_schemas_in_keyspace(&self) -> Result<Option<Vec<u8>>, MontycatClientError>;
+
+    /// See `Keyspace::update_bulk`.
+    async fn update_bulk(
+        &self,
+        bulk_keys_values: Vec<HashMap<String, Value>>,
+        bulk_custom_keys_values: Vec<HashMap<String, Value>>,
+    ) -> Result<Option<Vec<u8>>, MontycatClientError>;
+
+    /// See `Keyspace::lookup_keys_where`.
+    async fn lookup_keys_where(
+        &self,
+        search_criteria: Value,
+        limit: Option<Limit>,
+        schema_name: Option<(HashMap<&str, &str>, &str)>,
+    ) -> Result<Option<Vec<u8>>, MontycatClientError>;
+
+    /// See `Keyspace::lookup_values_where`.
+    #[allow(clippy::too_many_arguments)]
+    async fn lookup_values_where(
+        &self,
+        search_criteria: Value,
+        limit: Option<Limit>,
+        with_pointers: bool,
+        key_included: bool,
+        pointers_metadata: bool,
+        schema_name: Option<(HashMap<&str, &str>, &str)>,
+    ) -> Result<Option<Vec<u8>>, MontycatClientError>;
+
+    /// Inserts a value without schema, optionally under a custom key.
+    async fn insert_value_no_schema(
+        &self,
+        custom_key: Option<String>,
+        value: Value,
+    ) -> Result<Option<Vec<u8>>, MontycatClientError>;
+
+    /// Inserts several values without schema.
+    async fn insert_bulk_no_schema(
+        &self,
+        bulk_values: Vec<Value>,
+    ) -> Result<Option<Vec<u8>>, MontycatClientError>;
+
+    /// Inserts an empty entry under a custom key.
+    async fn insert_custom_key(
+        &self,
+        custom_key: String,
+    ) -> Result<Option<Vec<u8>>, MontycatClientError>;
+
+    /// Updates the value stored under a key or custom key.
+    async fn update_value(
+        &self,
+        key: Option<String>,
+        custom_key: Option<String>,
+        value: Value,
+    ) -> Result<Option<Vec<u8>>, MontycatClientError>;
+
+    /// Lists the keys of the keyspace, optionally restricted to volumes.
+    async fn get_keys(
+        &self,
+        volumes: Option<Vec<String>>,
+        latest_volume: Option<bool>,
+    ) -> Result<Option<Vec<u8>>, MontycatClientError>;
+}
+
+/// Implements `DynKeyspace` for a keyspace type by forwarding the shared operations to `Keyspace`.
+/// The remaining items are the inherent operations, which differ between keyspace kinds.
+macro_rules! impl_dyn_keyspace {
+    ($keyspace:ty, { $($inherent:tt)* }) => {
+        #[async_trait]
+        impl DynKeyspace for $keyspace {
+            fn get_engine(&self) -> &Engine {
+                Keyspace::get_engine(self)
+            }
+
+            fn get_name(&self) -> &str {
+                Keyspace::get_name(self)
+            }
+
+            fn get_persistent(&self) -> bool {
+                Keyspace::get_persistent(self)
+            }
+
+            fn get_distributed(&self) -> bool {
+                Keyspace::get_distributed(self)
+            }
+
+            async fn remove_keyspace(&self) -> Result<Option<Vec<u8>>, MontycatClientError> {
+                Keyspace::remove_keyspace(self).await
+            }
+
+            async fn get_value(
+                &self,
+                key: Option<&str>,
+                custom_key: Option<&str>,
+                with_pointers: bool,
+                key_included: bool,
+                with_pointers_metadata: bool,
+            ) -> Result<Option<Vec<u8>>, MontycatClientError> {
+                Keyspace::get_value(
+                    self,
+                    key,
+                    custom_key,
+                    with_pointers,
+                    key_included,
+                    with_pointers_metadata,
+                )
+                .await
+            }
+
+            async fn delete_key(
+                &self,
+                key: Option<&str>,
+                custom_key: Option<&str>,
+            ) -> Result<Option<Vec<u8>>, MontycatClientError> {
+                Keyspace::delete_key(self, key, custom_key).await
+            }
+
+            async fn list_all_depending_keys(
+                &self,
+                key: &str,
+                custom_key: Option<&str>,
+            ) -> Result<Option<Vec<u8>>, MontycatClientError> {
+                Keyspace::list_all_depending_keys(self, key, custom_key).await
+            }
+
+            async fn get_bulk(
+                &self,
+                bulk_keys: Option<Vec<String>>,
+                bulk_custom_keys: Option<Vec<String>>,
+                with_pointers: bool,
+                key_included: bool,
+                with_pointers_metadata: bool,
+                limit: Option<Limit>,
+                volumes: Option<Vec<String>>,
+                latest_volume: Option<bool>,
+            ) -> Result<Option<Vec<u8>>, MontycatClientError> {
+                Keyspace::get_bulk(
+                    self,
+                    bulk_keys,
+                    bulk_custom_keys,
+                    with_pointers,
+                    key_included,
+                    with_pointers_metadata,
+                    limit,
+                    volumes,
+                    latest_volume,
+                )
+                .await
+            }
+
+            async fn delete_bulk(
+                &self,
+                bulk_keys: Option<Vec<String>>,
+                bulk_custom_keys: Option<Vec<String>>,
+            ) -> Result<Option<Vec<u8>>, MontycatClientError> {
+                Keyspace::delete_bulk(self, bulk_keys, bulk_custom_keys).await
+            }
+
+            async fn get_len(&self) -> Result<Option<Vec<u8>>, MontycatClientError> {
+                Keyspace::get_len(self).await
+            }
+
+            async fn enforce_schema(
+                &self,
+                schema_params: (HashMap<&str, &str>, &str),
+            ) -> Result<Option<Vec<u8>>, MontycatClientError> {
+                Keyspace::enforce_schema(self, schema_params).await
+            }
+
+            async fn remove_enforced_schema(
+                &self,
+                schema_name: (HashMap<&str, &str>, &str),
+            ) -> Result<Option<Vec<u8>>, MontycatClientError> {
+                Keyspace::remove_enforced_schema(self, schema_name).await
+            }
+
+            async fn list_all_schemas_in_keyspace(&self) -> Result<Option<Vec<u8>>, MontycatClientError> {
+                Keyspace::list_all_schemas_in_keyspace(self).await
+            }
+
+            async fn update_bulk(
+                &self,
+                bulk_keys_values: Vec<HashMap<String, Value>>,
+                bulk_custom_keys_values: Vec<HashMap<String, Value>>,
+            ) -> Result<Option<Vec<u8>>, MontycatClientError> {
+                Keyspace::update_bulk(self, bulk_keys_values, bulk_custom_keys_values).await
+            }
+
+            async fn lookup_keys_where(
+                &self,
+                search_criteria: Value,
+                limit: Option<Limit>,
+                schema_name: Option<(HashMap<&str, &str>, &str)>,
+            ) -> Result<Option<Vec<u8>>, MontycatClientError> {
+                Keyspace::lookup_keys_where(self, search_criteria, limit, schema_name).await
+            }
+
+            async fn lookup_values_where(
+                &self,
+                search_criteria: Value,
+                limit: Option<Limit>,
+                with_pointers: bool,
+                key_included: bool,
+                pointers_metadata: bool,
+                schema_name: Option<(HashMap<&str, &str>, &str)>,
+            ) -> Result<Option<Vec<u8>>, MontycatClientError> {
+                Keyspace::lookup_values_where(
+                    self,
+                    search_criteria,
+                    limit,
+                    with_pointers,
+                    key_included,
+                    pointers_metadata,
+                    schema_name,
+                )
+                .await
+            }
+
+            $($inherent)*
+        }
+    };
+}
+
+impl_dyn_keyspace!(PersistentKeyspace, {
+    async fn insert_value_no_schema(
+        &self,
+        custom_key: Option<String>,
+        value: Value,
+    ) -> Result<Option<Vec<u8>>, MontycatClientError> {
+        PersistentKeyspace::insert_value_no_schema(self, custom_key, value).await
+    }
+
+    async fn insert_bulk_no_schema(
+        &self,
+        bulk_values: Vec<Value>,
+    ) -> Result<Option<Vec<u8>>, MontycatClientError> {
+        PersistentKeyspace::insert_bulk_no_schema(self, bulk_values).await
+    }
+
+    async fn insert_custom_key(
+        &self,
+        custom_key: String,
+    ) -> Result<Option<Vec<u8>>, MontycatClientError> {
+        PersistentKeyspace::insert_custom_key(self, custom_key).await
+    }
+
+    async fn update_value(
+        &self,
+        key: Option<String>,
+        custom_key: Option<String>,
+        value: Value,
+    ) -> Result<Option<Vec<u8>>, MontycatClientError> {
+        PersistentKeyspace::update_value(self, key, custom_key, value).await
+    }
+
+    async fn get_keys(
+        &self,
+        volumes: Option<Vec<String>>,
+        latest_volume: Option<bool>,
+    ) -> Result<Option<Vec<u8>>, MontycatClientError> {
+        PersistentKeyspace::get_keys(self, None, volumes, latest_volume).await
+    }
+});
+
+impl_dyn_keyspace!(InMemoryKeyspace, {
+    async fn insert_value_no_schema(
+        &self,
+        custom_key: Option<String>,
+        value: Value,
+    ) -> Result<Option<Vec<u8>>, MontycatClientError> {
+        InMemoryKeyspace::insert_value_no_schema(self, custom_key, value, None).await
+    }
+
+    async fn insert_bulk_no_schema(
+        &self,
+        bulk_values: Vec<Value>,
+    ) -> Result<Option<Vec<u8>>, MontycatClientError> {
+        InMemoryKeyspace::insert_bulk_no_schema(self, bulk_values, None).await
+    }
+
+    async fn insert_custom_key(
+        &self,
+        custom_key: String,
+    ) -> Result<Option<Vec<u8>>, MontycatClientError> {
+        InMemoryKeyspace::insert_custom_key(self, custom_key, None).await
+    }
+
+    async fn update_value(
+        &self,
+        key: Option<String>,
+        custom_key: Option<String>,
+        value: Value,
+    ) -> Result<Option<Vec<u8>>, MontycatClientError> {
+        InMemoryKeyspace::update_value(self, key, custom_key, value, None).await
+    }
+
+    async fn get_keys(
+        &self,
+        volumes: Option<Vec<String>>,
+        latest_volume: Option<bool>,
+    ) -> Result<Option<Vec<u8>>, MontycatClientError> {
+        InMemoryKeyspace::get_keys(self, volumes, latest_volume).await
+    }
+});
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{InMemoryKeyspace, PersistentKeyspace, ReadOptions};
+
+    fn assert_send<F: Send>(_: &F) {}
+
+    fn engine() -> Engine {
+        Engine::new(
+            "localhost".to_string(),
+            21210,
+            "user".to_string(),
+            "pass".to_string(),
+            Some("store".to_string()),
+            false,
+        )
+    }
+
+    #[test]
+    fn test_keyspace_futures_are_send() {
+        let keyspace = PersistentKeyspace::new("employees", &engine());
+
+        assert_send(&Keyspace::get_len(&keyspace));
+        assert_send(&Keyspace::get_value(
+            &keyspace,
+            Some("1"),
+            None,
+            false,
+            false,
+            false,
+        ));
+        assert_send(&Keyspace::update_bulk::<String>(
+            &keyspace,
+            Vec::new(),
+            Vec::new(),
+        ));
+        assert_send(&Keyspace::get_value_as::<serde_json::Value, _>(
+            &keyspace,
+            Some("1"),
+            None,
+            ReadOptions::new().key_included().pointers_metadata(),
+        ));
+    }
+
+    #[cfg(not(feature = "tls"))]
+    #[test]
+    fn test_typed_get_value_parses_and_checks_status() {
+        use crate::test_support::MockServer;
+
+        #[derive(Debug, PartialEq, serde::Deserialize)]
+        struct Employee {
+            id: u32,
+            name: String,
+        }
+
+        tokio_test::block_on(async {
+            let server = MockServer::responses([
+                "{\"status\":true,\"payload\":\"{\\\"id\\\":1,\\\"name\\\":\\\"[draft]\\\"}\",\"error\":null}\n",
+                "{\"status\":false,\"payload\":null,\"error\":\"Key not found\"}\n",
+            ])
+            .await;
+
+            let engine = server.engine();
+            let keyspace = PersistentKeyspace::new("employees", &engine);
+
+            let found: Option<Employee> = keyspace
+                .get_value_as(Some("1"), None, ReadOptions::new())
+                .await
+                .unwrap();
+            let missing: Result<Option<Employee>, MontycatClientError> = keyspace
+                .get_value_as(Some("2"), None, ReadOptions::new())
+                .await;
+
+            assert_eq!(
+                found,
+                Some(Employee {
+                    id: 1,
+                    name: "[draft]".into()
+                })
+            );
+            assert_eq!(missing.unwrap_err().message(), "Key not found");
+            server.requests().await;
+        });
+    }
+
+    #[cfg(not(feature = "tls"))]
+    #[test]
+    fn test_get_bulk_as_key_included_returns_keyed_values() {
+        use crate::{Keyed, test_support::MockServer};
+
+        tokio_test::block_on(async {
+            let server = MockServer::responses([
+                "{\"status\":true,\"payload\":{\"1\":\"Monty\",\"2\":\"Cat\"},\"error\":null}\n",
+            ])
+            .await;
+
+            let engine = server.engine();
+            let keyspace = PersistentKeyspace::new("employees", &engine);
+
+            let mut names: Vec<Keyed<String>> = keyspace
+                .get_bulk_as(
+                    Some(vec!["1".into(), "2".into()]),
+                    None,
+                    ReadOptions::new().key_included(),
+                    None,
+                    None,
+                    None,
+                )
+                .await
+                .unwrap();
+            names.sort_by(|a, b| a.key.cmp(&b.key));
+
+            let request: String = server.requests().await.remove(0);
+            assert!(request.contains("\"key_included\":true"));
+            assert_eq!(names[0].key, "1");
+            assert_eq!(names[1].value, "Cat");
+        });
+    }
+
+    #[cfg(not(feature = "tls"))]
+    #[test]
+    fn test_get_value_as_pointers_metadata_returns_metadata() {
+        use crate::{WithPointerMetadata, test_support::MockServer};
+
+        tokio_test::block_on(async {
+            let server = MockServer::responses([
+                r#"{"status":true,"payload":{"name":"Monty","pointers":{"boss":{"keyspace":"staff","key":"9"}}},"error":null}"#,
+            ])
+            .await;
+
+            let engine = server.engine();
+            let keyspace = PersistentKeyspace::new("employees", &engine);
+
+            let employee: Option<WithPointerMetadata<serde_json::Value>> = keyspace
+                .get_value_as(Some("1"), None, ReadOptions::new().pointers_metadata())
+                .await
+                .unwrap();
+            let employee: WithPointerMetadata<serde_json::Value> = employee.unwrap();
+
+            let request: serde_json::Value = server.json_requests().await.remove(0);
+            assert_eq!(request["pointers_metadata"], true);
+            assert_eq!(request["key_included"], false);
+            assert_eq!(employee.value["name"], "Monty");
+            assert_eq!(employee.pointers["boss"].key, "9");
+        });
+    }
+
+    #[test]
+    fn test_dyn_keyspace_holds_both_kinds() {
+        let engine = engine();
+        let keyspaces: Vec<Box<dyn DynKeyspace>> = vec![
+            Box::new(PersistentKeyspace::new("employees", &engine)),
+            Box::new(InMemoryKeyspace::new("sessions", &engine)),
+        ];
+
+        let names: Vec<(&str, bool)> = keyspaces
+            .iter()
+            .map(|keyspace| (keyspace.get_name(), keyspace.get_persistent()))
+            .collect();
+
+        assert_eq!(names, vec![("employees", true), ("sessions", false)]);
+    }
+
+    #[test]
+    fn test_dyn_keyspace_shared_state() {
+        let keyspace: std::sync::Arc<dyn DynKeyspace> =
+            std::sync::Arc::new(InMemoryKeyspace::new("sessions", &engine()));
+
+        let result = tokio_test::block_on(keyspace.update_bulk(Vec::new(), Vec::new()));
+        assert!(matches!(
+            result,
+            Err(MontycatClientError::ClientNoValidInputProvided)
+        ));
+    }
+
+    #[test]
+    fn test_dyn_keyspace_forwards_validation() {
+        let keyspace: Box<dyn DynKeyspace> =
+            Box::new(PersistentKeyspace::new("employees", &engine()));
+
+        let result = tokio_test::block_on(keyspace.delete_key(Some("1"), Some("custom")));
+        assert!(matches!(
+            result,
+            Err(MontycatClientError::ClientSelectedBothKeyAndCustomKey)
+        ));
+    }
+}
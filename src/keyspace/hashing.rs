@@ -0,0 +1,377 @@
+use super::{pubtrait::Keyspace, resolver::stored_document};
+use crate::{MontycatClientError, codec::Codec, response::structure::MontycatResponse};
+use serde_json::{Map, Value, json};
+use std::{collections::HashMap, fmt::Display};
+use xxhash_rust::{xxh3::xxh3_64, xxh3::xxh3_128, xxh32::xxh32};
+
+/// Field under which a value stores its original custom key when collision detection is enabled.
+pub const CUSTOM_KEY_FIELD: &str = "__custom_key";
+
+/// Scheme used to turn a custom key into the key the value is stored under.
+///
+/// Each scheme has a version number, so the scheme a keyspace was written with can be recorded
+/// and restored with `KeyHashing::from_version`. Keys written with one scheme cannot be read with
+/// another, so a keyspace must keep the scheme it was created with.
+///
+/// # Variants
+/// - `Xxh32` : Version 1. 32-bit xxHash, the historical default. Collisions become likely after
+///   a few tens of thousands of custom keys.
+/// - `Xxh3_64` : Version 2. 64-bit xxh3.
+/// - `Xxh3_128` : Version 3. 128-bit xxh3, the same width as keys generated by the server.
+///
+/// # Examples
+///
+/// ```rust, ignore
+/// let keyspace = PersistentKeyspace::new("users", &engine)
+///     .with_key_hashing(KeyHashing::Xxh3_128)
+///     .with_collision_detection(true);
+/// ```
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum KeyHashing {
+    #[default]
+    Xxh32,
+    Xxh3_64,
+    Xxh3_128,
+}
+
+impl KeyHashing {
+    /// The latest scheme, recommended for new keyspaces.
+    pub const LATEST: KeyHashing = KeyHashing::Xxh3_128;
+
+    /// Returns the version number of the scheme.
+    pub const fn version(self) -> u8 {
+        match self {
+            KeyHashing::Xxh32 => 1,
+            KeyHashing::Xxh3_64 => 2,
+            KeyHashing::Xxh3_128 => 3,
+        }
+    }
+
+    /// Returns the scheme with the given version number, if there is one.
+    pub const fn from_version(version: u8) -> Option<KeyHashing> {
+        match version {
+            1 => Some(KeyHashing::Xxh32),
+            2 => Some(KeyHashing::Xxh3_64),
+            3 => Some(KeyHashing::Xxh3_128),
+            _ => None,
+        }
+    }
+
+    /// Hashes a custom key (integer, string, ...) into the key its value is stored under.
+    ///
+    /// # Arguments
+    /// - `custom_key: T` : The custom key.
+    ///
+    /// # Returns
+    /// - `String` : The digest, as a decimal string.
+    ///
+    pub fn hash<T: Display>(self, custom_key: T) -> String {
+        let custom_key: String = custom_key.to_string();
+        let bytes: &[u8] = custom_key.as_bytes();
+
+        match self {
+            KeyHashing::Xxh32 => xxh32(bytes, 0).to_string(),
+            KeyHashing::Xxh3_64 => xxh3_64(bytes).to_string(),
+            KeyHashing::Xxh3_128 => xxh3_128(bytes).to_string(),
+        }
+    }
+}
+
+/// Adds the original custom key to a serialized value, so reads can detect collisions.
+///
+/// # Errors
+/// - `MontycatClientError::ClientValueParsingError` : If the value is not a JSON object.
+///
+pub(crate) fn embed_custom_key(
+    value: String,
+    custom_key: &str,
+) -> Result<String, MontycatClientError> {
+    let mut object: Map<String, Value> = serde_json::from_str(&value).map_err(|_| {
+        MontycatClientError::ClientValueParsingError(
+            "Collision detection requires values that serialize to a JSON object".into(),
+        )
+    })?;
+
+    object.insert(CUSTOM_KEY_FIELD.into(), Value::String(custom_key.into()));
+
+    serde_json::to_string(&object)
+        .map_err(|e| MontycatClientError::ClientValueParsingError(e.to_string()))
+}
+
+/// Returns the key an update is sent under and the serialized value to send.
+///
+/// A custom key is hashed with the keyspace's `KeyHashing`. With collision detection, an update by
+/// custom key embeds it in the value again, so the value still passes verification after the update.
+///
+/// # Errors
+/// - `MontycatClientError::ClientNoValidInputProvided` : If neither or both of `key` and `custom_key` are given.
+/// - `MontycatClientError::ClientValueParsingError` : If collision detection is on and the value is not a JSON object.
+///
+pub(crate) fn update_target<K: Keyspace>(
+    keyspace: &K,
+    key: Option<String>,
+    custom_key: Option<String>,
+    value: String,
+) -> Result<(String, String), MontycatClientError> {
+    match (key, custom_key) {
+        (Some(key), None) => Ok((key, value)),
+        (None, Some(custom_key)) => {
+            let value: String = if keyspace.get_collision_detection() {
+                embed_custom_key(value, &custom_key)?
+            } else {
+                value
+            };
+
+            Ok((keyspace.get_key_hashing().hash(&custom_key), value))
+        }
+        _ => Err(MontycatClientError::ClientNoValidInputProvided),
+    }
+}
+
+/// Checks that every value in a read response was stored under one of the requested custom keys.
+///
+/// Values without an embedded custom key were written without collision detection and pass.
+/// Failed responses are returned unchanged.
+///
+/// # Errors
+/// - `MontycatClientError::ClientCustomKeyCollision` : If a value was stored under another custom
+///   key, carrying that key.
+///
+pub(crate) fn verify_custom_keys(
+    response: Option<Vec<u8>>,
+    custom_keys: &[&str],
+) -> Result<Option<Vec<u8>>, MontycatClientError> {
+    let Some(bytes) = response else {
+        return Ok(None);
+    };
+
+    let parsed: MontycatResponse<Value> = MontycatResponse::from_slice(&bytes)?;
+
+    if parsed.status {
+        check_stored_keys(&parsed.payload, custom_keys, true)?;
+    }
+
+    Ok(Some(bytes))
+}
+
+/// Checks the values of a bulk read made with `key_included` against the custom keys their stored
+/// keys were hashed from.
+///
+/// Only values stored under the hash of a requested custom key are checked, so values read by plain
+/// key in the same request pass. Unless the caller asked for the keys, they are removed again and
+/// the values are returned as a list, the shape of a read without `key_included`. Failed responses
+/// are returned unchanged.
+///
+/// # Arguments
+/// - `response: Option<Vec<u8>>` : The response of the read.
+/// - `custom_keys: &HashMap<String, String>` : The requested custom keys, by their hash.
+/// - `key_included: bool` : Whether the caller asked for the keys.
+///
+/// # Errors
+/// - `MontycatClientError::ClientCustomKeyCollision` : If a value stored under the hash of a custom
+///   key was written under another custom key, carrying that key.
+/// - `MontycatClientError::ClientValueParsingError` : If the response is not a keyed read response.
+///
+pub(crate) fn verify_hashed_custom_keys(
+    response: Option<Vec<u8>>,
+    custom_keys: &HashMap<String, String>,
+    key_included: bool,
+) -> Result<Option<Vec<u8>>, MontycatClientError> {
+    let Some(bytes) = response else {
+        return Ok(None);
+    };
+
+    let codec: Codec = Codec::detect(&bytes);
+    let mut envelope: Map<String, Value> = codec.decode(&mut bytes.clone())?;
+
+    if envelope.get("status") != Some(&Value::Bool(true)) {
+        return Ok(Some(bytes));
+    }
+
+    let entries: Vec<(String, Value)> =
+        keyed_entries(envelope.remove("payload").unwrap_or(Value::Null))?;
+
+    for (key, value) in &entries {
+        let Some(custom_key) = custom_keys.get(key) else {
+            continue;
+        };
+
+        if let Value::Object(document) = stored_document(value.clone())
+            && let Some(stored) = document.get(CUSTOM_KEY_FIELD)
+            && stored.as_str() != Some(custom_key.as_str())
+        {
+            return Err(MontycatClientError::ClientCustomKeyCollision(
+                stored.as_str().unwrap_or_default().into(),
+            ));
+        }
+    }
+
+    let payload: Value = if key_included {
+        Value::Array(
+            entries
+                .into_iter()
+                .map(|(key, value)| json!({ "key": key, "value": value }))
+                .collect(),
+        )
+    } else {
+        Value::Array(entries.into_iter().map(|(_, value)| value).collect())
+    };
+    envelope.insert("payload".into(), payload);
+
+    Ok(Some(codec.encode_body(&envelope)?))
+}
+
+/// Returns the keys and values of a `key_included` payload: a map from key to value, or a list of
+/// `{"key", "value"}` objects or single-entry maps.
+fn keyed_entries(payload: Value) -> Result<Vec<(String, Value)>, MontycatClientError> {
+    let invalid = || {
+        MontycatClientError::ClientValueParsingError(
+            "Expected a list of keyed values or a map from key to value".into(),
+        )
+    };
+
+    match payload {
+        Value::Null => Ok(Vec::new()),
+        Value::Object(entries) => Ok(entries.into_iter().collect()),
+        Value::Array(items) => items
+            .into_iter()
+            .map(|item| {
+                let Value::Object(mut item) = item else {
+                    return Err(invalid());
+                };
+
+                match (item.remove("key"), item.remove("value")) {
+                    (Some(Value::String(key)), Some(value)) => Ok((key, value)),
+                    (Some(Value::Number(key)), Some(value)) => Ok((key.to_string(), value)),
+                    (None, None) if item.len() == 1 => Ok(item.into_iter().next().unwrap()),
+                    _ => Err(invalid()),
+                }
+            })
+            .collect(),
+        _ => Err(invalid()),
+    }
+}
+
+fn check_stored_keys(
+    payload: &Value,
+    custom_keys: &[&str],
+    descend: bool,
+) -> Result<(), MontycatClientError> {
+    match payload {
+        Value::Object(object) => match object.get(CUSTOM_KEY_FIELD) {
+            Some(stored) => match stored.as_str() {
+                Some(stored) if custom_keys.contains(&stored) => Ok(()),
+                _ => Err(MontycatClientError::ClientCustomKeyCollision(
+                    stored.as_str().unwrap_or_default().into(),
+                )),
+            },
+            // Values returned with `key_included` are keyed by their hash.
+            None if descend => object
+                .values()
+                .try_for_each(|value| check_stored_keys(value, custom_keys, false)),
+            None => Ok(()),
+        },
+        Value::Array(values) if descend => values
+            .iter()
+            .try_for_each(|value| check_stored_keys(value, custom_keys, false)),
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_versions_round_trip_and_xxh32_stays_compatible() {
+        for hashing in [KeyHashing::Xxh32, KeyHashing::Xxh3_64, KeyHashing::Xxh3_128] {
+            assert_eq!(KeyHashing::from_version(hashing.version()), Some(hashing));
+        }
+
+        assert_eq!(KeyHashing::from_version(0), None);
+        assert_eq!(
+            KeyHashing::default().hash("a@b.c"),
+            xxh32(b"a@b.c", 0).to_string()
+        );
+        assert_eq!(
+            KeyHashing::Xxh3_128.hash("a@b.c").parse::<u128>().unwrap(),
+            xxh3_128(b"a@b.c")
+        );
+        assert_ne!(KeyHashing::Xxh3_64.hash(42), KeyHashing::Xxh3_64.hash(43));
+    }
+
+    #[test]
+    fn test_embedded_custom_key_is_verified() {
+        let value: String = embed_custom_key(r#"{"name":"Monty"}"#.into(), "monty").unwrap();
+        let escaped: String = serde_json::to_string(&value).unwrap();
+        let response = |body: &str| {
+            Some(format!(r#"{{"status":true,"payload":{},"error":null}}"#, body).into_bytes())
+        };
+
+        assert!(embed_custom_key("[1,2]".into(), "monty").is_err());
+        assert!(verify_custom_keys(response(&escaped), &["monty"]).is_ok());
+        assert!(verify_custom_keys(response(r#"{"name":"Legacy"}"#), &["monty"]).is_ok());
+        assert!(verify_custom_keys(response(&format!("[{}]", escaped)), &["a", "monty"]).is_ok());
+
+        let collision = verify_custom_keys(response(&escaped), &["other"]).unwrap_err();
+        assert!(matches!(
+            collision,
+            MontycatClientError::ClientCustomKeyCollision(ref key) if key == "monty"
+        ));
+    }
+
+    #[cfg(not(feature = "tls"))]
+    #[test]
+    fn test_bulk_read_only_verifies_values_of_requested_custom_keys() {
+        use crate::{Keyspace, PersistentKeyspace, ReadOptions, test_support::MockServer};
+
+        tokio_test::block_on(async {
+            let alice: String = KeyHashing::Xxh3_128.hash("alice");
+            let stored = |name: &str, custom_key: &str| {
+                json!({ "name": name, CUSTOM_KEY_FIELD: custom_key }).to_string()
+            };
+            let response = |alice_owner: &str| {
+                json!({
+                    "status": true,
+                    "payload": { alice.clone(): stored("Alice", alice_owner), "7": stored("Bob", "bob") },
+                    "error": null,
+                })
+                .to_string()
+            };
+            let server = MockServer::responses([response("alice"), response("mallory")]).await;
+
+            let engine = server.engine();
+            let keyspace: PersistentKeyspace = PersistentKeyspace::new("users", &engine)
+                .with_key_hashing(KeyHashing::Xxh3_128)
+                .with_collision_detection(true);
+            let read = || {
+                keyspace.get_bulk_as::<Value, _>(
+                    Some(vec!["7".into()]),
+                    Some(vec!["alice".into()]),
+                    ReadOptions::new(),
+                    None,
+                    None,
+                    None,
+                )
+            };
+
+            let mut names: Vec<String> = read()
+                .await
+                .unwrap()
+                .into_iter()
+                .map(|value| value["name"].as_str().unwrap().to_owned())
+                .collect();
+            names.sort_unstable();
+            let collision: MontycatClientError = read().await.unwrap_err();
+
+            let requests: Vec<Value> = server.json_requests().await;
+            assert_eq!(requests[0]["key_included"], true);
+            assert_eq!(names, ["Alice", "Bob"]);
+            assert!(matches!(
+                collision,
+                MontycatClientError::ClientCustomKeyCollision(ref key) if key == "mallory"
+            ));
+        });
+    }
+}
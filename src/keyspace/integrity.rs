@@ -0,0 +1,1172 @@
+use super::{
+    dyntrait::DynKeyspace,
+    pubtrait::Keyspace,
+    registry::{KEY_REGISTRY_SUFFIX, registry_keyspaces},
+    resolver::{Target, as_target, get_keyed, store_keyspaces, stored_document},
+    structures::{inmemory::InMemoryKeyspace, persistent::PersistentKeyspace},
+};
+use crate::{
+    MontycatClientError,
+    engine::structure::Engine,
+    response::{
+        structure::parse_payload,
+        typed::{Keyed, ReadOptions},
+    },
+    tools::structure::{Limit, Pointer},
+};
+use serde_json::{Map, Value};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet, VecDeque};
+
+/// What `Keyspace::delete_with_policy` does with the values pointing to the deleted key.
+///
+/// # Variants
+/// - `Restrict` : Fails with `ClientDeleteRestricted` if any value depends on the key.
+/// - `Cascade` : Also deletes the dependent values, and the values depending on them, across keyspaces.
+/// - `Nullify` : Removes the pointers to the key from the dependent values. Pointer fields are set
+///   to null, and the key is dropped from pointer lists and maps.
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DeletePolicy {
+    Restrict,
+    Cascade,
+    Nullify,
+}
+
+/// What a `Keyspace::delete_with_policy` call affected, or would affect in a dry run.
+///
+/// # Fields
+/// - `policy: DeletePolicy` : The policy the delete used.
+/// - `dry_run: bool` : Whether nothing was written.
+/// - `deleted: Vec<Pointer>` : The deleted values, starting with the requested key.
+/// - `nullified: Vec<Pointer>` : The values whose pointers to the key were removed.
+///
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DeleteReport {
+    pub policy: DeletePolicy,
+    pub dry_run: bool,
+    pub deleted: Vec<Pointer>,
+    pub nullified: Vec<Pointer>,
+}
+
+/// Deletes `key` from `keyspace`, handling its dependents according to `policy`.
+///
+/// Dependents are deleted before the key, deepest first, with one `delete_bulk` per keyspace.
+/// Dependent keyspaces are addressed as persistent or in-memory as listed by
+/// `get_structure_available`, and deleted dependents are removed from their keyspace's key
+/// registry if `KEY_REGISTRY_CATALOG` marks one.
+pub(crate) async fn delete_with_policy<K: Keyspace>(
+    keyspace: &K,
+    key: String,
+    policy: DeletePolicy,
+    dry_run: bool,
+) -> Result<DeleteReport, MontycatClientError> {
+    let engine: &Engine = keyspace.get_engine();
+    let root: Pointer = Pointer::new(keyspace.get_name(), &key);
+    let dependents: Vec<Pointer> =
+        parse_dependents(keyspace.list_all_depending_keys(&key, None).await)?
+            .into_iter()
+            .filter(|dependent| *dependent != root)
+            .collect();
+
+    let mut report: DeleteReport = DeleteReport {
+        policy,
+        dry_run,
+        deleted: Vec::new(),
+        nullified: Vec::new(),
+    };
+
+    match policy {
+        DeletePolicy::Restrict if !dependents.is_empty() => {
+            return Err(MontycatClientError::ClientDeleteRestricted(format!(
+                "{} values depend on key '{}' in keyspace '{}'",
+                dependents.len(),
+                key,
+                keyspace.get_name()
+            )));
+        }
+        DeletePolicy::Restrict => {}
+        DeletePolicy::Cascade => {
+            let persistence: HashMap<String, bool> =
+                store_keyspaces(engine).await?.into_iter().collect();
+            let cascade: Vec<Pointer> =
+                collect_cascade(engine, &persistence, &root, dependents).await?;
+            if !dry_run {
+                delete_cascade(engine, &persistence, &cascade).await?;
+            }
+            report.deleted = cascade;
+        }
+        DeletePolicy::Nullify => {
+            let persistence: HashMap<String, bool> =
+                store_keyspaces(engine).await?.into_iter().collect();
+            report.nullified = nullify(engine, &persistence, &root, dependents, dry_run).await?;
+        }
+    }
+
+    if !dry_run {
+        parse_payload::<Value>(keyspace.delete_key(Some(&key), None).await)?;
+    }
+
+    report.deleted.insert(0, root);
+    Ok(report)
+}
+
+/// Parses the payload of `list_all_depending_keys`: a list of pointers, as `[keyspace, key]` or
+/// `{"keyspace", "key"}`, or a map of keyspaces to their dependent keys.
+pub(crate) fn parse_dependents(
+    response: Result<Option<Vec<u8>>, MontycatClientError>,
+) -> Result<Vec<Pointer>, MontycatClientError> {
+    match stored_document(parse_payload::<Value>(response)?) {
+        Value::Null => Ok(Vec::new()),
+        Value::Array(items) => items
+            .into_iter()
+            .map(|item| {
+                serde_json::from_value::<Pointer>(item)
+                    .map_err(|e| MontycatClientError::ClientValueParsingError(e.to_string()))
+            })
+            .collect(),
+        Value::Object(entries) => Ok(entries
+            .into_iter()
+            .flat_map(|(keyspace, keys)| {
+                let keys: Vec<Value> = match keys {
+                    Value::Array(keys) => keys,
+                    key => vec![key],
+                };
+                keys.into_iter()
+                    .filter_map(|key| key.as_str().map(|key| Pointer::new(&keyspace, key)))
+                    .collect::<Vec<Pointer>>()
+            })
+            .collect()),
+        other => Err(MontycatClientError::ClientValueParsingError(format!(
+            "Unexpected dependents payload: {}",
+            other
+        ))),
+    }
+}
+
+/// Returns a handle on a dependent keyspace, persistent unless `persistence` lists it as in-memory.
+fn dependent_keyspace(
+    engine: &Engine,
+    persistence: &HashMap<String, bool>,
+    name: &str,
+    key_registry: bool,
+) -> Box<dyn DynKeyspace> {
+    if persistence.get(name) == Some(&false) {
+        Box::new(InMemoryKeyspace::new(name, engine).with_key_registry(key_registry))
+    } else {
+        Box::new(PersistentKeyspace::new(name, engine).with_key_registry(key_registry))
+    }
+}
+
+/// Walks the dependents of `root` breadth first, reading each value's dependents once.
+async fn collect_cascade(
+    engine: &Engine,
+    persistence: &HashMap<String, bool>,
+    root: &Pointer,
+    dependents: Vec<Pointer>,
+) -> Result<Vec<Pointer>, MontycatClientError> {
+    let mut visited: HashSet<Pointer> = HashSet::from([root.clone()]);
+    let mut queue: VecDeque<Pointer> = dependents.into();
+    let mut cascade: Vec<Pointer> = Vec::new();
+
+    while let Some(dependent) = queue.pop_front() {
+        if !visited.insert(dependent.clone()) {
+            continue;
+        }
+
+        let nested: Vec<Pointer> = parse_dependents(
+            dependent_keyspace(engine, persistence, &dependent.keyspace, false)
+                .list_all_depending_keys(&dependent.key, None)
+                .await,
+        )?;
+
+        queue.extend(nested);
+        cascade.push(dependent);
+    }
+
+    Ok(cascade)
+}
+
+/// Deletes the cascade deepest first, with one `delete_bulk` per keyspace, through the key
+/// registry of the keyspaces that have a marked one.
+async fn delete_cascade(
+    engine: &Engine,
+    persistence: &HashMap<String, bool>,
+    cascade: &[Pointer],
+) -> Result<(), MontycatClientError> {
+    let registries: HashSet<String> = registry_keyspaces(engine).await?;
+    let mut batches: Vec<(&str, Vec<String>)> = Vec::new();
+
+    for dependent in cascade.iter().rev() {
+        match batches
+            .iter_mut()
+            .find(|(keyspace, _)| *keyspace == dependent.keyspace)
+        {
+            Some((_, keys)) => keys.push(dependent.key.clone()),
+            None => batches.push((&dependent.keyspace, vec![dependent.key.clone()])),
+        }
+    }
+
+    for (keyspace, keys) in batches {
+        let key_registry: bool =
+            registries.contains(&format!("{}{}", keyspace, KEY_REGISTRY_SUFFIX));
+
+        parse_payload::<Value>(
+            dependent_keyspace(engine, persistence, keyspace, key_registry)
+                .delete_bulk(Some(keys), None)
+                .await,
+        )?;
+    }
+
+    Ok(())
+}
+
+/// Removes the pointers to `target` from its dependents, returning the dependents that held one.
+async fn nullify(
+    engine: &Engine,
+    persistence: &HashMap<String, bool>,
+    target: &Pointer,
+    dependents: Vec<Pointer>,
+    dry_run: bool,
+) -> Result<Vec<Pointer>, MontycatClientError> {
+    let target: Target = (target.keyspace.clone(), target.key.clone());
+    let mut by_keyspace: BTreeMap<String, BTreeSet<String>> = BTreeMap::new();
+
+    for dependent in dependents {
+        by_keyspace
+            .entry(dependent.keyspace)
+            .or_default()
+            .insert(dependent.key);
+    }
+
+    let mut nullified: Vec<Pointer> = Vec::new();
+
+    for (keyspace, keys) in by_keyspace {
+        let dependent: Box<dyn DynKeyspace> =
+            dependent_keyspace(engine, persistence, &keyspace, false);
+        let values: Vec<Keyed<Value>> = get_keyed(
+            engine,
+            &keyspace,
+            dependent.get_persistent(),
+            keys.into_iter().collect(),
+        )
+        .await?;
+
+        for keyed in values {
+            let Some(update) = rewrite_pointers(stored_document(keyed.value), |_, pointer| {
+                (*pointer == target).then_some(None)
+            }) else {
+                continue;
+            };
+
+            if !dry_run {
+                parse_payload::<Value>(
+                    dependent
+                        .update_value(Some(keyed.key.clone()), None, update)
+                        .await,
+                )?;
+            }
+
+            nullified.push(Pointer::new(&keyspace, &keyed.key));
+        }
+    }
+
+    Ok(nullified)
+}
+
+/// Outcome of rewriting one encoded pointer, or a list or map of them.
+enum Rewritten {
+    Kept,
+    Changed,
+    Removed,
+}
+
+/// Rewrites the pointers of a stored document, returning the update to send if any changed.
+///
+/// `rewrite` receives the field and the target of each pointer, and returns `None` to keep it,
+/// `Some(None)` to remove it or `Some(Some(target))` to point it to `target`. The update replaces
+/// the `pointers` section and sets the fields whose pointer was removed to null.
+fn rewrite_pointers<F>(document: Value, rewrite: F) -> Option<Value>
+where
+    F: Fn(&str, &Target) -> Option<Option<Target>>,
+{
+    let Value::Object(mut document) = document else {
+        return None;
+    };
+    let Some(Value::Object(mut pointers)) = document.remove("pointers") else {
+        return None;
+    };
+
+    let mut update: Map<String, Value> = Map::new();
+    let mut changed: bool = false;
+
+    pointers.retain(
+        |field, encoded| match rewrite_encoded(encoded, field, &rewrite) {
+            Rewritten::Kept => true,
+            Rewritten::Changed => {
+                changed = true;
+                true
+            }
+            Rewritten::Removed => {
+                update.insert(field.clone(), Value::Null);
+                changed = true;
+                false
+            }
+        },
+    );
+
+    if !changed {
+        return None;
+    }
+
+    update.insert("pointers".into(), Value::Object(pointers));
+    Some(Value::Object(update))
+}
+
+fn rewrite_encoded<F>(encoded: &mut Value, field: &str, rewrite: &F) -> Rewritten
+where
+    F: Fn(&str, &Target) -> Option<Option<Target>>,
+{
+    if let Some(target) = as_target(encoded) {
+        return match rewrite(field, &target) {
+            None => Rewritten::Kept,
+            Some(None) => Rewritten::Removed,
+            Some(Some((keyspace, key))) => {
+                *encoded = Value::Array(vec![Value::String(keyspace), Value::String(key)]);
+                Rewritten::Changed
+            }
+        };
+    }
+
+    let mut changed: bool = false;
+    let mut keep = |rewritten: Rewritten| match rewritten {
+        Rewritten::Kept => true,
+        Rewritten::Changed => {
+            changed = true;
+            true
+        }
+        Rewritten::Removed => {
+            changed = true;
+            false
+        }
+    };
+
+    match encoded {
+        Value::Array(items) => items.retain_mut(|item| keep(rewrite_encoded(item, field, rewrite))),
+        Value::Object(entries) => {
+            entries.retain(|_, entry| keep(rewrite_encoded(entry, field, rewrite)))
+        }
+        _ => {}
+    }
+
+    if changed {
+        Rewritten::Changed
+    } else {
+        Rewritten::Kept
+    }
+}
+
+/// Returns the field and target of every pointer in the `pointers` section of a stored document.
+fn pointer_targets(document: &Value) -> Vec<(String, Target)> {
+    fn collect(encoded: &Value, field: &str, targets: &mut Vec<(String, Target)>) {
+        if let Some(target) = as_target(encoded) {
+            targets.push((field.to_owned(), target));
+            return;
+        }
+
+        match encoded {
+            Value::Array(items) => items.iter().for_each(|item| collect(item, field, targets)),
+            Value::Object(entries) => entries
+                .values()
+                .for_each(|entry| collect(entry, field, targets)),
+            _ => {}
+        }
+    }
+
+    let mut targets: Vec<(String, Target)> = Vec::new();
+
+    if let Some(Value::Object(pointers)) = document.get("pointers") {
+        for (field, encoded) in pointers {
+            collect(encoded, field, &mut targets);
+        }
+    }
+
+    targets
+}
+
+/// What `audit_store_with` does with the dangling pointers it finds.
+///
+/// # Variants
+/// - `Report` : Only reports them.
+/// - `Remove` : Removes them, as `DeletePolicy::Nullify` does. Pointer fields are set to null, and
+///   the pointer is dropped from pointer lists and maps.
+/// - `Repair(fn)` : Points them to the pointer the function returns, or removes them if it returns `None`.
+///
+#[derive(Debug, Clone, Copy)]
+pub enum DanglingAction {
+    Report,
+    Remove,
+    Repair(fn(&DanglingPointer) -> Option<Pointer>),
+}
+
+/// Options of `audit_store_with`.
+///
+/// # Fields
+/// - `page_size: usize` : How many values to read per request. Defaults to 500.
+/// - `action: DanglingAction` : What to do with dangling pointers. Defaults to `DanglingAction::Report`.
+///
+#[derive(Debug, Clone, Copy)]
+pub struct AuditOptions {
+    pub page_size: usize,
+    pub action: DanglingAction,
+}
+
+impl Default for AuditOptions {
+    fn default() -> Self {
+        Self {
+            page_size: 500,
+            action: DanglingAction::Report,
+        }
+    }
+}
+
+/// A pointer whose target does not exist.
+///
+/// # Fields
+/// - `keyspace: String` : The keyspace of the value holding the pointer.
+/// - `key: String` : The key of the value holding the pointer.
+/// - `field: String` : The field holding the pointer, directly or in a list or map.
+/// - `target: Pointer` : The missing target.
+///
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DanglingPointer {
+    pub keyspace: String,
+    pub key: String,
+    pub field: String,
+    pub target: Pointer,
+}
+
+/// Result of `audit_store`.
+///
+/// # Fields
+/// - `keyspaces: usize` : How many keyspaces were audited.
+/// - `values: usize` : How many values were read.
+/// - `pointers: usize` : How many pointers were checked.
+/// - `dangling: Vec<DanglingPointer>` : The pointers whose target does not exist.
+/// - `unverified: Vec<DanglingPointer>` : The pointers whose target could not be checked because
+///   the server rejected the read of its keyspace. They are never rewritten.
+/// - `updated: Vec<Pointer>` : The values rewritten by `DanglingAction::Remove` or `Repair`.
+///
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct AuditReport {
+    pub keyspaces: usize,
+    pub values: usize,
+    pub pointers: usize,
+    pub dangling: Vec<DanglingPointer>,
+    pub unverified: Vec<DanglingPointer>,
+    pub updated: Vec<Pointer>,
+}
+
+impl AuditReport {
+    /// Returns true if every pointer was checked and none is dangling.
+    pub fn is_clean(&self) -> bool {
+        self.dangling.is_empty() && self.unverified.is_empty()
+    }
+
+    /// Counts the dangling pointers per keyspace and field.
+    ///
+    /// # Returns
+    /// - `BTreeMap<(&str, &str), usize>` : The number of dangling pointers per `(keyspace, field)`.
+    ///
+    pub fn per_field(&self) -> BTreeMap<(&str, &str), usize> {
+        let mut counts: BTreeMap<(&str, &str), usize> = BTreeMap::new();
+
+        for dangling in &self.dangling {
+            *counts
+                .entry((dangling.keyspace.as_str(), dangling.field.as_str()))
+                .or_default() += 1;
+        }
+
+        counts
+    }
+}
+
+/// Checks every pointer in the store of `engine` and reports the ones whose target does not exist.
+///
+/// Same as `audit_store_with` with the default `AuditOptions`, which only report.
+///
+/// # Examples
+///
+/// ```rust, ignore
+/// let report: AuditReport = integrity::audit_store(&engine).await?;
+///
+/// for ((keyspace, field), count) in report.per_field() {
+///     println!("{}.{}: {} dangling pointers", keyspace, field, count);
+/// }
+/// ```
+///
+/// # Errors
+/// - Every error `audit_store_with` returns.
+///
+pub async fn audit_store(engine: &Engine) -> Result<AuditReport, MontycatClientError> {
+    audit_store_with(engine, AuditOptions::default()).await
+}
+
+/// Checks every pointer in the store of `engine`, handling dangling pointers according to `options`.
+///
+/// Walks the keyspaces listed by `get_structure_available`, reads their values in pages of
+/// `options.page_size` and checks the targets of their pointers with one `get_bulk` per target
+/// keyspace and page. Each target is checked once per audit. Targets in keyspaces the store does
+/// not list count as dangling. Targets the server refuses to read are reported as unverified and
+/// left untouched. Values are rewritten with one `update_bulk` per page. Key registry keyspaces marked in `KEY_REGISTRY_CATALOG`, and the catalog
+/// itself, are skipped.
+///
+/// # Arguments
+/// - `engine: &Engine` : The engine of the store to audit.
+/// - `options: AuditOptions` : The page size and what to do with dangling pointers.
+///
+/// # Returns
+/// - `Result<AuditReport, MontycatClientError>` : The dangling and unverified pointers and the
+///   rewritten values.
+///
+/// # Examples
+///
+/// ```rust, ignore
+/// let options: AuditOptions = AuditOptions {
+///     action: DanglingAction::Remove,
+///     ..Default::default()
+/// };
+///
+/// let report: AuditReport = integrity::audit_store_with(&engine, options).await?;
+/// ```
+///
+/// # Errors
+/// - `MontycatClientError::ClientStoreNotSet` : If the store is not set in the engine.
+/// - `MontycatClientError::ClientServerError` : If the server rejects the structure lookup, or a
+///   read or update of an audited keyspace.
+///
+pub async fn audit_store_with(
+    engine: &Engine,
+    options: AuditOptions,
+) -> Result<AuditReport, MontycatClientError> {
+    let keyspaces: Vec<(String, bool)> = store_keyspaces(engine).await?;
+    let registries: HashSet<String> = registry_keyspaces(engine).await?;
+
+    let mut audit: Audit = Audit {
+        engine,
+        options,
+        persistence: keyspaces.iter().cloned().collect(),
+        existing: HashMap::new(),
+        report: AuditReport::default(),
+    };
+
+    for (name, persistent) in keyspaces {
+        if registries.contains(&name) {
+            continue;
+        }
+
+        if persistent {
+            audit
+                .keyspace(&PersistentKeyspace::new(&name, engine))
+                .await?;
+        } else {
+            audit
+                .keyspace(&InMemoryKeyspace::new(&name, engine))
+                .await?;
+        }
+    }
+
+    Ok(audit.report)
+}
+
+/// State of a running `audit_store_with`.
+struct Audit<'a> {
+    engine: &'a Engine,
+    options: AuditOptions,
+    /// Whether each keyspace of the store is persistent.
+    persistence: HashMap<String, bool>,
+    /// Whether each checked target exists.
+    existing: HashMap<Target, TargetState>,
+    report: AuditReport,
+}
+
+impl Audit<'_> {
+    async fn keyspace<K: Keyspace>(&mut self, keyspace: &K) -> Result<(), MontycatClientError> {
+        let page_size: usize = self.options.page_size.max(1);
+        let mut start: usize = 0;
+
+        self.report.keyspaces += 1;
+
+        loop {
+            let page: Vec<Keyed<Value>> = keyspace
+                .lookup_values_where_as(
+                    Value::Object(Map::new()),
+                    Some(Limit::new(start, start + page_size)),
+                    ReadOptions::new().key_included(),
+                    None,
+                )
+                .await?;
+            let len: usize = page.len();
+
+            let documents: Vec<(String, Value)> = page
+                .into_iter()
+                .map(|keyed| (keyed.key, stored_document(keyed.value)))
+                .collect();
+
+            self.check_targets(documents.iter().flat_map(|(_, document)| {
+                pointer_targets(document)
+                    .into_iter()
+                    .map(|(_, target)| target)
+            }))
+            .await?;
+
+            let mut updates: Vec<HashMap<String, Value>> = Vec::new();
+
+            for (key, document) in documents {
+                self.report.values += 1;
+
+                let mut dangling: Vec<DanglingPointer> = Vec::new();
+                for (field, (target_keyspace, target_key)) in pointer_targets(&document) {
+                    self.report.pointers += 1;
+                    let state: TargetState =
+                        self.existing[&(target_keyspace.clone(), target_key.clone())];
+                    if state == TargetState::Present {
+                        continue;
+                    }
+
+                    let pointer: DanglingPointer = DanglingPointer {
+                        keyspace: keyspace.get_name().to_owned(),
+                        key: key.clone(),
+                        field,
+                        target: Pointer::new(&target_keyspace, &target_key),
+                    };
+
+                    match state {
+                        TargetState::Missing => dangling.push(pointer),
+                        _ => self.report.unverified.push(pointer),
+                    }
+                }
+
+                if dangling.is_empty() {
+                    continue;
+                }
+
+                let update: Option<Value> = match self.options.action {
+                    DanglingAction::Report => None,
+                    action => rewrite_pointers(document, |field, target| {
+                        let dangling: &DanglingPointer = dangling.iter().find(|dangling| {
+                            dangling.field == field
+                                && dangling.target.keyspace == target.0
+                                && dangling.target.key == target.1
+                        })?;
+
+                        match action {
+                            DanglingAction::Repair(repair) => Some(
+                                repair(dangling).map(|pointer| (pointer.keyspace, pointer.key)),
+                            ),
+                            _ => Some(None),
+                        }
+                    }),
+                };
+
+                if let Some(update) = update {
+                    updates.push(HashMap::from([(key.clone(), update)]));
+                    self.report
+                        .updated
+                        .push(Pointer::new(keyspace.get_name(), &key));
+                }
+
+                self.report.dangling.extend(dangling);
+            }
+
+            if !updates.is_empty() {
+                parse_payload::<Value>(keyspace.update_bulk(updates, Vec::new()).await)?;
+            }
+
+            if len < page_size {
+                return Ok(());
+            }
+
+            start += page_size;
+        }
+    }
+
+    /// Reads the targets not checked yet, with one `get_bulk` per target keyspace.
+    ///
+    /// Targets in keyspaces the store does not list do not exist. Targets the server refuses to read
+    /// are unverified, so their pointers are reported without being rewritten.
+    async fn check_targets(
+        &mut self,
+        targets: impl Iterator<Item = Target>,
+    ) -> Result<(), MontycatClientError> {
+        let mut by_keyspace: BTreeMap<String, BTreeSet<String>> = BTreeMap::new();
+
+        for (keyspace, key) in targets {
+            if !self.existing.contains_key(&(keyspace.clone(), key.clone())) {
+                by_keyspace.entry(keyspace).or_default().insert(key);
+            }
+        }
+
+        for (keyspace, keys) in by_keyspace {
+            let keys: Vec<String> = keys.into_iter().collect();
+            let found: Option<HashSet<String>> = match self.persistence.get(&keyspace) {
+                Some(persistent) => {
+                    existing_keys(self.engine, &keyspace, *persistent, keys.clone()).await?
+                }
+                None => Some(HashSet::new()),
+            };
+
+            for key in keys {
+                let state: TargetState = match &found {
+                    Some(found) if found.contains(&key) => TargetState::Present,
+                    Some(_) => TargetState::Missing,
+                    None => TargetState::Unverified,
+                };
+                self.existing.insert((keyspace.clone(), key), state);
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Whether a pointer target exists, as far as the audit could check.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum TargetState {
+    Present,
+    Missing,
+    /// The server rejected the read of the target keyspace.
+    Unverified,
+}
+
+/// Returns the keys among `keys` that hold a value, or None if the server refuses to read them.
+async fn existing_keys(
+    engine: &Engine,
+    keyspace: &str,
+    persistent: bool,
+    keys: Vec<String>,
+) -> Result<Option<HashSet<String>>, MontycatClientError> {
+    let values: Vec<Keyed<Value>> = match get_keyed(engine, keyspace, persistent, keys).await {
+        Ok(values) => values,
+        Err(MontycatClientError::ClientServerError(_)) => return Ok(None),
+        Err(error) => return Err(error),
+    };
+
+    Ok(Some(
+        values
+            .into_iter()
+            .filter(|keyed| !keyed.value.is_null())
+            .map(|keyed| keyed.key)
+            .collect(),
+    ))
+}
+
+#[cfg(all(test, not(feature = "tls")))]
+mod tests {
+    use super::*;
+    use crate::{KEY_REGISTRY_CATALOG, test_support::MockServer};
+    use serde_json::json;
+
+    /// `users/u1` <- `orders/o1`, `orders/o2`; `orders/o1` <- `invoices/i1` <- `users/u1`.
+    fn dependents(request: &Value) -> Value {
+        match (request["keyspace"].as_str(), request["key"].as_str()) {
+            (Some("users"), Some("u1")) => json!([["orders", "o1"], ["orders", "o2"]]),
+            (Some("orders"), Some("o1")) => json!({ "invoices": ["i1"] }),
+            (Some("invoices"), Some("i1")) => json!([{ "keyspace": "users", "key": "u1" }]),
+            _ => json!([]),
+        }
+    }
+
+    /// `orders` is in-memory and `invoices` keeps a marked key registry.
+    fn store(request: &Value) -> Option<Value> {
+        if request["raw"][0] == "get-structure-available" {
+            return Some(json!({
+                "store": {
+                    "persistent": ["users", "invoices", "invoices_key_registry", "__key_registries"],
+                    "in_memory": ["orders"],
+                },
+            }));
+        }
+
+        (request["keyspace"] == KEY_REGISTRY_CATALOG).then(|| {
+            json!([Value::String(
+                json!({ "keyspace": "invoices_key_registry" }).to_string()
+            )])
+        })
+    }
+
+    fn commands(requests: &[Value]) -> Vec<(&str, &str)> {
+        requests
+            .iter()
+            .map(|request| {
+                (
+                    request["command"].as_str().unwrap(),
+                    request["keyspace"].as_str().unwrap(),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_cascade_deletes_dependents_deepest_first() {
+        tokio_test::block_on(async {
+            let server = MockServer::payloads(10, |request| {
+                store(request).unwrap_or_else(|| match request["command"].as_str().unwrap() {
+                    "list_all_depending_keys" => dependents(request),
+                    _ => Value::Null,
+                })
+            })
+            .await;
+
+            let engine: Engine = server.engine();
+            let report: DeleteReport = PersistentKeyspace::new("users", &engine)
+                .delete_with_policy(Some("u1"), None, DeletePolicy::Cascade, false)
+                .await
+                .unwrap();
+            let requests: Vec<Value> = server.json_requests().await;
+
+            assert_eq!(
+                report.deleted,
+                [
+                    Pointer::new("users", "u1"),
+                    Pointer::new("orders", "o1"),
+                    Pointer::new("orders", "o2"),
+                    Pointer::new("invoices", "i1"),
+                ]
+            );
+            assert_eq!(
+                commands(&requests[6..]),
+                [
+                    ("delete_bulk", "invoices"),
+                    ("delete_bulk", "invoices_key_registry"),
+                    ("delete_bulk", "orders"),
+                    ("delete_key", "users"),
+                ]
+            );
+            assert_eq!(requests[7]["bulk_keys"], json!(["i1"]));
+            assert_eq!(requests[8]["bulk_keys"], json!(["o2", "o1"]));
+            assert_eq!(requests[8]["persistent"], false);
+        });
+    }
+
+    #[test]
+    fn test_dry_run_and_restrict_do_not_write() {
+        tokio_test::block_on(async {
+            let server = MockServer::payloads(6, |request| {
+                store(request).unwrap_or_else(|| dependents(request))
+            })
+            .await;
+
+            let engine: Engine = server.engine();
+            let users: PersistentKeyspace = PersistentKeyspace::new("users", &engine);
+            let report: DeleteReport = users
+                .delete_with_policy(Some("u1"), None, DeletePolicy::Cascade, true)
+                .await
+                .unwrap();
+            let restricted: MontycatClientError = users
+                .delete_with_policy(Some("u1"), None, DeletePolicy::Restrict, false)
+                .await
+                .unwrap_err();
+            let requests: Vec<Value> = server.json_requests().await;
+
+            assert!(report.dry_run);
+            assert_eq!(report.deleted.len(), 4);
+            assert!(matches!(
+                restricted,
+                MontycatClientError::ClientDeleteRestricted(_)
+            ));
+            assert!(requests.iter().all(|request| {
+                request["command"] == "list_all_depending_keys"
+                    || request["raw"][0] == "get-structure-available"
+            }));
+        });
+    }
+
+    #[test]
+    fn test_nullify_removes_pointers_to_the_key() {
+        tokio_test::block_on(async {
+            let server = MockServer::payloads(5, |request| {
+                if let Some(payload) = store(request) {
+                    return payload;
+                }
+
+                match request["command"].as_str().unwrap() {
+                    "list_all_depending_keys" => dependents(request),
+                    "get_bulk" => json!({
+                        "o1": json!({
+                            "id": 1,
+                            "pointers": {
+                                "owner": ["users", "u1"],
+                                "watchers": [["users", "u1"], ["users", "u2"]],
+                                "region": ["regions", "r1"],
+                            },
+                        }).to_string(),
+                        "o2": json!({ "id": 2, "pointers": { "owner": ["users", "u3"] } }).to_string(),
+                    }),
+                    _ => Value::Null,
+                }
+            }).await;
+
+            let engine: Engine = server.engine();
+            let report: DeleteReport = PersistentKeyspace::new("users", &engine)
+                .delete_with_policy(Some("u1"), None, DeletePolicy::Nullify, false)
+                .await
+                .unwrap();
+            let requests: Vec<Value> = server.json_requests().await;
+
+            assert_eq!(report.deleted, [Pointer::new("users", "u1")]);
+            assert_eq!(report.nullified, [Pointer::new("orders", "o1")]);
+            assert_eq!(
+                commands(&requests[2..]),
+                [
+                    ("get_bulk", "orders"),
+                    ("update_value", "orders"),
+                    ("delete_key", "users"),
+                ]
+            );
+            assert_eq!(requests[2]["persistent"], false);
+            assert_eq!(requests[3]["persistent"], false);
+
+            let update: Value =
+                serde_json::from_str(requests[3]["value"].as_str().unwrap()).unwrap();
+            assert_eq!(
+                update,
+                json!({
+                    "owner": null,
+                    "pointers": {
+                        "watchers": [["users", "u2"]],
+                        "region": ["regions", "r1"],
+                    },
+                })
+            );
+        });
+    }
+
+    fn audited(request: &Value) -> Value {
+        let stored = |document: Value| Value::String(document.to_string());
+
+        if request["raw"][0] == "get-structure-available" {
+            return json!({
+                "store": {
+                    "persistent": ["orders", "customers", "orders_key_registry", "__key_registries", "legacy_key_registry"],
+                    "in_memory": ["sessions"],
+                },
+            });
+        }
+
+        match (
+            request["command"].as_str().unwrap(),
+            request["keyspace"].as_str().unwrap(),
+            request["limit_output"]["start"].as_u64(),
+        ) {
+            ("lookup_values", "__key_registries", _) => {
+                json!([stored(json!({ "keyspace": "orders_key_registry" }))])
+            }
+            ("lookup_values", "sessions", _) => json!({
+                "s1": stored(json!({ "pointers": { "user": ["customers", "c9"] } })),
+            }),
+            ("lookup_values", "orders", Some(0)) => json!({
+                "o1": stored(json!({
+                    "id": 1,
+                    "pointers": {
+                        "customer": ["customers", "c1"],
+                        "reviewers": [["customers", "c1"], ["customers", "c9"]],
+                    },
+                })),
+                "o2": stored(json!({ "id": 2, "pointers": { "customer": ["customers", "c9"] } })),
+            }),
+            ("lookup_values", "orders", Some(2)) => json!({
+                "o3": stored(json!({ "id": 3, "pointers": { "customer": ["customers", "c1"] } })),
+            }),
+            ("get_bulk", "customers", _) => json!({
+                "c1": stored(json!({ "name": "Ann" })),
+                "c9": null,
+            }),
+            _ => json!({}),
+        }
+    }
+
+    #[test]
+    fn test_audit_store_reports_and_removes_dangling_pointers() {
+        tokio_test::block_on(async {
+            let server = MockServer::payloads(10, audited).await;
+
+            let engine: Engine = server.engine();
+            let options: AuditOptions = AuditOptions {
+                page_size: 2,
+                action: DanglingAction::Remove,
+            };
+            let report: AuditReport = audit_store_with(&engine, options).await.unwrap();
+            let requests: Vec<Value> = server.json_requests().await;
+
+            // Only the marked registry and the catalog are skipped, not every `_key_registry` name.
+            assert_eq!(
+                (report.keyspaces, report.values, report.pointers),
+                (4, 4, 6)
+            );
+            assert!(
+                requests
+                    .iter()
+                    .any(|request| request["keyspace"] == "legacy_key_registry")
+            );
+            assert!(
+                !requests
+                    .iter()
+                    .any(|request| request["keyspace"] == "orders_key_registry")
+            );
+            assert_eq!(
+                report.per_field().into_iter().collect::<Vec<_>>(),
+                [
+                    (("orders", "customer"), 1),
+                    (("orders", "reviewers"), 1),
+                    (("sessions", "user"), 1),
+                ]
+            );
+            assert_eq!(
+                report.updated,
+                [
+                    Pointer::new("orders", "o1"),
+                    Pointer::new("orders", "o2"),
+                    Pointer::new("sessions", "s1"),
+                ]
+            );
+            // `customers/c9` is read once for `orders` and reused for `sessions`.
+            assert_eq!(
+                requests
+                    .iter()
+                    .filter(|request| request["command"] == "get_bulk")
+                    .count(),
+                1
+            );
+
+            let update: &Value = requests
+                .iter()
+                .find(|request| {
+                    request["command"] == "update_value" && request["keyspace"] == "orders"
+                })
+                .unwrap();
+            let o2: Value =
+                serde_json::from_str(update["bulk_keys_values"]["o2"].as_str().unwrap()).unwrap();
+            assert_eq!(o2, json!({ "customer": null, "pointers": {} }));
+        });
+    }
+
+    /// `orders/o1` points to `pointers` targets; `locked` rejects reads and `archived` is unlisted.
+    fn audited_with(pointers: Value) -> impl FnMut(&str) -> Option<Vec<u8>> {
+        move |request| {
+            let request: Value = serde_json::from_str(request).unwrap();
+            let payload: Value = match (
+                request["raw"][0].as_str(),
+                request["command"].as_str(),
+                request["keyspace"].as_str(),
+            ) {
+                (Some("get-structure-available"), _, _) => {
+                    json!({ "store": { "persistent": ["orders", "locked"], "in_memory": [] } })
+                }
+                (_, Some("lookup_values"), Some("orders")) => json!({
+                    "o1": json!({ "pointers": pointers }).to_string(),
+                }),
+                (_, Some("lookup_values"), Some("locked")) => json!({}),
+                (_, Some("update_value"), _) => Value::Null,
+                _ => {
+                    return Some(
+                        br#"{"status":false,"payload":null,"error":"access denied"}"#.to_vec(),
+                    );
+                }
+            };
+
+            Some(
+                json!({ "status": true, "payload": payload, "error": null })
+                    .to_string()
+                    .into_bytes(),
+            )
+        }
+    }
+
+    #[test]
+    fn test_audit_reports_unlisted_targets_as_dangling_and_rejected_reads_as_unverified() {
+        tokio_test::block_on(async {
+            let server = MockServer::start(
+                5,
+                audited_with(json!({
+                    "customer": ["archived", "a1"],
+                    "approver": ["locked", "l1"],
+                })),
+            )
+            .await;
+
+            let engine: Engine = server.engine();
+            let report: AuditReport = audit_store(&engine).await.unwrap();
+            let requests: Vec<Value> = server.json_requests().await;
+
+            assert_eq!(
+                report.per_field().into_iter().collect::<Vec<_>>(),
+                [(("orders", "customer"), 1)]
+            );
+            assert_eq!(
+                report.unverified,
+                vec![DanglingPointer {
+                    keyspace: "orders".into(),
+                    key: "o1".into(),
+                    field: "approver".into(),
+                    target: Pointer::new("locked", "l1"),
+                }]
+            );
+            assert!(!report.is_clean());
+            assert!(
+                !requests
+                    .iter()
+                    .any(|request| request["keyspace"] == "archived")
+            );
+        });
+    }
+
+    #[test]
+    fn test_audit_never_removes_unverified_pointers() {
+        tokio_test::block_on(async {
+            let server =
+                MockServer::start(5, audited_with(json!({ "approver": ["locked", "l1"] }))).await;
+
+            let engine: Engine = server.engine();
+            let options: AuditOptions = AuditOptions {
+                action: DanglingAction::Remove,
+                ..Default::default()
+            };
+            let report: AuditReport = audit_store_with(&engine, options).await.unwrap();
+            let requests: Vec<Value> = server.json_requests().await;
+
+            assert!(report.dangling.is_empty());
+            assert!(report.updated.is_empty());
+            assert_eq!(report.unverified.len(), 1);
+            assert!(
+                !requests
+                    .iter()
+                    .any(|request| request["command"] == "update_value")
+            );
+        });
+    }
+
+    #[test]
+    fn test_rewrite_pointers_repairs_targets() {
+        let document: Value = json!({
+            "pointers": {
+                "owner": ["users", "gone"],
+                "watchers": { "a": ["users", "gone"], "b": ["users", "u2"] },
+            },
+        });
+
+        let update: Value = rewrite_pointers(document, |field, (_, key)| {
+            (key == "gone" && field == "owner").then(|| Some(("users".into(), "u1".into())))
+        })
+        .unwrap();
+
+        assert_eq!(
+            update,
+            json!({
+                "pointers": {
+                    "owner": ["users", "u1"],
+                    "watchers": { "a": ["users", "gone"], "b": ["users", "u2"] },
+                },
+            })
+        );
+    }
+}
@@ -0,0 +1,367 @@
+use crate::{errors::MontycatClientError, request::command::StoreOperation};
+use serde_json::Value;
+use std::{
+    collections::HashSet,
+    fmt::Debug,
+    sync::Mutex,
+    time::{SystemTime, UNIX_EPOCH},
+};
+use ulid::Ulid;
+use uuid::Uuid;
+
+/// Strategy that chooses the key of a value inserted without a custom key.
+///
+/// Keyspaces call the generator set with `with_key_generator` from `insert_value`,
+/// `insert_value_no_schema`, `insert_bulk` and `insert_bulk_no_schema`. The generated key is
+/// sent as is, so it is not hashed like a custom key. Without a generator the server creates the key.
+///
+/// The built-in generators return decimal integers, the same format as keys created by the server.
+///
+/// # Examples
+///
+/// ```rust, ignore
+/// let keyspace = PersistentKeyspace::new("orders", &engine)
+///     .with_key_generator(UuidV5Generator::new("orders", "request_id"));
+///
+/// // Retrying after a timeout inserts under the same key instead of creating a duplicate.
+/// keyspace.insert_value(None, order.clone()).await?;
+/// ```
+///
+pub trait KeyGenerator: Debug + Send + Sync {
+    /// Generates the key of a value.
+    ///
+    /// # Arguments
+    /// - `value: &str` : The value as it is sent to the server (serialized JSON).
+    ///
+    /// # Returns
+    /// - `Result<String, MontycatClientError>` : The key to store the value under.
+    ///
+    /// # Errors
+    /// - `MontycatClientError::ClientValueParsingError` : If no key can be derived from the value.
+    ///
+    fn generate(&self, value: &str) -> Result<String, MontycatClientError>;
+}
+
+/// Random UUIDv4 keys.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct UuidV4Generator;
+
+impl KeyGenerator for UuidV4Generator {
+    fn generate(&self, _value: &str) -> Result<String, MontycatClientError> {
+        Ok(Uuid::new_v4().as_u128().to_string())
+    }
+}
+
+/// Time-ordered UUIDv7 keys.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct UuidV7Generator;
+
+impl KeyGenerator for UuidV7Generator {
+    fn generate(&self, _value: &str) -> Result<String, MontycatClientError> {
+        Ok(Uuid::now_v7().as_u128().to_string())
+    }
+}
+
+/// Time-ordered ULID keys.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct UlidGenerator;
+
+impl KeyGenerator for UlidGenerator {
+    fn generate(&self, _value: &str) -> Result<String, MontycatClientError> {
+        Ok(Ulid::new().0.to_string())
+    }
+}
+
+/// Name-based UUIDv5 keys derived from an idempotency key carried by the value.
+///
+/// The caller puts an idempotency key (e.g. a request id) in a top-level field of every value.
+/// The same idempotency key always gets the same key, so an insert retried after a timeout writes
+/// the same entry again instead of creating a duplicate, while distinct inserts of equal values
+/// get distinct keys.
+///
+/// # Fields
+/// - `namespace: Uuid` : Namespace the keys are derived in, usually one per keyspace.
+/// - `key_field: String` : Name of the field holding the idempotency key.
+///
+#[derive(Debug, Clone)]
+pub struct UuidV5Generator {
+    namespace: Uuid,
+    key_field: String,
+}
+
+impl UuidV5Generator {
+    /// Creates a generator whose namespace is derived from a name, e.g. the keyspace name,
+    /// reading the idempotency key from the field `key_field` of each value.
+    pub fn new(namespace: &str, key_field: &str) -> Self {
+        Self::with_namespace(
+            Uuid::new_v5(&Uuid::NAMESPACE_OID, namespace.as_bytes()),
+            key_field,
+        )
+    }
+
+    /// Creates a generator with an explicit namespace.
+    pub fn with_namespace(namespace: Uuid, key_field: &str) -> Self {
+        Self {
+            namespace,
+            key_field: key_field.to_owned(),
+        }
+    }
+}
+
+impl KeyGenerator for UuidV5Generator {
+    fn generate(&self, value: &str) -> Result<String, MontycatClientError> {
+        let parsed: Value = serde_json::from_str(value)
+            .map_err(|e| MontycatClientError::ClientValueParsingError(e.to_string()))?;
+
+        let idempotency_key: String = match parsed.get(&self.key_field) {
+            Some(Value::String(key)) => key.to_owned(),
+            Some(Value::Number(key)) => key.to_string(),
+            _ => {
+                return Err(MontycatClientError::ClientValueParsingError(format!(
+                    "value has no string or number idempotency key in field `{}`",
+                    self.key_field
+                )));
+            }
+        };
+
+        Ok(Uuid::new_v5(&self.namespace, idempotency_key.as_bytes())
+            .as_u128()
+            .to_string())
+    }
+}
+
+/// Snowflake-style 64-bit keys: 41 bits of milliseconds since `SnowflakeGenerator::EPOCH_MS`,
+/// 10 bits of node id and a 12-bit sequence.
+///
+/// Keys are unique per node as long as every client process uses its own node id.
+///
+#[derive(Debug)]
+pub struct SnowflakeGenerator {
+    node_id: u64,
+    state: Mutex<(u64, u64)>,
+}
+
+impl SnowflakeGenerator {
+    /// Start of the timestamp (2024-01-01T00:00:00Z), in milliseconds since the Unix epoch.
+    pub const EPOCH_MS: u64 = 1_704_067_200_000;
+
+    const NODE_BITS: u64 = 10;
+    const SEQUENCE_BITS: u64 = 12;
+    const MAX_SEQUENCE: u64 = (1 << Self::SEQUENCE_BITS) - 1;
+
+    /// Creates a generator for a node. Only the lowest 10 bits of `node_id` are used.
+    pub fn new(node_id: u16) -> Self {
+        Self {
+            node_id: u64::from(node_id) & ((1 << Self::NODE_BITS) - 1),
+            state: Mutex::new((0, 0)),
+        }
+    }
+
+    fn now_ms() -> u64 {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|elapsed| elapsed.as_millis() as u64)
+            .unwrap_or_default()
+            .saturating_sub(Self::EPOCH_MS)
+    }
+
+    fn next(&self) -> u64 {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        let (last_ms, sequence) = *state;
+        let mut now: u64 = Self::now_ms().max(last_ms);
+
+        let sequence: u64 = if now == last_ms {
+            (sequence + 1) & Self::MAX_SEQUENCE
+        } else {
+            0
+        };
+
+        // The sequence wrapped within one millisecond: borrow the next millisecond.
+        if now == last_ms && sequence == 0 {
+            now += 1;
+        }
+
+        *state = (now, sequence);
+
+        (now << (Self::NODE_BITS + Self::SEQUENCE_BITS))
+            | (self.node_id << Self::SEQUENCE_BITS)
+            | sequence
+    }
+}
+
+impl KeyGenerator for SnowflakeGenerator {
+    fn generate(&self, _value: &str) -> Result<String, MontycatClientError> {
+        Ok(self.next().to_string())
+    }
+}
+
+/// The request fields of a bulk insert, with or without generated keys.
+pub(crate) struct BulkInsert {
+    pub value: String,
+    pub bulk_keys_values: Vec<(String, String)>,
+    pub operation: StoreOperation,
+}
+
+impl BulkInsert {
+    /// Values sent as one serialized list; the server creates the keys.
+    pub(crate) fn plain(value: String) -> Self {
+        Self {
+            value,
+            bulk_keys_values: Vec::new(),
+            operation: StoreOperation::InsertValue,
+        }
+    }
+
+    /// Serialized values sent under keys chosen by a generator.
+    ///
+    /// Two values getting the same key are rejected instead of one silently replacing the other.
+    pub(crate) fn generated(
+        values: Vec<String>,
+        generator: &dyn KeyGenerator,
+    ) -> Result<Self, MontycatClientError> {
+        let mut seen: HashSet<String> = HashSet::with_capacity(values.len());
+        let mut bulk_keys_values: Vec<(String, String)> = Vec::with_capacity(values.len());
+
+        for value in values {
+            let key: String = generator.generate(&value)?;
+
+            if !seen.insert(key.clone()) {
+                return Err(MontycatClientError::ClientValueParsingError(format!(
+                    "generated key {} is shared by several values of the bulk insert",
+                    key
+                )));
+            }
+
+            bulk_keys_values.push((key, value));
+        }
+
+        Ok(Self {
+            value: String::new(),
+            bulk_keys_values,
+            operation: StoreOperation::InsertCustomKeyValue,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    #[test]
+    fn test_generators_return_decimal_keys() {
+        let generators: Vec<Box<dyn KeyGenerator>> = vec![
+            Box::new(UuidV4Generator),
+            Box::new(UuidV7Generator),
+            Box::new(UlidGenerator),
+            Box::new(SnowflakeGenerator::new(1)),
+            Box::new(UuidV5Generator::new("orders", "request_id")),
+        ];
+
+        for generator in generators {
+            let key: String = generator.generate(r#"{"request_id":"r-1"}"#).unwrap();
+            assert!(
+                key.parse::<u128>().is_ok(),
+                "{:?} returned {}",
+                generator,
+                key
+            );
+        }
+    }
+
+    #[test]
+    fn test_uuid_v5_is_deterministic_per_idempotency_key_and_namespace() {
+        let orders: UuidV5Generator = UuidV5Generator::new("orders", "request_id");
+
+        assert_eq!(
+            orders.generate(r#"{"request_id":"r-1","id":1}"#).unwrap(),
+            orders.generate(r#"{"id":1,"request_id":"r-1"}"#).unwrap()
+        );
+        assert_ne!(
+            orders.generate(r#"{"request_id":"r-1","id":1}"#).unwrap(),
+            orders.generate(r#"{"request_id":"r-2","id":1}"#).unwrap()
+        );
+        assert_ne!(
+            orders.generate(r#"{"request_id":"r-1"}"#).unwrap(),
+            UuidV5Generator::new("invoices", "request_id")
+                .generate(r#"{"request_id":"r-1"}"#)
+                .unwrap()
+        );
+        assert!(matches!(
+            orders.generate(r#"{"id":1}"#),
+            Err(MontycatClientError::ClientValueParsingError(_))
+        ));
+    }
+
+    #[test]
+    fn test_bulk_insert_rejects_duplicate_generated_keys() {
+        let orders: UuidV5Generator = UuidV5Generator::new("orders", "request_id");
+        let values: Vec<String> = vec![
+            r#"{"request_id":"r-1","id":1}"#.to_owned(),
+            r#"{"request_id":"r-1","id":2}"#.to_owned(),
+        ];
+
+        assert!(matches!(
+            BulkInsert::generated(values, &orders),
+            Err(MontycatClientError::ClientValueParsingError(_))
+        ));
+
+        let bulk: BulkInsert = BulkInsert::generated(
+            vec![
+                r#"{"request_id":"r-1"}"#.to_owned(),
+                r#"{"request_id":"r-2"}"#.to_owned(),
+            ],
+            &orders,
+        )
+        .unwrap();
+        assert_eq!(bulk.bulk_keys_values.len(), 2);
+        assert_eq!(bulk.bulk_keys_values[1].1, r#"{"request_id":"r-2"}"#);
+    }
+
+    #[test]
+    fn test_snowflake_keys_are_unique_and_increasing() {
+        let generator: SnowflakeGenerator = SnowflakeGenerator::new(7);
+        let keys: Vec<u64> = (0..10_000).map(|_| generator.next()).collect();
+
+        assert!(keys.windows(2).all(|pair| pair[0] < pair[1]));
+        assert_eq!(keys.iter().collect::<HashSet<_>>().len(), keys.len());
+        assert_eq!((keys[0] >> 12) & 0x3ff, 7);
+    }
+
+    #[cfg(not(feature = "tls"))]
+    #[test]
+    fn test_retried_inserts_reuse_the_generated_key() {
+        use crate::{PersistentKeyspace, test_support::MockServer};
+
+        tokio_test::block_on(async {
+            let ok: &str = r#"{"status":true,"payload":null,"error":null}"#;
+            let server = MockServer::responses([ok; 3]).await;
+
+            let engine = server.engine();
+            let keyspace: PersistentKeyspace = PersistentKeyspace::new("orders", &engine)
+                .with_key_generator(UuidV5Generator::new("orders", "request_id"));
+            let order: serde_json::Value = serde_json::json!({ "request_id": "r-1", "id": 1 });
+
+            keyspace
+                .insert_value_no_schema(None, order.clone())
+                .await
+                .unwrap();
+            keyspace.insert_value_no_schema(None, order).await.unwrap();
+            keyspace
+                .insert_bulk_no_schema(vec![
+                    serde_json::json!({ "request_id": "r-2", "id": 2 }),
+                    serde_json::json!({ "request_id": "r-3", "id": 2 }),
+                ])
+                .await
+                .unwrap();
+
+            let requests: Vec<serde_json::Value> = server.json_requests().await;
+            assert_eq!(requests[0]["command"], "insert_custom_key_value");
+            assert_eq!(requests[0]["key"], requests[1]["key"]);
+            assert_eq!(
+                requests[2]["bulk_keys_values"].as_object().unwrap().len(),
+                2
+            );
+        });
+    }
+}
@@ -1,2 +1,9 @@
+pub mod dyntrait;
+pub mod hashing;
+pub mod integrity;
+pub mod keygen;
 pub mod pubtrait;
+pub mod registry;
+pub(crate) mod resolver;
+pub mod schematrait;
 pub mod structures;
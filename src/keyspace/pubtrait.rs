@@ -1,16 +1,32 @@
+use super::{
+    hashing::{KeyHashing, verify_custom_keys, verify_hashed_custom_keys},
+    integrity::{self, DeletePolicy, DeleteReport},
+    registry::{KEY_REGISTRY_DISABLED, KeyRegistry, ResolvedKey, unregister_deleted},
+    resolver::resolve_pointers,
+};
 use crate::{
     Limit, MontycatClientError,
-    engine::{structure::Engine, utils::send_data},
+    engine::{
+        capabilities::{ServerCapabilities, ServerFeature},
+        structure::Engine,
+    },
     request::{
-        store_request::structure::StoreRequestClient,
-        structure::Req,
-        utis::functions::{convert_custom_key, merge_bulk_keys_values, merge_keys},
+        command::{Command, StoreCommand, StoreOperation},
+        utis::functions::{merge_bulk_keys_values, merge_keys},
+    },
+    response::{
+        nested,
+        structure::{parse_keys, parse_payload},
+        typed::{ReadOptions, ReadShape},
+    },
+    tools::{
+        functions::process_json_value,
+        schema::{FieldSchema, describe_fields},
     },
-    tools::functions::{define_type, process_json_value},
 };
-use async_trait::async_trait;
-use serde::Serialize;
-use std::collections::HashMap;
+use serde::{Serialize, de::DeserializeOwned};
+use serde_json::Value;
+use std::{collections::HashMap, future::Future};
 
 /// PubTrait defines the public interface for keyspace operations.
 ///
@@ -20,16 +36,34 @@ use std::collections::HashMap;
 /// - `get_name`: Retrieves the name of the keyspace.
 /// - `get_persistent`: Checks if the keyspace is persistent.
 /// - `get_distributed`: Checks if the keyspace is distributed.
+/// - `get_key_hashing`: Retrieves the scheme used to hash custom keys.
+/// - `get_collision_detection`: Checks if reads by custom key verify the stored custom key.
+/// - `get_key_registry`: Retrieves the key registry, if the keyspace keeps one.
 /// - `remove_keyspace`: Removes the keyspace from the store.
 /// - `get_value`: Retrieves a value by key or custom key.
+/// - `get_value_as`: Retrieves a value by key or custom key and deserializes it in the shape its `ReadOptions` select.
 /// - `delete_key`: Deletes a value by key or custom key.
 /// - `list_all_depending_keys`: Lists all keys that depend on a given key or custom key.
+/// - `delete_with_policy`: Deletes a value, restricting, cascading or nullifying its dependents.
 /// - `get_bulk`: Retrieves multiple values by a list of keys.
+/// - `get_bulk_as`: Retrieves multiple values and deserializes them in the shape their `ReadOptions` select.
+/// - `get_bulk_resolved`: Retrieves multiple values with their pointers resolved to their targets.
 /// - `delete_bulk`: Deletes multiple values by a list of keys.
 /// - `get_len`: Gets the length of the keyspace.
 /// - `enforce_schema`: Enforces a schema on the keyspace.
 /// - `remove_enforced_schema`: Removes an enforced schema from the keyspace.
 /// - `update_bulk`: Updates multiple key-value pairs in the keyspace.
+/// - `lookup_keys_where_resolved`: Looks up keys by filters together with their original custom keys.
+/// - `lookup_values_where_as`: Looks up values by filters and deserializes them in the shape their `ReadOptions` select.
+/// - `lookup_values_where_resolved`: Looks up values with their pointers resolved to their targets.
+///
+/// The `_as` methods parse the response themselves and turn `status: false` into
+/// `MontycatClientError::ClientServerError`. The raw-bytes methods remain available.
+/// Their `ReadOptions` select the returned type: requesting `key_included` returns `Keyed<T>`,
+/// `pointers_metadata` returns `WithPointerMetadata<T>`, and both return `Keyed<WithPointerMetadata<T>>`.
+///
+/// Methods return `impl Future + Send` instead of boxed futures, so calls do not allocate.
+/// Use `DynKeyspace` when a trait object is needed.
 ///
 /// # Errors
 /// - `MontycatClientError::ClientStoreNotSet`: If the store is not set in the engine.
@@ -38,15 +72,17 @@ use std::collections::HashMap;
 /// - `MontycatClientError::ClientSelectedBothKeyAndCustomKey`: If both key and custom_key are provided.
 /// - `MontycatClientError::ClientNoValidInputProvided`: If neither key nor custom_key are provided.
 /// - `MontycatClientError::ClientSelectedBothPointersValueAndMetadata`: If both with_pointers and pointers_metadata are true.
-#[async_trait]
 pub trait Keyspace
 where
     Self: Sized + Send + Sync,
 {
-    fn get_engine(&self) -> Engine;
+    fn get_engine(&self) -> &Engine;
     fn get_name(&self) -> &str;
     fn get_persistent(&self) -> bool;
     fn get_distributed(&self) -> bool;
+    fn get_key_hashing(&self) -> KeyHashing;
+    fn get_collision_detection(&self) -> bool;
+    fn get_key_registry(&self) -> Option<KeyRegistry>;
 
     /// Remove keyspace
     ///
@@ -64,40 +100,25 @@ where
     /// * `MontycatClientError::ClientEngineError` - If there is an error with the engine
     /// * `MontycatClientError::ClientValueParsingError` - If there is an error parsing the response
     ///
-    async fn remove_keyspace(&self) -> Result<Option<Vec<u8>>, MontycatClientError> {
-        let engine: Engine = self.get_engine();
-        let name: &str = self.get_name();
-        let persistent: bool = self.get_persistent();
-        let store: String = engine
-            .store
-            .clone()
-            .ok_or(MontycatClientError::ClientStoreNotSet)?;
-        let use_tls: bool = engine.use_tls;
-
-        let vec: Vec<String> = vec![
-            "remove-keyspace".into(),
-            "store".into(),
-            store,
-            "keyspace".into(),
-            name.to_owned(),
-            "persistent".into(),
-            if persistent { "y".into() } else { "n".into() },
-        ];
-
-        let credentials: Vec<String> = engine.get_credentials();
-        let query: Req = Req::new_raw_command(vec, credentials);
-        let bytes: Vec<u8> = query.byte_down()?;
-        let response: Option<Vec<u8>> = send_data(
-            &engine.host,
-            engine.port,
-            bytes.as_slice(),
-            None,
-            None,
-            use_tls,
-        )
-        .await?;
-
-        Ok(response)
+    fn remove_keyspace(
+        &self,
+    ) -> impl Future<Output = Result<Option<Vec<u8>>, MontycatClientError>> + Send {
+        async move {
+            let engine: &Engine = self.get_engine();
+            let name: &str = self.get_name();
+            let persistent: bool = self.get_persistent();
+            let store: &str = engine
+                .store()
+                .ok_or(MontycatClientError::ClientStoreNotSet)?;
+
+            let command: Command = Command::RemoveKeyspace {
+                store: store.to_owned(),
+                keyspace: name.to_owned(),
+                persistent,
+            };
+
+            engine.send_command(&command).await
+        }
     }
 
     /// Get value by key or custom key
@@ -148,71 +169,129 @@ where
     /// Returns MontycatClientError if neither key nor custom_key are provided
     /// Returns MontycatClientError if pointers_metadata and with_pointers are both true
     /// Returns MontycatClientError if the store is not set in the engine
+    /// Returns MontycatClientError::ClientUnsupported if the server does not support pointers metadata
     ///
-    async fn get_value(
+    fn get_value(
         &self,
         key: Option<&str>,
         custom_key: Option<&str>,
         with_pointers: bool,
         key_included: bool,
         with_pointers_metadata: bool,
-    ) -> Result<Option<Vec<u8>>, MontycatClientError> {
-        if key.is_none() && custom_key.is_none() {
-            return Err(MontycatClientError::ClientSelectedBothKeyAndCustomKey);
-        }
+    ) -> impl Future<Output = Result<Option<Vec<u8>>, MontycatClientError>> + Send {
+        async move {
+            if key.is_none() && custom_key.is_none() {
+                return Err(MontycatClientError::ClientSelectedBothKeyAndCustomKey);
+            }
 
-        if key.is_none() && custom_key.is_none() {
-            return Err(MontycatClientError::ClientNoValidInputProvided);
-        }
+            if key.is_none() && custom_key.is_none() {
+                return Err(MontycatClientError::ClientNoValidInputProvided);
+            }
 
-        let mut key: String = key.unwrap_or("").to_owned();
+            let mut key: String = key.unwrap_or("").to_owned();
 
-        if with_pointers_metadata && with_pointers {
-            return Err(MontycatClientError::ClientSelectedBothPointersValueAndMetadata);
-        }
+            if with_pointers_metadata && with_pointers {
+                return Err(MontycatClientError::ClientSelectedBothPointersValueAndMetadata);
+            }
+
+            if let Some(custom_key_unwrapped) = custom_key {
+                key = self.get_key_hashing().hash(custom_key_unwrapped);
+            }
 
-        if let Some(custom_key_unwrapped) = custom_key {
-            key = convert_custom_key(custom_key_unwrapped);
+            let engine: &Engine = self.get_engine();
+
+            if with_pointers_metadata {
+                engine.require(ServerFeature::PointersMetadata).await?;
+            }
+            let name: &str = self.get_name();
+            let persistent: bool = self.get_persistent();
+            let distributed: bool = self.get_distributed();
+            let store: &str = engine
+                .store()
+                .ok_or(MontycatClientError::ClientStoreNotSet)?;
+            let operation: StoreOperation = StoreOperation::GetValue;
+
+            let new_store_req: StoreCommand = StoreCommand {
+                key: key.to_owned().into(),
+                keyspace: name.into(),
+                store: store.into(),
+                persistent,
+                distributed,
+                operation,
+                with_pointers,
+                key_included,
+                pointers_metadata: with_pointers_metadata,
+                ..Default::default()
+            };
+
+            let response: Option<Vec<u8>> = engine
+                .send_command(&Command::Store(Box::new(new_store_req)))
+                .await?;
+
+            match custom_key {
+                Some(custom_key) if self.get_collision_detection() => {
+                    verify_custom_keys(response, &[custom_key])
+                }
+                _ => Ok(response),
+            }
         }
+    }
 
-        let engine: Engine = self.get_engine();
-        let name: &str = self.get_name();
-        let persistent: bool = self.get_persistent();
-        let distributed: bool = self.get_distributed();
-        let store: String = engine
-            .store
-            .clone()
-            .ok_or(MontycatClientError::ClientStoreNotSet)?;
-        let use_tls: bool = engine.use_tls;
-        let command: String = "get_value".to_string();
-
-        let new_store_req: StoreRequestClient = StoreRequestClient {
-            key: key.to_owned().into(),
-            keyspace: name.to_owned(),
-            store,
-            persistent,
-            distributed,
-            command,
-            with_pointers,
-            key_included,
-            pointers_metadata: with_pointers_metadata,
-            username: engine.username.clone(),
-            password: engine.password.clone(),
-            ..Default::default()
-        };
-
-        let bytes: Vec<u8> = Req::new_store_command(new_store_req).byte_down()?;
-        let response: Option<Vec<u8>> = send_data(
-            &engine.host,
-            engine.port,
-            bytes.as_slice(),
-            None,
-            None,
-            use_tls,
-        )
-        .await?;
-
-        Ok(response)
+    /// Get value by key or custom key, deserialized according to `options`
+    ///
+    /// The value is returned as `T`, `Keyed<T>`, `WithPointerMetadata<T>` or
+    /// `Keyed<WithPointerMetadata<T>>`, depending on the options requested, see `ReadShape`.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key to retrieve the value for
+    /// * `custom_key` - The custom key to retrieve the value for
+    /// * `options` - Whether to resolve pointers and include the key or pointer metadata
+    ///
+    /// # Returns
+    /// * `Result<Option<S::Value<T>>, MontycatClientError>` - The value, or None if the server returned no value
+    ///
+    /// # Examples
+    ///
+    /// ```rust, ignore
+    /// let employee: Option<Employee> = keyspace
+    ///     .get_value_as(None, Some("employee-1"), ReadOptions::new())
+    ///     .await?;
+    ///
+    /// let employee: Option<Keyed<Employee>> = keyspace
+    ///     .get_value_as(None, Some("employee-1"), ReadOptions::new().key_included())
+    ///     .await?;
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns every error `get_value` returns
+    /// Returns MontycatClientError::ClientServerError if the server answers with `status: false`
+    /// Returns MontycatClientError::ClientValueParsingError if the value is not a valid `S::Value<T>`
+    ///
+    fn get_value_as<T, S>(
+        &self,
+        key: Option<&str>,
+        custom_key: Option<&str>,
+        options: ReadOptions<S>,
+    ) -> impl Future<Output = Result<Option<S::Value<T>>, MontycatClientError>> + Send
+    where
+        T: DeserializeOwned + Send,
+        S: ReadShape,
+    {
+        async move {
+            let response: Result<Option<Vec<u8>>, MontycatClientError> = self
+                .get_value(
+                    key,
+                    custom_key,
+                    options.resolves_pointers(),
+                    S::KEY_INCLUDED,
+                    S::POINTERS_METADATA,
+                )
+                .await;
+
+            parse_payload(response)
+        }
     }
 
     /// Delete value by key or custom key
@@ -253,60 +332,51 @@ where
     /// * Returns MontycatClientError if neither key nor custom_key are provided
     /// * Returns MontycatClientError if the store is not set in the engine
     ///
-    async fn delete_key(
+    fn delete_key(
         &self,
         key: Option<&str>,
         custom_key: Option<&str>,
-    ) -> Result<Option<Vec<u8>>, MontycatClientError> {
-        if key.is_some() && custom_key.is_some() {
-            return Err(MontycatClientError::ClientSelectedBothKeyAndCustomKey);
-        }
+    ) -> impl Future<Output = Result<Option<Vec<u8>>, MontycatClientError>> + Send {
+        async move {
+            if key.is_some() && custom_key.is_some() {
+                return Err(MontycatClientError::ClientSelectedBothKeyAndCustomKey);
+            }
 
-        if key.is_none() && custom_key.is_none() {
-            return Err(MontycatClientError::ClientNoValidInputProvided);
-        }
+            if key.is_none() && custom_key.is_none() {
+                return Err(MontycatClientError::ClientNoValidInputProvided);
+            }
 
-        let mut key: String = key.unwrap_or("").to_owned();
+            let mut key: String = key.unwrap_or("").to_owned();
 
-        if let Some(custom_key_unwrapped) = custom_key {
-            key = convert_custom_key(custom_key_unwrapped);
-        }
+            if let Some(custom_key_unwrapped) = custom_key {
+                key = self.get_key_hashing().hash(custom_key_unwrapped);
+            }
 
-        let engine: Engine = self.get_engine();
-        let name: &str = self.get_name();
-        let persistent: bool = self.get_persistent();
-        let distributed: bool = self.get_distributed();
-        let store: String = engine
-            .store
-            .clone()
-            .ok_or(MontycatClientError::ClientStoreNotSet)?;
-        let use_tls: bool = engine.use_tls;
-        let command: String = "delete_key".to_string();
-
-        let new_store_req: StoreRequestClient = StoreRequestClient {
-            key: key.to_owned().into(),
-            keyspace: name.to_owned(),
-            store,
-            persistent,
-            distributed,
-            command,
-            username: engine.username.clone(),
-            password: engine.password.clone(),
-            ..Default::default()
-        };
-
-        let bytes: Vec<u8> = Req::new_store_command(new_store_req).byte_down()?;
-        let response: Option<Vec<u8>> = send_data(
-            &engine.host,
-            engine.port,
-            bytes.as_slice(),
-            None,
-            None,
-            use_tls,
-        )
-        .await?;
-
-        Ok(response)
+            let engine: &Engine = self.get_engine();
+            let name: &str = self.get_name();
+            let persistent: bool = self.get_persistent();
+            let distributed: bool = self.get_distributed();
+            let store: &str = engine
+                .store()
+                .ok_or(MontycatClientError::ClientStoreNotSet)?;
+            let operation: StoreOperation = StoreOperation::DeleteKey;
+
+            let new_store_req: StoreCommand = StoreCommand {
+                key: key.to_owned().into(),
+                keyspace: name.into(),
+                store: store.into(),
+                persistent,
+                distributed,
+                operation,
+                ..Default::default()
+            };
+
+            let response: Option<Vec<u8>> = engine
+                .send_command(&Command::Store(Box::new(new_store_req)))
+                .await?;
+
+            unregister_deleted(self, vec![key], response).await
+        }
     }
 
     /// List all keys that depend on the given key or custom key
@@ -349,56 +419,101 @@ where
     /// * Returns MontycatClientError if neither key nor custom_key are provided
     /// * Returns MontycatClientError if the store is not set in the engine
     ///
-    async fn list_all_depending_keys(
+    fn list_all_depending_keys(
         &self,
         key: &str,
         custom_key: Option<&str>,
-    ) -> Result<Option<Vec<u8>>, MontycatClientError> {
-        if !key.is_empty() && custom_key.is_some() {
-            return Err(MontycatClientError::ClientSelectedBothKeyAndCustomKey);
-        }
+    ) -> impl Future<Output = Result<Option<Vec<u8>>, MontycatClientError>> + Send {
+        async move {
+            if !key.is_empty() && custom_key.is_some() {
+                return Err(MontycatClientError::ClientSelectedBothKeyAndCustomKey);
+            }
 
-        let mut key: String = key.to_owned();
+            let mut key: String = key.to_owned();
 
-        if let Some(custom_key_unwrapped) = custom_key {
-            key = convert_custom_key(custom_key_unwrapped);
+            if let Some(custom_key_unwrapped) = custom_key {
+                key = self.get_key_hashing().hash(custom_key_unwrapped);
+            }
+
+            let engine: &Engine = self.get_engine();
+            let name: &str = self.get_name();
+            let persistent: bool = self.get_persistent();
+            let distributed: bool = self.get_distributed();
+            let store: &str = engine
+                .store()
+                .ok_or(MontycatClientError::ClientStoreNotSet)?;
+            let operation: StoreOperation = StoreOperation::ListAllDependingKeys;
+
+            let new_store_req: StoreCommand = StoreCommand {
+                key: key.to_owned().into(),
+                keyspace: name.into(),
+                store: store.into(),
+                persistent,
+                distributed,
+                operation,
+                ..Default::default()
+            };
+
+            engine
+                .send_command(&Command::Store(Box::new(new_store_req)))
+                .await
         }
+    }
+
+    /// Delete a value by key or custom key, handling the values that point to it
+    ///
+    /// Dependents are found with `list_all_depending_keys`. With `DeletePolicy::Cascade` they are
+    /// deleted too, deepest first, with one `delete_bulk` per keyspace. With `DeletePolicy::Nullify`
+    /// their pointers to the key are removed with `update_value`. Dependent keyspaces are addressed
+    /// as persistent or in-memory as listed by `get_structure_available`, and cascaded deletes are
+    /// removed from the key registry of keyspaces marked in `KEY_REGISTRY_CATALOG`.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key of the value to delete
+    /// * `custom_key` - An optional custom key of the value to delete
+    /// * `policy` - What to do with the values that point to the key
+    /// * `dry_run` - If true, only reports what would be affected, without writing
+    ///
+    /// # Returns
+    /// * `Result<DeleteReport, MontycatClientError>` - The deleted and nullified values
+    ///
+    /// # Examples
+    ///
+    /// ```rust, ignore
+    /// let report: DeleteReport = keyspace
+    ///     .delete_with_policy(Some("42"), None, DeletePolicy::Cascade, true)
+    ///     .await?;
+    ///
+    /// println!("would delete {} values", report.deleted.len());
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// * Returns MontycatClientError if both key and custom_key are provided
+    /// * Returns MontycatClientError if neither key nor custom_key are provided
+    /// * Returns `MontycatClientError::ClientDeleteRestricted` with `DeletePolicy::Restrict` if values depend on the key
+    /// * Returns `MontycatClientError::ClientServerError` if the server answers a request with `status: false`
+    ///
+    fn delete_with_policy(
+        &self,
+        key: Option<&str>,
+        custom_key: Option<&str>,
+        policy: DeletePolicy,
+        dry_run: bool,
+    ) -> impl Future<Output = Result<DeleteReport, MontycatClientError>> + Send {
+        async move {
+            let key: String = match (key, custom_key) {
+                (Some(_), Some(_)) => {
+                    return Err(MontycatClientError::ClientSelectedBothKeyAndCustomKey);
+                }
+                (Some(key), None) => key.to_owned(),
+                (None, Some(custom_key)) => self.get_key_hashing().hash(custom_key),
+                (None, None) => return Err(MontycatClientError::ClientNoValidInputProvided),
+            };
 
-        let engine: Engine = self.get_engine();
-        let name: &str = self.get_name();
-        let persistent: bool = self.get_persistent();
-        let distributed: bool = self.get_distributed();
-        let store: String = engine
-            .store
-            .clone()
-            .ok_or(MontycatClientError::ClientStoreNotSet)?;
-        let use_tls: bool = engine.use_tls;
-        let command: String = "list_all_depending_keys".to_string();
-
-        let new_store_req: StoreRequestClient = StoreRequestClient {
-            key: key.to_owned().into(),
-            keyspace: name.to_owned(),
-            store,
-            persistent,
-            distributed,
-            command,
-            username: engine.username.clone(),
-            password: engine.password.clone(),
-            ..Default::default()
-        };
-
-        let bytes: Vec<u8> = Req::new_store_command(new_store_req).byte_down()?;
-        let response: Option<Vec<u8>> = send_data(
-            &engine.host,
-            engine.port,
-            bytes.as_slice(),
-            None,
-            None,
-            use_tls,
-        )
-        .await?;
-
-        Ok(response)
+            integrity::delete_with_policy(self, key, policy, dry_run).await
+        }
     }
 
     /// Get multiple values by a list of keys
@@ -417,6 +532,9 @@ where
     /// # Behavior
     ///
     /// * Sends a request to the server to retrieve values for the provided keys
+    /// * With collision detection, the values are read with their keys, and each value stored under
+    ///   the hash of a requested custom key is checked against that custom key. The keys are removed
+    ///   from the response again unless `key_included` is set
     /// * Returns the raw response bytes from the server
     ///
     /// # Examples
@@ -436,9 +554,11 @@ where
     /// * Returns MontycatClientError if there is an error parsing the response
     /// * Returns MontycatClientError if both with_pointers and with_pointers_metadata are true
     /// * Returns MontycatClientError if multiple conflicting options are provided (keys, volumes, latest_volume)
+    /// * Returns MontycatClientError::ClientUnsupported if the server does not support volumes, latest_volume or pointers metadata
+    /// * Returns MontycatClientError::ClientCustomKeyCollision if a value read by custom key was stored under another custom key
     ///
     #[allow(clippy::too_many_arguments)]
-    async fn get_bulk(
+    fn get_bulk(
         &self,
         bulk_keys: Option<Vec<String>>,
         bulk_custom_keys: Option<Vec<String>>,
@@ -448,80 +568,223 @@ where
         limit: Option<Limit>,
         volumes: Option<Vec<String>>,
         latest_volume: Option<bool>,
-    ) -> Result<Option<Vec<u8>>, MontycatClientError> {
-        if with_pointers && with_pointers_metadata {
-            return Err(MontycatClientError::ClientSelectedBothPointersValueAndMetadata);
-        }
+    ) -> impl Future<Output = Result<Option<Vec<u8>>, MontycatClientError>> + Send {
+        async move {
+            if with_pointers && with_pointers_metadata {
+                return Err(MontycatClientError::ClientSelectedBothPointersValueAndMetadata);
+            }
 
-        let processed_keys: Vec<String> = merge_keys(bulk_keys, bulk_custom_keys).await?;
-
-        let selected_options = [
-            !processed_keys.is_empty(),
-            volumes.as_ref().is_some_and(|v| !v.is_empty()),
-            latest_volume.unwrap_or(false),
-        ]
-        .iter()
-        .filter(|&&x| x)
-        .count();
-
-        if selected_options != 1 {
-            return Err(MontycatClientError::ClientGenericError(
-                "Multiple conflicting options provided. Please provide exactly one of the following: keys, volumes, or latest volume.".into()
-            ));
-        }
+            let hashing: KeyHashing = self.get_key_hashing();
+            let verified_custom_keys: Option<HashMap<String, String>> = bulk_custom_keys
+                .as_ref()
+                .filter(|_| self.get_collision_detection())
+                .map(|custom_keys| {
+                    custom_keys
+                        .iter()
+                        .map(|custom_key| (hashing.hash(custom_key), custom_key.clone()))
+                        .collect()
+                });
+
+            let processed_keys: Vec<String> =
+                merge_keys(bulk_keys, bulk_custom_keys, self.get_key_hashing()).await?;
+
+            let selected_options = [
+                !processed_keys.is_empty(),
+                volumes.as_ref().is_some_and(|v| !v.is_empty()),
+                latest_volume.unwrap_or(false),
+            ]
+            .iter()
+            .filter(|&&x| x)
+            .count();
+
+            if selected_options != 1 {
+                return Err(MontycatClientError::ClientGenericError(
+                    "Multiple conflicting options provided. Please provide exactly one of the following: keys, volumes, or latest volume.".into()
+                ));
+            }
 
-        let engine: Engine = self.get_engine();
-        let name: &str = self.get_name();
-        let persistent: bool = self.get_persistent();
-        let distributed: bool = self.get_distributed();
-        let store: String = engine
-            .store
-            .clone()
-            .ok_or(MontycatClientError::ClientStoreNotSet)?;
-        let use_tls: bool = engine.use_tls;
-        let command: String = "get_bulk".to_string();
-
-        let limit_map: HashMap<String, usize> = match limit {
-            Some(lim) => {
-                if lim.start > lim.stop {
-                    return Err(MontycatClientError::ClientGenericError(
-                        "Limit start cannot be greater than stop".into(),
-                    ));
-                }
+            let engine: &Engine = self.get_engine();
 
-                lim.to_map()
+            if with_pointers_metadata {
+                engine.require(ServerFeature::PointersMetadata).await?;
             }
-            None => Limit::default_limit().to_map(),
-        };
-
-        let new_store_req: StoreRequestClient = StoreRequestClient {
-            bulk_keys: processed_keys,
-            keyspace: name.to_owned(),
-            store,
-            persistent,
-            distributed,
-            command,
-            limit_output: limit_map,
-            username: engine.username.clone(),
-            password: engine.password.clone(),
-            with_pointers,
-            key_included,
-            pointers_metadata: with_pointers_metadata,
-            ..Default::default()
-        };
-
-        let bytes: Vec<u8> = Req::new_store_command(new_store_req).byte_down()?;
-        let response: Option<Vec<u8>> = send_data(
-            &engine.host,
-            engine.port,
-            bytes.as_slice(),
-            None,
-            None,
-            use_tls,
-        )
-        .await?;
-
-        Ok(response)
+            if volumes.as_ref().is_some_and(|v| !v.is_empty()) {
+                engine.require(ServerFeature::Volumes).await?;
+            }
+            if latest_volume.unwrap_or(false) {
+                engine.require(ServerFeature::LatestVolume).await?;
+            }
+            let name: &str = self.get_name();
+            let persistent: bool = self.get_persistent();
+            let distributed: bool = self.get_distributed();
+            let store: &str = engine
+                .store()
+                .ok_or(MontycatClientError::ClientStoreNotSet)?;
+            let operation: StoreOperation = StoreOperation::GetBulk;
+
+            let limit_map: HashMap<String, usize> = match limit {
+                Some(lim) => {
+                    if lim.start > lim.stop {
+                        return Err(MontycatClientError::ClientGenericError(
+                            "Limit start cannot be greater than stop".into(),
+                        ));
+                    }
+
+                    lim.to_map()
+                }
+                None => Limit::default_limit().to_map(),
+            };
+
+            let new_store_req: StoreCommand = StoreCommand {
+                bulk_keys: processed_keys,
+                keyspace: name.into(),
+                store: store.into(),
+                persistent,
+                distributed,
+                operation,
+                limit_output: limit_map,
+                with_pointers,
+                // Verifying custom keys needs the stored key of every value.
+                key_included: key_included || verified_custom_keys.is_some(),
+                pointers_metadata: with_pointers_metadata,
+                ..Default::default()
+            };
+
+            let response: Option<Vec<u8>> = engine
+                .send_command(&Command::Store(Box::new(new_store_req)))
+                .await?;
+
+            match verified_custom_keys {
+                Some(custom_keys) => {
+                    verify_hashed_custom_keys(response, &custom_keys, key_included)
+                }
+                None => Ok(response),
+            }
+        }
+    }
+
+    /// Get multiple values by a list of keys, deserialized according to `options`
+    ///
+    /// Each value is returned as `T`, `Keyed<T>`, `WithPointerMetadata<T>` or
+    /// `Keyed<WithPointerMetadata<T>>`, depending on the options requested, see `ReadShape`. With
+    /// `key_included`, the server may answer with a list of keyed values or with a map from key to
+    /// value; both are accepted.
+    ///
+    /// # Arguments
+    ///
+    /// Same as `get_bulk`, with `options` instead of `with_pointers`, `key_included` and
+    /// `with_pointers_metadata`.
+    ///
+    /// # Returns
+    /// * `Result<Vec<S::Value<T>>, MontycatClientError>` - The values in the order the server returned them
+    ///
+    /// # Examples
+    ///
+    /// ```rust, ignore
+    /// let employees: Vec<Employee> = keyspace
+    ///     .get_bulk_as(Some(keys.clone()), None, ReadOptions::new(), None, None, None)
+    ///     .await?;
+    ///
+    /// let employees: Vec<WithPointerMetadata<Employee>> = keyspace
+    ///     .get_bulk_as(Some(keys), None, ReadOptions::new().pointers_metadata(), None, None, None)
+    ///     .await?;
+    /// ```
+    ///
+    /// # Errors
+    /// * Returns every error `get_bulk` returns
+    /// * Returns MontycatClientError::ClientServerError if the server answers with `status: false`
+    /// * Returns MontycatClientError::ClientValueParsingError if a value is not a valid `S::Value<T>`
+    ///
+    fn get_bulk_as<T, S>(
+        &self,
+        bulk_keys: Option<Vec<String>>,
+        bulk_custom_keys: Option<Vec<String>>,
+        options: ReadOptions<S>,
+        limit: Option<Limit>,
+        volumes: Option<Vec<String>>,
+        latest_volume: Option<bool>,
+    ) -> impl Future<Output = Result<Vec<S::Value<T>>, MontycatClientError>> + Send
+    where
+        T: DeserializeOwned + Send,
+        S: ReadShape,
+    {
+        async move {
+            let response: Result<Option<Vec<u8>>, MontycatClientError> = self
+                .get_bulk(
+                    bulk_keys,
+                    bulk_custom_keys,
+                    options.resolves_pointers(),
+                    S::KEY_INCLUDED,
+                    S::POINTERS_METADATA,
+                    limit,
+                    volumes,
+                    latest_volume,
+                )
+                .await;
+
+            let values: S::List<T> = parse_payload(response)?;
+            Ok(S::into_values(values))
+        }
+    }
+
+    /// Get multiple values with their pointers resolved to the values they point to
+    ///
+    /// Pointer targets are read with one `get_bulk` per keyspace and level, each keyspace addressed
+    /// as persistent or in-memory as listed by `get_structure_available`, and are available through
+    /// `Pointer::value`. Targets are resolved in turn up to `depth`
+    /// levels of pointers.
+    ///
+    /// # Arguments
+    ///
+    /// Same as `get_bulk`, with `depth` instead of `with_pointers`, `key_included` and
+    /// `with_pointers_metadata`.
+    ///
+    /// * `depth` - How many levels of pointers to resolve. 0 resolves nothing
+    ///
+    /// # Returns
+    /// * `Result<Vec<T>, MontycatClientError>` - The values, in the order the server returned them
+    ///
+    /// # Examples
+    ///
+    /// ```rust, ignore
+    /// let orders: Vec<Order> = keyspace
+    ///     .get_bulk_resolved(Some(keys), None, 1, None, None, None)
+    ///     .await?;
+    ///
+    /// let customer: Option<&Customer> = orders[0].customer.value();
+    /// ```
+    ///
+    /// # Errors
+    /// * Returns every error `get_bulk_as` returns, for the values and for their targets
+    ///
+    fn get_bulk_resolved<T>(
+        &self,
+        bulk_keys: Option<Vec<String>>,
+        bulk_custom_keys: Option<Vec<String>>,
+        depth: usize,
+        limit: Option<Limit>,
+        volumes: Option<Vec<String>>,
+        latest_volume: Option<bool>,
+    ) -> impl Future<Output = Result<Vec<T>, MontycatClientError>> + Send
+    where
+        T: DeserializeOwned + Send,
+    {
+        async move {
+            let mut documents: Vec<Value> = self
+                .get_bulk_as(
+                    bulk_keys,
+                    bulk_custom_keys,
+                    ReadOptions::new(),
+                    limit,
+                    volumes,
+                    latest_volume,
+                )
+                .await?;
+
+            resolve_pointers(self.get_engine(), &mut documents, depth).await?;
+
+            documents.into_iter().map(nested::from_value).collect()
+        }
     }
 
     /// Delete multiple values by a list of keys
@@ -558,48 +821,45 @@ where
     /// * Returns MontycatClientError if there is an error with the engine
     /// * Returns MontycatClientError if there is an error parsing the response
     ///
-    async fn delete_bulk(
+    fn delete_bulk(
         &self,
         bulk_keys: Option<Vec<String>>,
         bulk_custom_keys: Option<Vec<String>>,
-    ) -> Result<Option<Vec<u8>>, MontycatClientError> {
-        let keys_processed: Vec<String> = merge_keys(bulk_keys, bulk_custom_keys).await?;
-
-        let engine: Engine = self.get_engine();
-        let name: &str = self.get_name();
-        let persistent: bool = self.get_persistent();
-        let distributed: bool = self.get_distributed();
-        let store: String = engine
-            .store
-            .clone()
-            .ok_or(MontycatClientError::ClientStoreNotSet)?;
-        let use_tls: bool = engine.use_tls;
-        let command: String = "delete_bulk".to_string();
-
-        let new_store_req: StoreRequestClient = StoreRequestClient {
-            bulk_keys: keys_processed,
-            keyspace: name.to_owned(),
-            store,
-            persistent,
-            distributed,
-            command,
-            username: engine.username.clone(),
-            password: engine.password.clone(),
-            ..Default::default()
-        };
-
-        let bytes: Vec<u8> = Req::new_store_command(new_store_req).byte_down()?;
-        let response: Option<Vec<u8>> = send_data(
-            &engine.host,
-            engine.port,
-            bytes.as_slice(),
-            None,
-            None,
-            use_tls,
-        )
-        .await?;
-
-        Ok(response)
+    ) -> impl Future<Output = Result<Option<Vec<u8>>, MontycatClientError>> + Send {
+        async move {
+            let keys_processed: Vec<String> =
+                merge_keys(bulk_keys, bulk_custom_keys, self.get_key_hashing()).await?;
+
+            let engine: &Engine = self.get_engine();
+            let name: &str = self.get_name();
+            let persistent: bool = self.get_persistent();
+            let distributed: bool = self.get_distributed();
+            let store: &str = engine
+                .store()
+                .ok_or(MontycatClientError::ClientStoreNotSet)?;
+            let operation: StoreOperation = StoreOperation::DeleteBulk;
+
+            let registered_keys: Vec<String> = match self.get_key_registry() {
+                Some(_) => keys_processed.clone(),
+                None => Vec::new(),
+            };
+
+            let new_store_req: StoreCommand = StoreCommand {
+                bulk_keys: keys_processed,
+                keyspace: name.into(),
+                store: store.into(),
+                persistent,
+                distributed,
+                operation,
+                ..Default::default()
+            };
+
+            let response: Option<Vec<u8>> = engine
+                .send_command(&Command::Store(Box::new(new_store_req)))
+                .await?;
+
+            unregister_deleted(self, registered_keys, response).await
+        }
     }
 
     /// Get the length of the keyspace
@@ -620,41 +880,30 @@ where
     /// * `MontycatClientError::ClientEngineError` - If there is an error with the engine
     /// * `MontycatClientError::ClientValueParsingError` - If there is an error parsing the response
     ///
-    async fn get_len(&self) -> Result<Option<Vec<u8>>, MontycatClientError> {
-        let engine: Engine = self.get_engine();
-        let name: &str = self.get_name();
-        let persistent: bool = self.get_persistent();
-        let distributed: bool = self.get_distributed();
-        let store: String = engine
-            .store
-            .clone()
-            .ok_or(MontycatClientError::ClientStoreNotSet)?;
-        let use_tls: bool = engine.use_tls;
-        let command: String = "get_len".to_string();
-
-        let new_store_req: StoreRequestClient = StoreRequestClient {
-            keyspace: name.to_owned(),
-            store,
-            persistent,
-            distributed,
-            command,
-            username: engine.username.clone(),
-            password: engine.password.clone(),
-            ..Default::default()
-        };
-
-        let bytes: Vec<u8> = Req::new_store_command(new_store_req).byte_down()?;
-        let response: Option<Vec<u8>> = send_data(
-            &engine.host,
-            engine.port,
-            bytes.as_slice(),
-            None,
-            None,
-            use_tls,
-        )
-        .await?;
-
-        Ok(response)
+    fn get_len(&self) -> impl Future<Output = Result<Option<Vec<u8>>, MontycatClientError>> + Send {
+        async move {
+            let engine: &Engine = self.get_engine();
+            let name: &str = self.get_name();
+            let persistent: bool = self.get_persistent();
+            let distributed: bool = self.get_distributed();
+            let store: &str = engine
+                .store()
+                .ok_or(MontycatClientError::ClientStoreNotSet)?;
+            let operation: StoreOperation = StoreOperation::GetLen;
+
+            let new_store_req: StoreCommand = StoreCommand {
+                keyspace: name.into(),
+                store: store.into(),
+                persistent,
+                distributed,
+                operation,
+                ..Default::default()
+            };
+
+            engine
+                .send_command(&Command::Store(Box::new(new_store_req)))
+                .await
+        }
     }
 
     /// Enforce schema on the keyspace
@@ -666,6 +915,10 @@ where
     /// # Behavior
     ///
     /// Sends a request to the server to enforce the provided schema on the keyspace
+    /// If the server advertises `ServerFeature::NestedSchemas`, fields of structs registered with
+    /// `register_schema` and enums registered with `register_enum_schema` are sent with their nested
+    /// definitions, and collections with the definitions of their elements. Otherwise every field is
+    /// sent as `[type, nullable]`
     /// Returns the raw response bytes from the server
     ///
     /// # Examples
@@ -688,60 +941,48 @@ where
     /// * Returns MontycatClientError if the store is not set in the engine
     /// * Returns MontycatClientError if there is an error with the engine
     /// * Returns MontycatClientError if there is an error parsing the response
+    /// * Returns MontycatClientError::ClientUnsupported if a field holds a registered enum and the server does not support nested schemas
     ///
-    async fn enforce_schema(
+    fn enforce_schema(
         &self,
         schema_params: (std::collections::HashMap<&str, &str>, &str),
-    ) -> Result<Option<Vec<u8>>, MontycatClientError> {
-        let (fields, schema_name) = schema_params;
+    ) -> impl Future<Output = Result<Option<Vec<u8>>, MontycatClientError>> + Send {
+        async move {
+            let (fields, schema_name) = schema_params;
 
-        let mut schema_types: HashMap<String, (&'static str, bool)> = HashMap::new();
+            let mut schema_types: HashMap<String, FieldSchema> = describe_fields(fields)?;
 
-        for (field_name, field_type) in fields.into_iter() {
-            let type_def = define_type(field_type)?;
-            schema_types.insert(field_name.to_string(), type_def);
-        }
+            let engine: &Engine = self.get_engine();
+            let capabilities: &ServerCapabilities = engine.server_capabilities().await?;
+
+            if !capabilities.supports(ServerFeature::NestedSchemas) {
+                // Enums have no flat type, so they need the nested format.
+                if schema_types.values().any(FieldSchema::is_enum) {
+                    capabilities.require(ServerFeature::NestedSchemas)?;
+                }
+
+                schema_types.values_mut().for_each(FieldSchema::flatten);
+            }
+
+            let schema_types_as_string: String = serde_json::to_string(&schema_types)
+                .map_err(|e| MontycatClientError::ClientValueParsingError(e.to_string()))?;
+
+            let name: &str = self.get_name();
+            let persistent: bool = self.get_persistent();
+            let store: &str = engine
+                .store()
+                .ok_or(MontycatClientError::ClientStoreNotSet)?;
 
-        let schema_types_as_string: String = serde_json::to_string(&schema_types)
-            .map_err(|e| MontycatClientError::ClientValueParsingError(e.to_string()))?;
-
-        let engine: Engine = self.get_engine();
-        let name: &str = self.get_name();
-        let persistent: bool = self.get_persistent();
-        let store: String = engine
-            .store
-            .clone()
-            .ok_or(MontycatClientError::ClientStoreNotSet)?;
-        let use_tls: bool = engine.use_tls;
-
-        let vec: Vec<String> = vec![
-            "enforce-schema".into(),
-            "store".into(),
-            store,
-            "keyspace".into(),
-            name.to_owned(),
-            "persistent".into(),
-            if persistent { "y".into() } else { "n".into() },
-            "schema_name".into(),
-            schema_name.to_string(),
-            "schema_content".into(),
-            schema_types_as_string,
-        ];
-
-        let credentials: Vec<String> = engine.get_credentials();
-        let query: Req = Req::new_raw_command(vec, credentials);
-        let bytes: Vec<u8> = query.byte_down()?;
-        let response: Option<Vec<u8>> = send_data(
-            &engine.host,
-            engine.port,
-            bytes.as_slice(),
-            None,
-            None,
-            use_tls,
-        )
-        .await?;
-
-        Ok(response)
+            let command: Command = Command::EnforceSchema {
+                store: store.to_owned(),
+                keyspace: name.to_owned(),
+                persistent,
+                schema_name: schema_name.to_owned(),
+                schema_content: schema_types_as_string,
+            };
+
+            engine.send_command(&command).await
+        }
     }
 
     /// Remove enforced schema from the keyspace
@@ -769,47 +1010,29 @@ where
     /// * Returns MontycatClientError if there is an error with the engine
     /// * Returns MontycatClientError if there is an error parsing the response
     ///
-    async fn remove_enforced_schema(
+    fn remove_enforced_schema(
         &self,
         schema_name: (HashMap<&str, &str>, &str),
-    ) -> Result<Option<Vec<u8>>, MontycatClientError> {
-        let (_fields, schema_name) = schema_name;
-
-        let engine: Engine = self.get_engine();
-        let name: &str = self.get_name();
-        let persistent: bool = self.get_persistent();
-        let store: String = engine
-            .store
-            .clone()
-            .ok_or(MontycatClientError::ClientStoreNotSet)?;
-        let use_tls: bool = engine.use_tls;
-
-        let vec: Vec<String> = vec![
-            "remove-enforced-schema".into(),
-            "store".into(),
-            store,
-            "keyspace".into(),
-            name.to_owned(),
-            "persistent".into(),
-            if persistent { "y".into() } else { "n".into() },
-            "schema_name".into(),
-            schema_name.to_string(),
-        ];
-
-        let credentials: Vec<String> = engine.get_credentials();
-        let query: Req = Req::new_raw_command(vec, credentials);
-        let bytes: Vec<u8> = query.byte_down()?;
-        let response: Option<Vec<u8>> = send_data(
-            &engine.host,
-            engine.port,
-            bytes.as_slice(),
-            None,
-            None,
-            use_tls,
-        )
-        .await?;
-
-        Ok(response)
+    ) -> impl Future<Output = Result<Option<Vec<u8>>, MontycatClientError>> + Send {
+        async move {
+            let (_fields, schema_name) = schema_name;
+
+            let engine: &Engine = self.get_engine();
+            let name: &str = self.get_name();
+            let persistent: bool = self.get_persistent();
+            let store: &str = engine
+                .store()
+                .ok_or(MontycatClientError::ClientStoreNotSet)?;
+
+            let command: Command = Command::RemoveEnforcedSchema {
+                store: store.to_owned(),
+                keyspace: name.to_owned(),
+                persistent,
+                schema_name: schema_name.to_owned(),
+            };
+
+            engine.send_command(&command).await
+        }
     }
 
     /// List all schemas in the keyspace
@@ -830,41 +1053,32 @@ where
     /// * Returns MontycatClientError if there is an error with the engine
     /// * Returns MontycatClientError if there is an error parsing the response
     ///
-    async fn list_all_schemas_in_keyspace(&self) -> Result<Option<Vec<u8>>, MontycatClientError> {
-        let engine: Engine = self.get_engine();
-        let name: &str = self.get_name();
-        let persistent: bool = self.get_persistent();
-        let distributed: bool = self.get_distributed();
-        let store: String = engine
-            .store
-            .clone()
-            .ok_or(MontycatClientError::ClientStoreNotSet)?;
-        let use_tls: bool = engine.use_tls;
-        let command: String = "list_all_schemas_in_keyspace".to_string();
-
-        let new_store_request: StoreRequestClient = StoreRequestClient {
-            username: engine.username.clone(),
-            password: engine.password.clone(),
-            keyspace: name.to_owned(),
-            store,
-            persistent,
-            distributed,
-            command,
-            ..Default::default()
-        };
-
-        let bytes: Vec<u8> = Req::new_store_command(new_store_request).byte_down()?;
-        let response: Option<Vec<u8>> = send_data(
-            &engine.host,
-            engine.port,
-            bytes.as_slice(),
-            None,
-            None,
-            use_tls,
-        )
-        .await?;
-
-        Ok(response)
+    fn list_all_schemas_in_keyspace(
+        &self,
+    ) -> impl Future<Output = Result<Option<Vec<u8>>, MontycatClientError>> + Send {
+        async move {
+            let engine: &Engine = self.get_engine();
+            let name: &str = self.get_name();
+            let persistent: bool = self.get_persistent();
+            let distributed: bool = self.get_distributed();
+            let store: &str = engine
+                .store()
+                .ok_or(MontycatClientError::ClientStoreNotSet)?;
+            let operation: StoreOperation = StoreOperation::ListAllSchemasInKeyspace;
+
+            let new_store_request: StoreCommand = StoreCommand {
+                keyspace: name.into(),
+                store: store.into(),
+                persistent,
+                distributed,
+                operation,
+                ..Default::default()
+            };
+
+            engine
+                .send_command(&Command::Store(Box::new(new_store_request)))
+                .await
+        }
     }
 
     /// Update multiple key-value pairs in the keyspace
@@ -908,56 +1122,49 @@ where
     /// * Returns MontycatClientError if there is an error with the engine
     /// * Returns MontycatClientError if there is an error parsing the response
     ///
-    async fn update_bulk<T>(
+    fn update_bulk<T>(
         &self,
         bulk_keys_values: Vec<HashMap<String, T>>,
         bulk_custom_keys_values: Vec<HashMap<String, T>>,
-    ) -> Result<Option<Vec<u8>>, MontycatClientError>
+    ) -> impl Future<Output = Result<Option<Vec<u8>>, MontycatClientError>> + Send
     where
         T: Serialize + Send + 'static,
     {
-        if bulk_keys_values.is_empty() && bulk_custom_keys_values.is_empty() {
-            return Err(MontycatClientError::ClientNoValidInputProvided);
-        }
+        async move {
+            if bulk_keys_values.is_empty() && bulk_custom_keys_values.is_empty() {
+                return Err(MontycatClientError::ClientNoValidInputProvided);
+            }
 
-        let bulk: HashMap<String, String> =
-            merge_bulk_keys_values(bulk_keys_values, bulk_custom_keys_values).await?;
-
-        let engine: Engine = self.get_engine();
-        let name: &str = self.get_name();
-        let persistent: bool = self.get_persistent();
-        let distributed: bool = self.get_distributed();
-        let store: String = engine
-            .store
-            .clone()
-            .ok_or(MontycatClientError::ClientStoreNotSet)?;
-        let use_tls: bool = engine.use_tls;
-        let command: String = "update_value".to_string();
-
-        let new_store_request: StoreRequestClient = StoreRequestClient {
-            bulk_keys_values: bulk,
-            username: engine.username.clone(),
-            password: engine.password.clone(),
-            keyspace: name.to_owned(),
-            store,
-            persistent,
-            distributed,
-            command,
-            ..Default::default()
-        };
-
-        let bytes: Vec<u8> = Req::new_store_command(new_store_request).byte_down()?;
-        let response: Option<Vec<u8>> = send_data(
-            &engine.host,
-            engine.port,
-            bytes.as_slice(),
-            None,
-            None,
-            use_tls,
-        )
-        .await?;
-
-        Ok(response)
+            let bulk: HashMap<String, String> = merge_bulk_keys_values(
+                bulk_keys_values,
+                bulk_custom_keys_values,
+                self.get_key_hashing(),
+            )
+            .await?;
+
+            let engine: &Engine = self.get_engine();
+            let name: &str = self.get_name();
+            let persistent: bool = self.get_persistent();
+            let distributed: bool = self.get_distributed();
+            let store: &str = engine
+                .store()
+                .ok_or(MontycatClientError::ClientStoreNotSet)?;
+            let operation: StoreOperation = StoreOperation::UpdateValue;
+
+            let new_store_request: StoreCommand = StoreCommand {
+                bulk_keys_values: bulk,
+                keyspace: name.into(),
+                store: store.into(),
+                persistent,
+                distributed,
+                operation,
+                ..Default::default()
+            };
+
+            engine
+                .send_command(&Command::Store(Box::new(new_store_request)))
+                .await
+        }
     }
 
     /// Lookup keys in the keyspace based on provided filters
@@ -998,75 +1205,112 @@ where
     /// * Returns MontycatClientError if there is an error with the engine
     /// * Returns MontycatClientError if there is an error parsing the response
     ///
-    async fn lookup_keys_where<T>(
+    fn lookup_keys_where<T>(
         &self,
         search_criteria: T,
         limit: Option<Limit>,
         schema_name: Option<(HashMap<&str, &str>, &str)>,
-    ) -> Result<Option<Vec<u8>>, MontycatClientError>
+    ) -> impl Future<Output = Result<Option<Vec<u8>>, MontycatClientError>> + Send
     where
         T: Serialize + Send + 'static,
     {
-        let schema = {
-            if let Some((_, schema_name)) = schema_name {
-                Some(schema_name.to_string())
-            } else {
-                None
-            }
-        };
-
-        let engine: Engine = self.get_engine();
-        let name: &str = self.get_name();
-        let persistent: bool = self.get_persistent();
-        let distributed: bool = self.get_distributed();
-        let store: String = engine
-            .store
-            .clone()
-            .ok_or(MontycatClientError::ClientStoreNotSet)?;
-        let use_tls: bool = engine.use_tls;
-        let command: String = "lookup_keys".to_string();
-
-        let filters_serialized: String = process_json_value(&search_criteria)?;
-
-        let limit_map: HashMap<String, usize> = match limit {
-            Some(lim) => {
-                if lim.start > lim.stop {
-                    return Err(MontycatClientError::ClientGenericError(
-                        "Limit start cannot be greater than stop".into(),
-                    ));
+        async move {
+            let schema = {
+                if let Some((_, schema_name)) = schema_name {
+                    Some(schema_name.to_string())
+                } else {
+                    None
+                }
+            };
+
+            let engine: &Engine = self.get_engine();
+            let name: &str = self.get_name();
+            let persistent: bool = self.get_persistent();
+            let distributed: bool = self.get_distributed();
+            let store: &str = engine
+                .store()
+                .ok_or(MontycatClientError::ClientStoreNotSet)?;
+            let operation: StoreOperation = StoreOperation::LookupKeys;
+
+            let filters_serialized: String = process_json_value(&search_criteria)?;
+
+            let limit_map: HashMap<String, usize> = match limit {
+                Some(lim) => {
+                    if lim.start > lim.stop {
+                        return Err(MontycatClientError::ClientGenericError(
+                            "Limit start cannot be greater than stop".into(),
+                        ));
+                    }
+
+                    lim.to_map()
                 }
+                None => Limit::default_limit().to_map(),
+            };
+
+            let new_store_request: StoreCommand = StoreCommand {
+                schema,
+                limit_output: limit_map,
+                search_criteria: filters_serialized,
+                keyspace: name.into(),
+                store: store.into(),
+                persistent,
+                distributed,
+                operation,
+                ..Default::default()
+            };
+
+            engine
+                .send_command(&Command::Store(Box::new(new_store_request)))
+                .await
+        }
+    }
 
-                lim.to_map()
-            }
-            None => Limit::default_limit().to_map(),
-        };
-
-        let new_store_request: StoreRequestClient = StoreRequestClient {
-            schema,
-            limit_output: limit_map,
-            search_criteria: filters_serialized,
-            username: engine.username.clone(),
-            password: engine.password.clone(),
-            keyspace: name.to_owned(),
-            store,
-            persistent,
-            distributed,
-            command,
-            ..Default::default()
-        };
-
-        let bytes: Vec<u8> = Req::new_store_command(new_store_request).byte_down()?;
-        let response: Option<Vec<u8>> = send_data(
-            &engine.host,
-            engine.port,
-            bytes.as_slice(),
-            None,
-            None,
-            use_tls,
-        )
-        .await?;
-
-        Ok(response)
+    /// Lookup keys based on provided filters together with their original custom keys
+    ///
+    /// Resolves the keys returned by `lookup_keys_where` through the key registry.
+    ///
+    /// # Arguments
+    ///
+    /// Same as `lookup_keys_where`.
+    ///
+    /// # Returns
+    /// * `Result<Vec<ResolvedKey>, MontycatClientError>` - The matching keys, with the custom key each was created from
+    ///
+    /// # Examples
+    ///
+    /// ```rust, ignore
+    /// let keys: Vec<ResolvedKey> = keyspace
+    ///     .lookup_keys_where_resolved(json!({ "name": "Monty" }), None, None)
+    ///     .await?;
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// * Returns every error `lookup_keys_where` returns
+    /// * Returns MontycatClientError::ClientGenericError if the keyspace does not keep a key registry
+    /// * Returns MontycatClientError::ClientServerError if the server answers with `status: false`
+    ///
+    fn lookup_keys_where_resolved<T>(
+        &self,
+        search_criteria: T,
+        limit: Option<Limit>,
+        schema_name: Option<(HashMap<&str, &str>, &str)>,
+    ) -> impl Future<Output = Result<Vec<ResolvedKey>, MontycatClientError>> + Send
+    where
+        T: Serialize + Send + 'static,
+    {
+        async move {
+            let registry: KeyRegistry = self.get_key_registry().ok_or_else(|| {
+                MontycatClientError::ClientGenericError(KEY_REGISTRY_DISABLED.into())
+            })?;
+
+            let keys: Vec<String> = parse_keys(
+                self.lookup_keys_where(search_criteria, limit, schema_name)
+                    .await,
+            )?;
+
+            registry.resolve(keys).await
+        }
     }
 
     /// Lookup values in the keyspace based on provided filters
@@ -1108,8 +1352,9 @@ where
     /// * Returns MontycatClientError if the store is not set in the engine
     /// * Returns MontycatClientError if there is an error with the engine
     /// * Returns MontycatClientError if there is an error parsing the response
+    /// * Returns MontycatClientError::ClientUnsupported if the server does not support pointers metadata
     ///
-    async fn lookup_values_where<T>(
+    fn lookup_values_where<T>(
         &self,
         search_criteria: T,
         limit: Option<Limit>,
@@ -1117,72 +1362,169 @@ where
         key_included: bool,
         pointers_metadata: bool,
         schema_name: Option<(HashMap<&str, &str>, &str)>,
-    ) -> Result<Option<Vec<u8>>, MontycatClientError>
+    ) -> impl Future<Output = Result<Option<Vec<u8>>, MontycatClientError>> + Send
     where
         T: Serialize + Send + 'static,
     {
-        let schema = {
-            if let Some((_, schema_name)) = schema_name {
-                Some(schema_name.to_string())
-            } else {
-                None
-            }
-        };
-
-        let engine: Engine = self.get_engine();
-        let name: &str = self.get_name();
-        let persistent: bool = self.get_persistent();
-        let distributed: bool = self.get_distributed();
-        let store: String = engine
-            .store
-            .clone()
-            .ok_or(MontycatClientError::ClientStoreNotSet)?;
-        let use_tls: bool = engine.use_tls;
-        let command: String = "lookup_values".to_string();
-
-        let filters_serialized: String = process_json_value(&search_criteria)?;
-
-        let limit_map: HashMap<String, usize> = match limit {
-            Some(lim) => {
-                if lim.start > lim.stop {
-                    return Err(MontycatClientError::ClientGenericError(
-                        "Limit start cannot be greater than stop".into(),
-                    ));
+        async move {
+            let schema = {
+                if let Some((_, schema_name)) = schema_name {
+                    Some(schema_name.to_string())
+                } else {
+                    None
                 }
+            };
+
+            let engine: &Engine = self.get_engine();
 
-                lim.to_map()
+            if pointers_metadata {
+                engine.require(ServerFeature::PointersMetadata).await?;
             }
-            None => Limit::default_limit().to_map(),
-        };
-
-        let new_store_request: StoreRequestClient = StoreRequestClient {
-            with_pointers,
-            key_included,
-            pointers_metadata,
-            schema,
-            limit_output: limit_map,
-            search_criteria: filters_serialized,
-            username: engine.username.clone(),
-            password: engine.password.clone(),
-            keyspace: name.to_owned(),
-            store,
-            persistent,
-            distributed,
-            command,
-            ..Default::default()
-        };
-
-        let bytes: Vec<u8> = Req::new_store_command(new_store_request).byte_down()?;
-        let response: Option<Vec<u8>> = send_data(
-            &engine.host,
-            engine.port,
-            bytes.as_slice(),
-            None,
-            None,
-            use_tls,
-        )
-        .await?;
-
-        Ok(response)
+            let name: &str = self.get_name();
+            let persistent: bool = self.get_persistent();
+            let distributed: bool = self.get_distributed();
+            let store: &str = engine
+                .store()
+                .ok_or(MontycatClientError::ClientStoreNotSet)?;
+            let operation: StoreOperation = StoreOperation::LookupValues;
+
+            let filters_serialized: String = process_json_value(&search_criteria)?;
+
+            let limit_map: HashMap<String, usize> = match limit {
+                Some(lim) => {
+                    if lim.start > lim.stop {
+                        return Err(MontycatClientError::ClientGenericError(
+                            "Limit start cannot be greater than stop".into(),
+                        ));
+                    }
+
+                    lim.to_map()
+                }
+                None => Limit::default_limit().to_map(),
+            };
+
+            let new_store_request: StoreCommand = StoreCommand {
+                with_pointers,
+                key_included,
+                pointers_metadata,
+                schema,
+                limit_output: limit_map,
+                search_criteria: filters_serialized,
+                keyspace: name.into(),
+                store: store.into(),
+                persistent,
+                distributed,
+                operation,
+                ..Default::default()
+            };
+
+            engine
+                .send_command(&Command::Store(Box::new(new_store_request)))
+                .await
+        }
+    }
+
+    /// Lookup values in the keyspace based on provided filters, deserialized according to `options`
+    ///
+    /// Each value is returned as `T`, `Keyed<T>`, `WithPointerMetadata<T>` or
+    /// `Keyed<WithPointerMetadata<T>>`, depending on the options requested, see `ReadShape`.
+    ///
+    /// # Arguments
+    ///
+    /// Same as `lookup_values_where`, with `options` instead of `with_pointers`, `key_included` and
+    /// `pointers_metadata`.
+    ///
+    /// # Returns
+    /// * `Result<Vec<S::Value<T>>, MontycatClientError>` - The matching values
+    ///
+    /// # Examples
+    ///
+    /// ```rust, ignore
+    /// let employees: Vec<Keyed<Employee>> = keyspace
+    ///     .lookup_values_where_as(json!({ "name": "Monty" }), None, ReadOptions::new().key_included(), Some(Employee::schema_params()))
+    ///     .await?;
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// * Returns every error `lookup_values_where` returns
+    /// * Returns MontycatClientError::ClientServerError if the server answers with `status: false`
+    /// * Returns MontycatClientError::ClientValueParsingError if a value is not a valid `S::Value<T>`
+    ///
+    fn lookup_values_where_as<T, F, S>(
+        &self,
+        search_criteria: F,
+        limit: Option<Limit>,
+        options: ReadOptions<S>,
+        schema_name: Option<(HashMap<&str, &str>, &str)>,
+    ) -> impl Future<Output = Result<Vec<S::Value<T>>, MontycatClientError>> + Send
+    where
+        T: DeserializeOwned + Send,
+        F: Serialize + Send + 'static,
+        S: ReadShape,
+    {
+        async move {
+            let response: Result<Option<Vec<u8>>, MontycatClientError> = self
+                .lookup_values_where(
+                    search_criteria,
+                    limit,
+                    options.resolves_pointers(),
+                    S::KEY_INCLUDED,
+                    S::POINTERS_METADATA,
+                    schema_name,
+                )
+                .await;
+
+            let values: S::List<T> = parse_payload(response)?;
+            Ok(S::into_values(values))
+        }
+    }
+
+    /// Lookup values based on provided filters, with their pointers resolved to the values they point to
+    ///
+    /// See `get_bulk_resolved`.
+    ///
+    /// # Arguments
+    ///
+    /// Same as `lookup_values_where`, with `depth` instead of `with_pointers`, `key_included` and
+    /// `pointers_metadata`.
+    ///
+    /// * `depth` - How many levels of pointers to resolve. 0 resolves nothing
+    ///
+    /// # Returns
+    /// * `Result<Vec<T>, MontycatClientError>` - The matching values
+    ///
+    /// # Examples
+    ///
+    /// ```rust, ignore
+    /// let orders: Vec<Order> = keyspace
+    ///     .lookup_values_where_resolved(json!({ "status": "open" }), None, 2, Some(Order::schema_params()))
+    ///     .await?;
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// * Returns every error `lookup_values_where_as` returns, for the values and for their targets
+    ///
+    fn lookup_values_where_resolved<T, F>(
+        &self,
+        search_criteria: F,
+        limit: Option<Limit>,
+        depth: usize,
+        schema_name: Option<(HashMap<&str, &str>, &str)>,
+    ) -> impl Future<Output = Result<Vec<T>, MontycatClientError>> + Send
+    where
+        T: DeserializeOwned + Send,
+        F: Serialize + Send + 'static,
+    {
+        async move {
+            let mut documents: Vec<Value> = self
+                .lookup_values_where_as(search_criteria, limit, ReadOptions::new(), schema_name)
+                .await?;
+
+            resolve_pointers(self.get_engine(), &mut documents, depth).await?;
+
+            documents.into_iter().map(nested::from_value).collect()
+        }
     }
 }
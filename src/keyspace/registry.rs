@@ -0,0 +1,483 @@
+use super::{pubtrait::Keyspace, structures::persistent::PersistentKeyspace};
+use crate::{
+    MontycatClientError,
+    engine::structure::Engine,
+    request::command::{Command, StoreCommand, StoreOperation},
+    response::{
+        structure::MontycatResponse,
+        typed::{Keyed, ReadOptions},
+    },
+    tools::functions::process_json_value,
+};
+use serde::{Deserialize, Serialize, de::IgnoredAny};
+use std::collections::{HashMap, HashSet};
+
+/// Suffix of the keyspace that holds the key registry of a keyspace.
+pub const KEY_REGISTRY_SUFFIX: &str = "_key_registry";
+
+/// Persistent keyspace listing the key registry keyspaces of a store, so tools such as
+/// `integrity::audit_store` can tell them apart from data keyspaces without relying on their names.
+pub const KEY_REGISTRY_CATALOG: &str = "__key_registries";
+
+/// Error message for resolving keys of a keyspace without a key registry.
+pub(crate) const KEY_REGISTRY_DISABLED: &str =
+    "The keyspace does not keep a key registry. Enable it with `with_key_registry(true)`";
+
+/// Registry entry stored under the same custom key as the value it describes.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct RegistryEntry {
+    custom_key: String,
+}
+
+/// Catalog entry marking a keyspace as a key registry, stored under the registry name.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct CatalogEntry {
+    keyspace: String,
+}
+
+/// A key together with the custom key it was hashed from.
+///
+/// # Fields
+/// - `key: String` : The stored (hashed) key.
+/// - `custom_key: Option<String>` : The original custom key, or None if the key is not registered.
+///
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ResolvedKey {
+    pub key: String,
+    pub custom_key: Option<String>,
+}
+
+/// Reverse mapping from hashed keys to the custom keys they were created from.
+///
+/// The registry of keyspace `name` is the persistent keyspace `name_key_registry` in the same store.
+/// Each entry is inserted under the same custom key as the value, with the same key hashing, so it
+/// is stored under the same key. Keyspaces created with `with_key_registry(true)` register custom
+/// keys on insert and unregister keys on delete.
+///
+/// # Examples
+///
+/// ```rust, ignore
+/// let keyspace = PersistentKeyspace::new("users", &engine).with_key_registry(true);
+/// keyspace.get_key_registry().unwrap().create().await?;
+///
+/// keyspace.insert_value(Some("alice@example.com".into()), user).await?;
+///
+/// let keys: Vec<ResolvedKey> = keyspace.get_keys_resolved(None, None, None).await?;
+/// ```
+///
+/// # Notes
+///
+/// Entries of expired in-memory values stay in the registry until their key is deleted.
+///
+/// The insert or delete has already succeeded when the registry is written. If the registry write
+/// fails, the insert or delete returns `MontycatClientError::ClientKeyRegistryOutOfSync`, which
+/// carries its response; call `register` or `unregister` to bring the registry back in sync.
+///
+#[derive(Debug, Clone)]
+pub struct KeyRegistry {
+    keyspace: PersistentKeyspace,
+}
+
+impl KeyRegistry {
+    /// Returns the registry of a keyspace.
+    ///
+    /// # Arguments
+    /// - `keyspace: &K` : The keyspace whose custom keys are registered.
+    ///
+    pub fn for_keyspace<K: Keyspace>(keyspace: &K) -> Self {
+        let name: String = format!("{}{}", keyspace.get_name(), KEY_REGISTRY_SUFFIX);
+
+        Self {
+            keyspace: PersistentKeyspace::new(&name, keyspace.get_engine())
+                .with_key_hashing(keyspace.get_key_hashing()),
+        }
+    }
+
+    /// Returns the keyspace that holds the registry.
+    pub fn keyspace(&self) -> &PersistentKeyspace {
+        &self.keyspace
+    }
+
+    /// Creates the registry keyspace and marks it as a key registry in `KEY_REGISTRY_CATALOG`.
+    ///
+    /// # Errors
+    /// - Every error `PersistentKeyspace::create_keyspace` and `KeyRegistry::mark` return.
+    ///
+    pub async fn create(&self) -> Result<Option<Vec<u8>>, MontycatClientError> {
+        let response: Option<Vec<u8>> = self.keyspace.create_keyspace(None, None).await?;
+
+        if succeeded(&response).is_ok() {
+            self.mark().await?;
+        }
+
+        Ok(response)
+    }
+
+    /// Records the registry keyspace in the store's `KEY_REGISTRY_CATALOG`, creating the catalog
+    /// if needed. `create` does this already; call it for registries created without it.
+    ///
+    /// # Errors
+    /// - `MontycatClientError::ClientServerError` : If the server rejects the catalog entry.
+    /// - Every error `PersistentKeyspace::insert_value_no_schema` returns.
+    ///
+    pub async fn mark(&self) -> Result<(), MontycatClientError> {
+        let engine: &Engine = self.keyspace.get_engine();
+        let catalog: PersistentKeyspace = PersistentKeyspace::new(KEY_REGISTRY_CATALOG, engine);
+
+        // Fails if the catalog already exists, which is fine.
+        catalog.create_keyspace(None, None).await?;
+
+        let name: &str = self.keyspace.get_name();
+        let entry: CatalogEntry = CatalogEntry {
+            keyspace: name.to_owned(),
+        };
+
+        succeeded(
+            &catalog
+                .insert_value_no_schema(Some(name.to_owned()), entry)
+                .await?,
+        )
+    }
+
+    /// Registers a custom key.
+    ///
+    /// # Arguments
+    /// - `custom_key: &str` : The original custom key.
+    ///
+    /// # Errors
+    /// - `MontycatClientError::ClientServerError` : If the server rejects the entry.
+    /// - Every error `PersistentKeyspace::insert_value_no_schema` returns.
+    ///
+    pub async fn register(&self, custom_key: &str) -> Result<(), MontycatClientError> {
+        let entry: RegistryEntry = RegistryEntry {
+            custom_key: custom_key.to_owned(),
+        };
+
+        let request: StoreCommand = StoreCommand {
+            operation: StoreOperation::InsertCustomKeyValue,
+            key: Some(self.keyspace.get_key_hashing().hash(custom_key)),
+            value: process_json_value(&entry)?,
+            ..Default::default()
+        };
+
+        succeeded(&self.send(request).await?)
+    }
+
+    /// Removes the entries of stored (hashed) keys.
+    ///
+    /// # Arguments
+    /// - `keys: Vec<String>` : The stored keys to unregister.
+    ///
+    /// # Errors
+    /// - `MontycatClientError::ClientServerError` : If the server rejects the delete.
+    /// - `MontycatClientError::ClientStoreNotSet` : If the store is not set in the engine.
+    /// - `MontycatClientError::ClientEngineError` : If there is an error with the engine.
+    ///
+    pub async fn unregister(&self, keys: Vec<String>) -> Result<(), MontycatClientError> {
+        if keys.is_empty() {
+            return Ok(());
+        }
+
+        let request: StoreCommand = StoreCommand {
+            operation: StoreOperation::DeleteBulk,
+            bulk_keys: keys,
+            ..Default::default()
+        };
+
+        succeeded(&self.send(request).await?)
+    }
+
+    /// Resolves stored (hashed) keys to their original custom keys.
+    ///
+    /// # Arguments
+    /// - `keys: Vec<String>` : The stored keys to resolve.
+    ///
+    /// # Returns
+    /// - `Result<Vec<ResolvedKey>, MontycatClientError>` : One entry per key, in the given order.
+    ///   Keys without a registry entry have no custom key.
+    ///
+    /// # Errors
+    /// - Every error `Keyspace::get_bulk_as` returns.
+    ///
+    pub async fn resolve(
+        &self,
+        keys: Vec<String>,
+    ) -> Result<Vec<ResolvedKey>, MontycatClientError> {
+        if keys.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let entries: Vec<Keyed<Option<RegistryEntry>>> = self
+            .keyspace
+            .get_bulk_as(
+                Some(keys.clone()),
+                None,
+                ReadOptions::new().key_included(),
+                None,
+                None,
+                None,
+            )
+            .await?;
+
+        let mut custom_keys: HashMap<String, String> = entries
+            .into_iter()
+            .filter_map(|entry| Some((entry.key, entry.value?.custom_key)))
+            .collect();
+
+        Ok(keys
+            .into_iter()
+            .map(|key| {
+                let custom_key: Option<String> = custom_keys.remove(&key);
+                ResolvedKey { key, custom_key }
+            })
+            .collect())
+    }
+
+    /// Sends a store command to the registry keyspace. Registry writes bypass the keyspace
+    /// methods, which would otherwise call back into the registry.
+    async fn send(&self, request: StoreCommand) -> Result<Option<Vec<u8>>, MontycatClientError> {
+        let engine: &Engine = self.keyspace.get_engine();
+        let store: &str = engine
+            .store()
+            .ok_or(MontycatClientError::ClientStoreNotSet)?;
+
+        let request: StoreCommand = StoreCommand {
+            keyspace: self.keyspace.get_name().to_owned(),
+            store: store.to_owned(),
+            persistent: true,
+            distributed: false,
+            ..request
+        };
+
+        engine
+            .send_command(&Command::Store(Box::new(request)))
+            .await
+    }
+}
+
+/// Returns the keyspaces marked as key registries in the store of `engine`, and the catalog itself.
+/// A store without a catalog has no marked registries.
+///
+/// # Errors
+/// - Every error `Keyspace::lookup_values_where_as` returns, except a rejected lookup.
+///
+pub(crate) async fn registry_keyspaces(
+    engine: &Engine,
+) -> Result<HashSet<String>, MontycatClientError> {
+    let entries: Vec<CatalogEntry> = match PersistentKeyspace::new(KEY_REGISTRY_CATALOG, engine)
+        .lookup_values_where_as(
+            serde_json::Value::Object(serde_json::Map::new()),
+            None,
+            ReadOptions::new(),
+            None,
+        )
+        .await
+    {
+        Ok(entries) => entries,
+        Err(MontycatClientError::ClientServerError(_)) => Vec::new(),
+        Err(error) => return Err(error),
+    };
+
+    Ok(entries
+        .into_iter()
+        .map(|entry| entry.keyspace)
+        .chain([KEY_REGISTRY_CATALOG.to_owned()])
+        .collect())
+}
+
+/// Returns an error if a write response has `status: false`.
+pub(crate) fn succeeded(response: &Option<Vec<u8>>) -> Result<(), MontycatClientError> {
+    match response {
+        Some(bytes) => MontycatResponse::<Option<IgnoredAny>>::from_slice(bytes)?
+            .into_result()
+            .map(|_| ()),
+        None => Ok(()),
+    }
+}
+
+/// Registers the custom key of a successful insert, if the keyspace keeps a key registry.
+///
+/// # Errors
+/// - `MontycatClientError::ClientKeyRegistryOutOfSync` : If the registry write fails, carrying the
+///   insert response.
+///
+pub(crate) async fn register_inserted<K: Keyspace>(
+    keyspace: &K,
+    custom_key: Option<&str>,
+    response: Option<Vec<u8>>,
+) -> Result<Option<Vec<u8>>, MontycatClientError> {
+    if let (Some(registry), Some(custom_key)) = (keyspace.get_key_registry(), custom_key)
+        && succeeded(&response).is_ok()
+        && let Err(error) = registry.register(custom_key).await
+    {
+        return Err(MontycatClientError::ClientKeyRegistryOutOfSync {
+            error: format!(
+                "registering custom key '{}': {}",
+                custom_key,
+                error.message()
+            ),
+            response,
+        });
+    }
+
+    Ok(response)
+}
+
+/// Unregisters the keys of a successful delete, if the keyspace keeps a key registry.
+///
+/// # Errors
+/// - `MontycatClientError::ClientKeyRegistryOutOfSync` : If the registry write fails, carrying the
+///   delete response.
+///
+pub(crate) async fn unregister_deleted<K: Keyspace>(
+    keyspace: &K,
+    keys: Vec<String>,
+    response: Option<Vec<u8>>,
+) -> Result<Option<Vec<u8>>, MontycatClientError> {
+    if let Some(registry) = keyspace.get_key_registry()
+        && succeeded(&response).is_ok()
+    {
+        let description: String = format!("unregistering keys {:?}", keys);
+
+        if let Err(error) = registry.unregister(keys).await {
+            return Err(MontycatClientError::ClientKeyRegistryOutOfSync {
+                error: format!("{}: {}", description, error.message()),
+                response,
+            });
+        }
+    }
+
+    Ok(response)
+}
+
+#[cfg(all(test, not(feature = "tls")))]
+mod tests {
+    use super::*;
+    use crate::test_support::MockServer;
+    use crate::{Engine, KeyHashing};
+
+    #[test]
+    fn test_registry_follows_inserts_deletes_and_resolves_keys() {
+        tokio_test::block_on(async {
+            let hash: String = KeyHashing::Xxh3_128.hash("alice");
+            let ok: String = r#"{"status":true,"payload":null,"error":null}"#.to_string();
+            let server = MockServer::responses(vec![
+                    ok.clone(),
+                    ok.clone(),
+                    format!(
+                        r#"{{"status":true,"payload":["{}","7"],"error":null}}"#,
+                        hash
+                    ),
+                    format!(
+                        r#"{{"status":true,"payload":{{"{}":"{{\"custom_key\":\"alice\"}}"}},"error":null}}"#,
+                        hash
+                    ),
+                    ok.clone(),
+                    ok,
+                ]).await;
+
+            let engine = server.engine();
+            let keyspace: PersistentKeyspace = PersistentKeyspace::new("users", &engine)
+                .with_key_hashing(KeyHashing::Xxh3_128)
+                .with_key_registry(true);
+
+            keyspace
+                .insert_value_no_schema(Some("alice".into()), serde_json::json!({ "age": 30 }))
+                .await
+                .unwrap();
+            let keys: Vec<ResolvedKey> =
+                keyspace.get_keys_resolved(None, None, None).await.unwrap();
+            keyspace.delete_key(None, Some("alice")).await.unwrap();
+
+            let requests: Vec<String> = server.requests().await;
+            let registry_key: String = format!("\"key\":\"{}\"", hash);
+
+            assert!(requests[1].contains("\"keyspace\":\"users_key_registry\""));
+            assert!(requests[1].contains(&registry_key));
+            assert!(requests[3].contains("\"keyspace\":\"users_key_registry\""));
+            assert!(requests[5].contains("\"keyspace\":\"users_key_registry\""));
+            assert!(requests[5].contains("\"delete_bulk\""));
+            assert_eq!(
+                keys,
+                vec![
+                    ResolvedKey {
+                        key: hash.clone(),
+                        custom_key: Some("alice".into())
+                    },
+                    ResolvedKey {
+                        key: "7".into(),
+                        custom_key: None
+                    },
+                ]
+            );
+        });
+    }
+
+    #[test]
+    fn test_failed_registry_write_returns_the_write_response() {
+        tokio_test::block_on(async {
+            let ok: &str = r#"{"status":true,"payload":null,"error":null}"#;
+            let rejected: &str = r#"{"status":false,"payload":null,"error":"Keyspace not found"}"#;
+            let server = MockServer::responses([ok, rejected, ok, rejected]).await;
+
+            let engine = server.engine();
+            let keyspace: PersistentKeyspace =
+                PersistentKeyspace::new("users", &engine).with_key_registry(true);
+
+            let inserted: MontycatClientError = keyspace
+                .insert_value_no_schema(Some("alice".into()), serde_json::json!({ "age": 30 }))
+                .await
+                .unwrap_err();
+            let deleted: MontycatClientError =
+                keyspace.delete_key(None, Some("alice")).await.unwrap_err();
+
+            for error in [&inserted, &deleted] {
+                let MontycatClientError::ClientKeyRegistryOutOfSync { error, response } = error
+                else {
+                    panic!("expected ClientKeyRegistryOutOfSync, got {:?}", error);
+                };
+                assert!(succeeded(response).is_ok());
+                assert!(error.ends_with("Keyspace not found"));
+            }
+            assert!(inserted.message().contains("custom key 'alice'"));
+            assert_eq!(server.requests().await.len(), 4);
+        });
+    }
+
+    #[test]
+    fn test_create_marks_the_registry_in_the_catalog() {
+        tokio_test::block_on(async {
+            let ok: &str = r#"{"status":true,"payload":null,"error":null}"#;
+            let server = MockServer::responses([ok; 3]).await;
+
+            let engine = server.engine();
+            let keyspace: PersistentKeyspace =
+                PersistentKeyspace::new("users", &engine).with_key_registry(true);
+            keyspace.get_key_registry().unwrap().create().await.unwrap();
+
+            let requests: Vec<String> = server.requests().await;
+            assert!(requests[0].contains("users_key_registry"));
+            assert!(requests[1].contains(KEY_REGISTRY_CATALOG));
+            assert!(requests[2].contains("insert_custom_key_value"));
+            assert!(requests[2].contains(r#"\"keyspace\":\"users_key_registry\""#));
+        });
+    }
+
+    #[test]
+    fn test_resolving_without_registry_fails() {
+        let engine = Engine::new(
+            "127.0.0.1".to_string(),
+            1,
+            "user".to_string(),
+            "pass".to_string(),
+            Some("store".to_string()),
+            false,
+        );
+        let keyspace: PersistentKeyspace = PersistentKeyspace::new("users", &engine);
+
+        let result = tokio_test::block_on(keyspace.get_keys_resolved(None, None, None));
+
+        assert!(keyspace.get_key_registry().is_none());
+        assert_eq!(result.unwrap_err().message(), KEY_REGISTRY_DISABLED);
+    }
+}
@@ -0,0 +1,431 @@
+use super::{
+    pubtrait::Keyspace,
+    structures::{inmemory::InMemoryKeyspace, persistent::PersistentKeyspace},
+};
+use crate::{
+    MontycatClientError,
+    engine::structure::Engine,
+    response::{
+        structure::parse_payload,
+        typed::{Keyed, ReadOptions},
+    },
+};
+use serde::Deserialize;
+use serde_json::{Value, json};
+use std::collections::{HashMap, HashSet};
+
+/// Keyspace and key of a pointer target.
+pub(crate) type Target = (String, String);
+
+/// Resolves the pointers of stored documents, up to `depth` levels of pointers.
+///
+/// Targets are read level by level with one `get_bulk` per keyspace, so documents pointing to the
+/// same keyspace share a request and every target is read once. Target keyspaces are read as
+/// persistent or in-memory according to one `get_structure_available` lookup; targets in keyspaces
+/// the store does not list do not exist. Each resolved pointer in a `pointers` section is replaced
+/// by `{"keyspace", "key", "value"}`, which `Pointer<T>` reads back with its value. Pointers whose
+/// target does not exist keep their `[keyspace, key]` form.
+///
+/// # Arguments
+/// - `engine: &Engine` : The engine of the store the targets are in.
+/// - `documents: &mut [Value]` : The documents, as stored.
+/// - `depth: usize` : How many levels of pointers to follow. 0 resolves nothing.
+///
+/// # Errors
+/// - Every error `store_keyspaces` and `Keyspace::get_bulk_as` return.
+///
+pub(crate) async fn resolve_pointers(
+    engine: &Engine,
+    documents: &mut [Value],
+    depth: usize,
+) -> Result<(), MontycatClientError> {
+    let mut resolved: HashMap<Target, Value> = HashMap::new();
+    let mut frontier: Vec<Target> = Vec::new();
+    let mut persistence: Option<HashMap<String, bool>> = None;
+
+    for document in documents.iter() {
+        collect_targets(document, &mut frontier);
+    }
+
+    for _ in 0..depth {
+        let mut by_keyspace: HashMap<String, HashSet<String>> = HashMap::new();
+        for (keyspace, key) in frontier.drain(..) {
+            if !resolved.contains_key(&(keyspace.clone(), key.clone())) {
+                by_keyspace.entry(keyspace).or_default().insert(key);
+            }
+        }
+
+        if by_keyspace.is_empty() {
+            break;
+        }
+
+        let persistence: &HashMap<String, bool> = match &mut persistence {
+            Some(persistence) => persistence,
+            none => none.insert(store_keyspaces(engine).await?.into_iter().collect()),
+        };
+
+        for (keyspace, keys) in by_keyspace {
+            let keys: Vec<String> = keys.into_iter().collect();
+            let values: Vec<Keyed<Value>> = match persistence.get(&keyspace) {
+                Some(persistent) => get_keyed(engine, &keyspace, *persistent, keys.clone()).await?,
+                None => Vec::new(),
+            };
+
+            let mut found: HashMap<String, Value> = values
+                .into_iter()
+                .map(|keyed| (keyed.key, stored_document(keyed.value)))
+                .collect();
+
+            for key in keys {
+                let value: Value = found.remove(&key).unwrap_or(Value::Null);
+                collect_targets(&value, &mut frontier);
+                resolved.insert((keyspace.clone(), key), value);
+            }
+        }
+    }
+
+    for document in documents.iter_mut() {
+        substitute(document, &resolved, depth);
+    }
+
+    Ok(())
+}
+
+/// Lists the keyspaces of the engine's store with `get_structure_available`, with whether each
+/// is persistent.
+///
+/// # Errors
+/// - `MontycatClientError::ClientStoreNotSet` : If the store is not set in the engine.
+/// - `MontycatClientError::ClientServerError` : If the server rejects the request.
+/// - Every error `parse_structure` returns.
+///
+pub(crate) async fn store_keyspaces(
+    engine: &Engine,
+) -> Result<Vec<(String, bool)>, MontycatClientError> {
+    let store: &str = engine
+        .store()
+        .ok_or(MontycatClientError::ClientStoreNotSet)?;
+
+    parse_structure(
+        parse_payload::<Value>(engine.get_structure_available().await)?,
+        store,
+    )
+}
+
+/// Reads `keys` from a keyspace with their keys, addressing it as persistent or in-memory.
+pub(crate) async fn get_keyed(
+    engine: &Engine,
+    keyspace: &str,
+    persistent: bool,
+    keys: Vec<String>,
+) -> Result<Vec<Keyed<Value>>, MontycatClientError> {
+    let options: ReadOptions<_> = ReadOptions::new().key_included();
+
+    if persistent {
+        PersistentKeyspace::new(keyspace, engine)
+            .get_bulk_as(Some(keys), None, options, None, None, None)
+            .await
+    } else {
+        InMemoryKeyspace::new(keyspace, engine)
+            .get_bulk_as(Some(keys), None, options, None, None, None)
+            .await
+    }
+}
+
+/// Values may come back as the JSON text they are stored as.
+pub(crate) fn stored_document(value: Value) -> Value {
+    match value {
+        Value::String(text) => serde_json::from_str(&text).unwrap_or(Value::String(text)),
+        value => value,
+    }
+}
+
+/// Returns the `[keyspace, key]` pair of an encoded pointer.
+pub(crate) fn as_target(encoded: &Value) -> Option<Target> {
+    match encoded.as_array()?.as_slice() {
+        [Value::String(keyspace), Value::String(key)] => Some((keyspace.clone(), key.clone())),
+        _ => None,
+    }
+}
+
+/// Keyspaces of one store in the `get_structure_available` payload.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct StoreStructure {
+    persistent: Vec<String>,
+    in_memory: Vec<String>,
+}
+
+/// Keyspaces listed by `get_structure_available`, with whether each is persistent.
+///
+/// The payload maps each store to its `persistent` and `in_memory` keyspace names, e.g.
+/// `{"shop": {"persistent": ["orders"], "in_memory": ["sessions"]}}`, and may come as the JSON text
+/// of that map. Persistent keyspaces are listed first.
+///
+/// # Errors
+/// - `MontycatClientError::ClientValueParsingError` : If the payload has any other shape, or does
+///   not list `store`.
+///
+pub(crate) fn parse_structure(
+    payload: Value,
+    store: &str,
+) -> Result<Vec<(String, bool)>, MontycatClientError> {
+    let mut stores: HashMap<String, StoreStructure> =
+        serde_json::from_value(stored_document(payload)).map_err(|e| {
+            MontycatClientError::ClientValueParsingError(format!(
+                "Unexpected get_structure_available payload: {}",
+                e
+            ))
+        })?;
+
+    let structure: StoreStructure = stores.remove(store).ok_or_else(|| {
+        MontycatClientError::ClientValueParsingError(format!(
+            "get_structure_available did not list store '{}'",
+            store
+        ))
+    })?;
+
+    Ok(structure
+        .persistent
+        .into_iter()
+        .map(|name| (name, true))
+        .chain(structure.in_memory.into_iter().map(|name| (name, false)))
+        .collect())
+}
+
+fn collect_targets(document: &Value, targets: &mut Vec<Target>) {
+    fn collect(encoded: &Value, targets: &mut Vec<Target>) {
+        if let Some(target) = as_target(encoded) {
+            targets.push(target);
+            return;
+        }
+
+        match encoded {
+            Value::Array(items) => items.iter().for_each(|item| collect(item, targets)),
+            Value::Object(entries) => entries.values().for_each(|entry| collect(entry, targets)),
+            _ => {}
+        }
+    }
+
+    if let Some(pointers) = document.get("pointers") {
+        collect(pointers, targets);
+    }
+}
+
+fn substitute(document: &mut Value, resolved: &HashMap<Target, Value>, depth: usize) {
+    fn replace(encoded: &mut Value, resolved: &HashMap<Target, Value>, depth: usize) {
+        if let Some(target) = as_target(encoded) {
+            if let Some(value) = resolved.get(&target).filter(|value| !value.is_null()) {
+                let mut value: Value = value.clone();
+                substitute(&mut value, resolved, depth - 1);
+                *encoded = json!({ "keyspace": target.0, "key": target.1, "value": value });
+            }
+            return;
+        }
+
+        match encoded {
+            Value::Array(items) => items
+                .iter_mut()
+                .for_each(|item| replace(item, resolved, depth)),
+            Value::Object(entries) => entries
+                .values_mut()
+                .for_each(|entry| replace(entry, resolved, depth)),
+            _ => {}
+        }
+    }
+
+    if depth == 0 {
+        return;
+    }
+
+    if let Some(pointers) = document.get_mut("pointers") {
+        replace(pointers, resolved, depth);
+    }
+}
+
+#[cfg(all(test, not(feature = "tls")))]
+mod tests {
+    use super::parse_structure;
+    use crate::response::structure::parse_payload;
+    use crate::test_support::MockServer;
+    use crate::{Keyspace, MontycatClientError, PersistentKeyspace, Pointer};
+    use serde::Deserialize;
+    use serde_json::{Value, json};
+
+    #[derive(Debug, Deserialize)]
+    struct Region {
+        name: String,
+    }
+
+    #[derive(Debug, Deserialize)]
+    struct Customer {
+        name: String,
+        region: Pointer<Region>,
+    }
+
+    #[derive(Debug, Deserialize)]
+    struct Order {
+        id: u32,
+        customer: Pointer<Customer>,
+        reviewers: Vec<Pointer>,
+    }
+
+    fn stored(document: Value) -> Value {
+        Value::String(document.to_string())
+    }
+
+    fn structure() -> Value {
+        json!({
+            "store": {
+                "persistent": ["orders", "customers", "regions"],
+                "in_memory": ["staff"],
+            },
+        })
+    }
+
+    #[test]
+    fn test_get_bulk_resolved_batches_targets_per_keyspace() {
+        tokio_test::block_on(async {
+            let server = MockServer::payloads(5, |request| {
+                if request["raw"][0] == "get-structure-available" {
+                    return structure();
+                }
+
+                match request["keyspace"].as_str().unwrap() {
+                    "orders" => json!([
+                        stored(json!({
+                            "id": 1,
+                            "reviewers": [],
+                            "pointers": { "customer": ["customers", "c1"] },
+                        })),
+                        stored(json!({
+                            "id": 2,
+                            "pointers": {
+                                "customer": ["customers", "c2"],
+                                "reviewers": [["staff", "s1"], ["staff", "gone"]],
+                            },
+                        })),
+                    ]),
+                    "customers" => json!({
+                        "c1": stored(json!({ "name": "Ann", "pointers": { "region": ["regions", "r1"] } })),
+                        "c2": stored(json!({ "name": "Bob", "pointers": { "region": ["regions", "r1"] } })),
+                    }),
+                    "staff" => json!({ "s1": stored(json!({ "name": "Sam" })), "gone": null }),
+                    "regions" => json!({ "r1": stored(json!({ "name": "North" })) }),
+                    other => panic!("unexpected keyspace {}", other),
+                }
+            }).await;
+
+            let engine = server.engine();
+            let orders: Vec<Order> = PersistentKeyspace::new("orders", &engine)
+                .get_bulk_resolved(
+                    Some(vec!["1".into(), "2".into()]),
+                    None,
+                    2,
+                    None,
+                    None,
+                    None,
+                )
+                .await
+                .unwrap();
+
+            let requests: Vec<Value> = server.json_requests().await;
+            let customers: &Value = requests
+                .iter()
+                .find(|request| request["keyspace"] == "customers")
+                .unwrap();
+            let mut customer_keys: Vec<&str> = customers["bulk_keys"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .map(|key| key.as_str().unwrap())
+                .collect();
+            customer_keys.sort_unstable();
+
+            assert_eq!(customer_keys, ["c1", "c2"]);
+            assert_eq!(orders[0].id, 1);
+            assert_eq!(orders[0].customer, Pointer::new("customers", "c1"));
+
+            let bob: &Customer = orders[1].customer.value().unwrap();
+            assert_eq!(bob.name, "Bob");
+            assert_eq!(bob.region.value().unwrap().name, "North");
+            assert_eq!(orders[1].reviewers[0].value().unwrap()["name"], "Sam");
+            assert!(orders[1].reviewers[1].value().is_none());
+        });
+    }
+
+    #[test]
+    fn test_targets_are_read_with_the_persistence_of_their_keyspace() {
+        tokio_test::block_on(async {
+            let server = MockServer::payloads(3, |request| {
+                if request["raw"][0] == "get-structure-available" {
+                    return structure();
+                }
+
+                match request["keyspace"].as_str().unwrap() {
+                    "orders" => json!([stored(json!({
+                        "id": 1,
+                        "pointers": {
+                            "customer": ["archived", "c1"],
+                            "reviewers": [["staff", "s1"]],
+                        },
+                    }))]),
+                    "staff" => json!({ "s1": stored(json!({ "name": "Sam" })) }),
+                    other => panic!("unexpected keyspace {}", other),
+                }
+            })
+            .await;
+
+            let engine = server.engine();
+            let orders: Vec<Order> = PersistentKeyspace::new("orders", &engine)
+                .get_bulk_resolved(Some(vec!["1".into()]), None, 1, None, None, None)
+                .await
+                .unwrap();
+
+            let requests: Vec<Value> = server.json_requests().await;
+            let staff: &Value = requests
+                .iter()
+                .find(|request| request["keyspace"] == "staff")
+                .unwrap();
+
+            // `archived` is not listed by the store, so its target is not read.
+            assert_eq!(requests.len(), 3);
+            assert_eq!(staff["persistent"], false);
+            assert!(orders[0].customer.value().is_none());
+            assert_eq!(orders[0].reviewers[0].value().unwrap()["name"], "Sam");
+        });
+    }
+
+    #[test]
+    fn test_parse_structure_reads_the_store_structure() {
+        let response: &[u8] = br#"{"status":true,"payload":{"store":{"persistent":["orders","customers"],"in_memory":["sessions"]},"archive":{"persistent":[],"in_memory":[]}},"error":null}"#;
+        let payload: Value = parse_payload::<Value>(Ok(Some(response.to_vec()))).unwrap();
+
+        assert_eq!(
+            parse_structure(payload.clone(), "store").unwrap(),
+            [
+                ("orders".to_string(), true),
+                ("customers".to_string(), true),
+                ("sessions".to_string(), false),
+            ]
+        );
+        assert_eq!(
+            parse_structure(Value::String(payload.to_string()), "archive").unwrap(),
+            []
+        );
+
+        for payload in [
+            json!({ "other": { "persistent": [], "in_memory": [] } }),
+            json!({ "store": { "persistent": { "orders": {} }, "in_memory": [] } }),
+            json!({ "store": { "persistent": ["orders"], "inmemory": ["sessions"] } }),
+            json!({ "store": { "persistent": ["orders"] } }),
+            json!([{ "name": "orders", "persistent": true }]),
+            Value::Null,
+        ] {
+            assert!(matches!(
+                parse_structure(payload, "store"),
+                Err(MontycatClientError::ClientValueParsingError(_))
+            ));
+        }
+    }
+}
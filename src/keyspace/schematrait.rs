@@ -0,0 +1,118 @@
+use super::{
+    pubtrait::Keyspace,
+    structures::{inmemory::InMemoryKeyspace, persistent::PersistentKeyspace},
+};
+use crate::{MontycatClientError, traits::RuntimeSchema};
+use serde::Serialize;
+use std::future::Future;
+
+/// Schema-aware writes shared by `PersistentKeyspace` and `InMemoryKeyspace`.
+///
+/// The inherent `insert_value`, `insert_bulk` and `update_value` methods differ between the two
+/// keyspace kinds (in-memory writes take an expiration). `SchemaKeyspace` exposes them with one
+/// signature, so code such as `Collection<T, K>` can be written once for either kind.
+///
+/// # Notes
+///
+/// Values written to an `InMemoryKeyspace` through this trait do not expire.
+/// Calls on a concrete keyspace type resolve to the inherent methods; the trait methods are used
+/// from generic code bounded by `K: SchemaKeyspace`.
+///
+pub trait SchemaKeyspace: Keyspace {
+    /// See `PersistentKeyspace::insert_value`.
+    fn insert_value<T>(
+        &self,
+        custom_key: Option<String>,
+        value: T,
+    ) -> impl Future<Output = Result<Option<Vec<u8>>, MontycatClientError>> + Send
+    where
+        T: Serialize + RuntimeSchema + Send + 'static;
+
+    /// See `PersistentKeyspace::insert_bulk`.
+    fn insert_bulk<T>(
+        &self,
+        bulk_values: Vec<T>,
+    ) -> impl Future<Output = Result<Option<Vec<u8>>, MontycatClientError>> + Send
+    where
+        T: Serialize + RuntimeSchema + Send + 'static + Clone;
+
+    /// See `PersistentKeyspace::update_value`.
+    fn update_value<T>(
+        &self,
+        key: Option<String>,
+        custom_key: Option<String>,
+        value: T,
+    ) -> impl Future<Output = Result<Option<Vec<u8>>, MontycatClientError>> + Send
+    where
+        T: Serialize + Send + 'static;
+}
+
+impl SchemaKeyspace for PersistentKeyspace {
+    fn insert_value<T>(
+        &self,
+        custom_key: Option<String>,
+        value: T,
+    ) -> impl Future<Output = Result<Option<Vec<u8>>, MontycatClientError>> + Send
+    where
+        T: Serialize + RuntimeSchema + Send + 'static,
+    {
+        PersistentKeyspace::insert_value(self, custom_key, value)
+    }
+
+    fn insert_bulk<T>(
+        &self,
+        bulk_values: Vec<T>,
+    ) -> impl Future<Output = Result<Option<Vec<u8>>, MontycatClientError>> + Send
+    where
+        T: Serialize + RuntimeSchema + Send + 'static + Clone,
+    {
+        PersistentKeyspace::insert_bulk(self, bulk_values)
+    }
+
+    fn update_value<T>(
+        &self,
+        key: Option<String>,
+        custom_key: Option<String>,
+        value: T,
+    ) -> impl Future<Output = Result<Option<Vec<u8>>, MontycatClientError>> + Send
+    where
+        T: Serialize + Send + 'static,
+    {
+        PersistentKeyspace::update_value(self, key, custom_key, value)
+    }
+}
+
+impl SchemaKeyspace for InMemoryKeyspace {
+    fn insert_value<T>(
+        &self,
+        custom_key: Option<String>,
+        value: T,
+    ) -> impl Future<Output = Result<Option<Vec<u8>>, MontycatClientError>> + Send
+    where
+        T: Serialize + RuntimeSchema + Send + 'static,
+    {
+        InMemoryKeyspace::insert_value(self, custom_key, value, None)
+    }
+
+    fn insert_bulk<T>(
+        &self,
+        bulk_values: Vec<T>,
+    ) -> impl Future<Output = Result<Option<Vec<u8>>, MontycatClientError>> + Send
+    where
+        T: Serialize + RuntimeSchema + Send + 'static + Clone,
+    {
+        InMemoryKeyspace::insert_bulk(self, bulk_values, None)
+    }
+
+    fn update_value<T>(
+        &self,
+        key: Option<String>,
+        custom_key: Option<String>,
+        value: T,
+    ) -> impl Future<Output = Result<Option<Vec<u8>>, MontycatClientError>> + Send
+    where
+        T: Serialize + Send + 'static,
+    {
+        InMemoryKeyspace::update_value(self, key, custom_key, value, None)
+    }
+}
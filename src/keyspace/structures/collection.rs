@@ -0,0 +1,677 @@
+use super::persistent::PersistentKeyspace;
+use crate::errors::MontycatClientError;
+use crate::keyspace::schematrait::SchemaKeyspace;
+use crate::response::structure::{parse_key, parse_keys, parse_payload};
+use crate::response::typed::ReadOptions;
+use crate::tools::structure::Limit;
+use crate::traits::{MontycatEntity, RuntimeSchema};
+use serde::{Serialize, de::DeserializeOwned};
+use std::collections::HashMap;
+use std::marker::PhantomData;
+
+/// A keyspace that holds values of a single schema type `T`.
+///
+/// `Collection` wraps a `PersistentKeyspace` or an `InMemoryKeyspace`, enforces the schema of `T`
+/// when it is created, passes the schema name to every lookup and deserializes results into `T`.
+/// Responses with `status: false` are returned as `MontycatClientError::ClientServerError`.
+///
+/// # Fields
+/// - `keyspace`: The underlying keyspace.
+///
+/// # Examples
+///
+/// ```rust, ignore
+/// #[derive(Serialize, Deserialize, RuntimeSchema, Clone, Debug)]
+/// struct Employee {
+///     id: u32,
+///     name: String,
+/// }
+///
+/// let employees: Collection<Employee> =
+///     Collection::new(PersistentKeyspace::new("employees", &engine)).await?;
+///
+/// let key: String = employees.insert(Employee { id: 1, name: "Monty".into() }).await?;
+/// let employee: Option<Employee> = employees.get(&key).await?;
+/// let found: Vec<Employee> = employees.lookup_where(json!({ "name": "Monty" }), None).await?;
+/// ```
+///
+#[derive(Debug, Clone)]
+pub struct Collection<T, K = PersistentKeyspace> {
+    keyspace: K,
+    _schema: PhantomData<fn() -> T>,
+}
+
+impl<T, K> Collection<T, K>
+where
+    T: RuntimeSchema + Serialize + DeserializeOwned + Send + 'static,
+    K: SchemaKeyspace,
+{
+    /// Creates a collection over `keyspace` and enforces the schema of `T` on it.
+    ///
+    /// # Arguments
+    ///
+    /// * `keyspace` - The keyspace that stores the values. It must already exist.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<Collection<T, K>, MontycatClientError>` - The collection or an error.
+    ///
+    /// # Errors
+    ///
+    /// * `MontycatClientError::ClientUnsupportedFieldType` - If a field of `T` has no schema type.
+    /// * `MontycatClientError::ClientServerError` - If the server rejects the schema.
+    /// * Every error `Keyspace::enforce_schema` returns.
+    ///
+    pub async fn new(keyspace: K) -> Result<Self, MontycatClientError> {
+        let response: Result<Option<Vec<u8>>, MontycatClientError> =
+            keyspace.enforce_schema(T::schema_params()).await;
+        parse_payload::<serde_json::Value>(response)?;
+
+        Ok(Self::from_keyspace(keyspace))
+    }
+
+    /// Creates a collection over `keyspace` without enforcing the schema, e.g. when it is already
+    /// enforced or the keyspace is shared with untyped writers.
+    pub fn from_keyspace(keyspace: K) -> Self {
+        Self {
+            keyspace,
+            _schema: PhantomData,
+        }
+    }
+
+    /// Returns the underlying keyspace, for operations the collection does not wrap.
+    pub fn keyspace(&self) -> &K {
+        &self.keyspace
+    }
+
+    /// Returns the underlying keyspace.
+    pub fn into_keyspace(self) -> K {
+        self.keyspace
+    }
+
+    /// Inserts a value and returns the key the server created for it.
+    ///
+    /// # Errors
+    ///
+    /// * `MontycatClientError::ClientServerError` - If the server rejects the value.
+    /// * Every error `insert_value` returns.
+    ///
+    pub async fn insert(&self, value: T) -> Result<String, MontycatClientError> {
+        parse_key(self.keyspace.insert_value(None, value).await)
+    }
+
+    /// Inserts a value under a custom key and returns the key the server stored it under.
+    ///
+    /// # Errors
+    ///
+    /// * `MontycatClientError::ClientServerError` - If the server rejects the value.
+    /// * Every error `insert_value` returns.
+    ///
+    pub async fn insert_with_custom_key(
+        &self,
+        custom_key: &str,
+        value: T,
+    ) -> Result<String, MontycatClientError> {
+        parse_key(
+            self.keyspace
+                .insert_value(Some(custom_key.to_owned()), value)
+                .await,
+        )
+    }
+
+    /// Inserts several values and returns the keys the server created for them.
+    ///
+    /// # Errors
+    ///
+    /// * `MontycatClientError::ClientServerError` - If the server rejects the values.
+    /// * Every error `insert_bulk` returns.
+    ///
+    pub async fn insert_bulk(&self, values: Vec<T>) -> Result<Vec<String>, MontycatClientError>
+    where
+        T: Clone,
+    {
+        parse_keys(self.keyspace.insert_bulk(values).await)
+    }
+
+    /// Returns the value stored under `key`, or None if there is none.
+    ///
+    /// # Errors
+    ///
+    /// * `MontycatClientError::ClientServerError` - If the server answers with `status: false`.
+    /// * `MontycatClientError::ClientValueParsingError` - If the value is not a valid `T`.
+    ///
+    pub async fn get(&self, key: &str) -> Result<Option<T>, MontycatClientError> {
+        self.keyspace
+            .get_value_as(Some(key), None, ReadOptions::new())
+            .await
+    }
+
+    /// Returns the value stored under `custom_key`, or None if there is none.
+    ///
+    /// # Errors
+    ///
+    /// * `MontycatClientError::ClientServerError` - If the server answers with `status: false`.
+    /// * `MontycatClientError::ClientValueParsingError` - If the value is not a valid `T`.
+    ///
+    pub async fn get_by_custom_key(
+        &self,
+        custom_key: &str,
+    ) -> Result<Option<T>, MontycatClientError> {
+        self.keyspace
+            .get_value_as(None, Some(custom_key), ReadOptions::new())
+            .await
+    }
+
+    /// Returns the values stored under `keys`.
+    ///
+    /// # Errors
+    ///
+    /// * `MontycatClientError::ClientServerError` - If the server answers with `status: false`.
+    /// * `MontycatClientError::ClientValueParsingError` - If a value is not a valid `T`.
+    ///
+    pub async fn get_bulk(
+        &self,
+        keys: Vec<String>,
+        limit: Option<Limit>,
+    ) -> Result<Vec<T>, MontycatClientError> {
+        self.keyspace
+            .get_bulk_as(Some(keys), None, ReadOptions::new(), limit, None, None)
+            .await
+    }
+
+    /// Returns the values stored under `custom_keys`.
+    ///
+    /// # Errors
+    ///
+    /// * `MontycatClientError::ClientServerError` - If the server answers with `status: false`.
+    /// * `MontycatClientError::ClientValueParsingError` - If a value is not a valid `T`.
+    ///
+    pub async fn get_bulk_by_custom_keys(
+        &self,
+        custom_keys: Vec<String>,
+        limit: Option<Limit>,
+    ) -> Result<Vec<T>, MontycatClientError> {
+        self.keyspace
+            .get_bulk_as(
+                None,
+                Some(custom_keys),
+                ReadOptions::new(),
+                limit,
+                None,
+                None,
+            )
+            .await
+    }
+
+    /// Replaces the value stored under `key`.
+    ///
+    /// # Errors
+    ///
+    /// * `MontycatClientError::ClientServerError` - If the server answers with `status: false`.
+    /// * Every error `update_value` returns.
+    ///
+    pub async fn update(&self, key: &str, value: T) -> Result<(), MontycatClientError> {
+        let response: Result<Option<Vec<u8>>, MontycatClientError> = self
+            .keyspace
+            .update_value(Some(key.to_owned()), None, value)
+            .await;
+
+        parse_payload::<serde_json::Value>(response).map(|_| ())
+    }
+
+    /// Replaces the value stored under `custom_key`. With collision detection, the custom key is
+    /// embedded in the new value, like on insert.
+    ///
+    /// # Errors
+    ///
+    /// * `MontycatClientError::ClientServerError` - If the server answers with `status: false`.
+    /// * Every error `update_value` returns.
+    ///
+    pub async fn update_by_custom_key(
+        &self,
+        custom_key: &str,
+        value: T,
+    ) -> Result<(), MontycatClientError> {
+        let response: Result<Option<Vec<u8>>, MontycatClientError> = self
+            .keyspace
+            .update_value(None, Some(custom_key.to_owned()), value)
+            .await;
+
+        parse_payload::<serde_json::Value>(response).map(|_| ())
+    }
+
+    /// Deletes the value stored under `key`.
+    ///
+    /// # Errors
+    ///
+    /// * `MontycatClientError::ClientServerError` - If the server answers with `status: false`.
+    /// * Every error `Keyspace::delete_key` returns.
+    ///
+    pub async fn delete(&self, key: &str) -> Result<(), MontycatClientError> {
+        parse_payload::<serde_json::Value>(self.keyspace.delete_key(Some(key), None).await)
+            .map(|_| ())
+    }
+
+    /// Deletes the value stored under `custom_key`.
+    ///
+    /// # Errors
+    ///
+    /// * `MontycatClientError::ClientServerError` - If the server answers with `status: false`.
+    /// * Every error `Keyspace::delete_key` returns.
+    ///
+    pub async fn delete_by_custom_key(&self, custom_key: &str) -> Result<(), MontycatClientError> {
+        parse_payload::<serde_json::Value>(self.keyspace.delete_key(None, Some(custom_key)).await)
+            .map(|_| ())
+    }
+
+    /// Deletes the values stored under `keys`.
+    ///
+    /// # Errors
+    ///
+    /// * `MontycatClientError::ClientServerError` - If the server answers with `status: false`.
+    /// * Every error `Keyspace::delete_bulk` returns.
+    ///
+    pub async fn delete_bulk(&self, keys: Vec<String>) -> Result<(), MontycatClientError> {
+        parse_payload::<serde_json::Value>(self.keyspace.delete_bulk(Some(keys), None).await)
+            .map(|_| ())
+    }
+
+    /// Returns the values of type `T` matching `search_criteria`.
+    ///
+    /// # Errors
+    ///
+    /// * `MontycatClientError::ClientServerError` - If the server answers with `status: false`.
+    /// * `MontycatClientError::ClientValueParsingError` - If a value is not a valid `T`.
+    ///
+    pub async fn lookup_where<F>(
+        &self,
+        search_criteria: F,
+        limit: Option<Limit>,
+    ) -> Result<Vec<T>, MontycatClientError>
+    where
+        F: Serialize + Send + 'static,
+    {
+        let schema: (HashMap<&str, &str>, &str) = T::schema_params();
+
+        self.keyspace
+            .lookup_values_where_as(search_criteria, limit, ReadOptions::new(), Some(schema))
+            .await
+    }
+
+    /// Returns the keys of the values of type `T` matching `search_criteria`.
+    ///
+    /// # Errors
+    ///
+    /// * `MontycatClientError::ClientServerError` - If the server answers with `status: false`.
+    /// * `MontycatClientError::ClientValueParsingError` - If the response is not a list of keys.
+    ///
+    pub async fn lookup_keys_where<F>(
+        &self,
+        search_criteria: F,
+        limit: Option<Limit>,
+    ) -> Result<Vec<String>, MontycatClientError>
+    where
+        F: Serialize + Send + 'static,
+    {
+        let schema: (HashMap<&str, &str>, &str) = T::schema_params();
+
+        parse_keys(
+            self.keyspace
+                .lookup_keys_where(search_criteria, limit, Some(schema))
+                .await,
+        )
+    }
+
+    /// Returns the number of values in the keyspace.
+    ///
+    /// # Errors
+    ///
+    /// * `MontycatClientError::ClientServerError` - If the server answers with `status: false`.
+    /// * `MontycatClientError::ClientValueParsingError` - If the response is not a number.
+    ///
+    pub async fn len(&self) -> Result<usize, MontycatClientError> {
+        parse_payload(self.keyspace.get_len().await)
+    }
+
+    /// Returns true if the keyspace holds no values.
+    ///
+    /// # Errors
+    ///
+    /// * Every error `Collection::len` returns.
+    ///
+    pub async fn is_empty(&self) -> Result<bool, MontycatClientError> {
+        Ok(self.len().await? == 0)
+    }
+}
+
+/// What `Collection::save` did with a value.
+///
+/// # Variants
+///
+/// - `Inserted`: No value was stored under the custom key, so it was inserted.
+/// - `Updated`: A value was stored under the custom key and has been replaced.
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SaveOutcome {
+    Inserted,
+    Updated,
+}
+
+impl<T, K> Collection<T, K>
+where
+    T: RuntimeSchema + MontycatEntity + Serialize + DeserializeOwned + Clone + Send + 'static,
+    K: SchemaKeyspace,
+{
+    /// Inserts `entity` under its custom key, or replaces the value already stored there.
+    ///
+    /// The check and the write are two requests, so concurrent saves of the same new entity
+    /// can both insert; the last write wins.
+    ///
+    /// # Arguments
+    ///
+    /// * `entity` - The value to store. Its custom key comes from `MontycatEntity::custom_key`.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<SaveOutcome, MontycatClientError>` - Whether the value was inserted or updated.
+    ///
+    /// # Examples
+    ///
+    /// ```rust, ignore
+    /// montycat_entity!(Employee, email);
+    ///
+    /// let outcome: SaveOutcome = employees.save(&employee).await?;
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// * `MontycatClientError::ClientServerError` - If the server rejects the read or the write.
+    /// * `MontycatClientError::ClientCustomKeyCollision` - If collision detection is on and the value
+    ///   stored under the hash of the custom key belongs to another custom key.
+    /// * Every error `insert_value` and `update_value` return.
+    ///
+    pub async fn save(&self, entity: &T) -> Result<SaveOutcome, MontycatClientError> {
+        let custom_key: String = entity.custom_key();
+
+        if self.exists_by_custom_key(&custom_key).await? {
+            self.update_by_custom_key(&custom_key, entity.clone())
+                .await?;
+            Ok(SaveOutcome::Updated)
+        } else {
+            self.insert_with_custom_key(&custom_key, entity.clone())
+                .await?;
+            Ok(SaveOutcome::Inserted)
+        }
+    }
+
+    /// Deletes the value stored under the custom key of `entity`.
+    ///
+    /// # Errors
+    ///
+    /// * `MontycatClientError::ClientServerError` - If the server answers with `status: false`.
+    /// * Every error `Keyspace::delete_key` returns.
+    ///
+    pub async fn delete_entity(&self, entity: &T) -> Result<(), MontycatClientError> {
+        self.delete_by_custom_key(&entity.custom_key()).await
+    }
+
+    /// A missing key is reported either as a null payload or as a failed response saying the key
+    /// was not found. Any other failure, including a custom key collision, is returned.
+    async fn exists_by_custom_key(&self, custom_key: &str) -> Result<bool, MontycatClientError> {
+        let stored: Result<Option<serde_json::Value>, MontycatClientError> = self
+            .keyspace
+            .get_value_as(None, Some(custom_key), ReadOptions::new())
+            .await;
+
+        match stored {
+            Ok(value) => Ok(value.is_some()),
+            Err(MontycatClientError::ClientServerError(message))
+                if message.to_ascii_lowercase().contains("key not found") =>
+            {
+                Ok(false)
+            }
+            Err(e) => Err(e),
+        }
+    }
+}
+
+#[cfg(all(test, not(feature = "tls")))]
+mod tests {
+    use super::*;
+    use crate::test_support::MockServer;
+    use crate::{InMemoryKeyspace, KeyHashing, MontycatEntity, Pointer, Timestamp};
+    use crate::{Keyspace, ServerCapabilities};
+    use serde::Deserialize;
+
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize, crate::RuntimeSchema)]
+    struct Employee {
+        id: u32,
+        name: String,
+    }
+
+    #[test]
+    fn test_collection_enforces_schema_and_looks_up_typed_values() {
+        tokio_test::block_on(async {
+            let server = MockServer::responses(vec![
+                    "{\"status\":true,\"payload\":null,\"error\":null}\n",
+                    "{\"status\":true,\"payload\":[\"{\\\"id\\\":1,\\\"name\\\":\\\"Monty\\\"}\"],\"error\":null}\n",
+                ]).await;
+
+            let engine = server.engine();
+            let employees: Collection<Employee, InMemoryKeyspace> =
+                Collection::new(InMemoryKeyspace::new("employees", &engine))
+                    .await
+                    .unwrap();
+            let found: Vec<Employee> = employees
+                .lookup_where(serde_json::json!({ "name": "Monty" }), None)
+                .await
+                .unwrap();
+
+            assert_eq!(
+                found,
+                vec![Employee {
+                    id: 1,
+                    name: "Monty".into()
+                }]
+            );
+
+            let requests: Vec<String> = server.requests().await;
+            assert!(requests[0].contains("enforce-schema"));
+            assert!(requests[0].contains("Employee"));
+            assert!(requests[1].contains("\"schema\":\"Employee\""));
+        });
+    }
+
+    #[test]
+    fn test_collection_reports_rejected_schema() {
+        tokio_test::block_on(async {
+            let server = MockServer::responses(vec![
+                "{\"status\":false,\"payload\":null,\"error\":\"Keyspace not found\"}\n",
+            ])
+            .await;
+
+            let engine = server.engine();
+            let result: Result<Collection<Employee>, MontycatClientError> =
+                Collection::new(PersistentKeyspace::new("employees", &engine)).await;
+
+            assert_eq!(result.unwrap_err().message(), "Keyspace not found");
+            server.requests().await;
+        });
+    }
+
+    #[derive(Debug, Clone, Serialize, Deserialize, crate::RuntimeSchema)]
+    struct Account {
+        email: String,
+        plan: String,
+    }
+
+    crate::montycat_entity!(Account, email);
+
+    #[test]
+    fn test_save_inserts_then_updates_by_custom_key() {
+        tokio_test::block_on(async {
+            let server = MockServer::responses(vec![
+                    "{\"status\":true,\"payload\":null,\"error\":null}\n",
+                    "{\"status\":true,\"payload\":\"1\",\"error\":null}\n",
+                    "{\"status\":true,\"payload\":\"{\\\"email\\\":\\\"a@b.c\\\",\\\"plan\\\":\\\"free\\\"}\",\"error\":null}\n",
+                    "{\"status\":true,\"payload\":null,\"error\":null}\n",
+                ]).await;
+
+            let engine = server.engine();
+            let accounts: Collection<Account> =
+                Collection::from_keyspace(PersistentKeyspace::new("accounts", &engine));
+            let mut account = Account {
+                email: "a@b.c".into(),
+                plan: "free".into(),
+            };
+
+            let first: SaveOutcome = accounts.save(&account).await.unwrap();
+            account.plan = "pro".into();
+            let second: SaveOutcome = accounts.save(&account).await.unwrap();
+
+            assert_eq!(first, SaveOutcome::Inserted);
+            assert_eq!(second, SaveOutcome::Updated);
+            assert_eq!(Account::KEY_FIELD, "email");
+
+            let key: String = format!("\"key\":\"{}\"", KeyHashing::default().hash("a@b.c"));
+            let requests: Vec<String> = server.requests().await;
+            assert!(requests[1].contains("insert_custom_key_value") && requests[1].contains(&key));
+            assert!(requests[3].contains("update_value") && requests[3].contains(&key));
+        });
+    }
+
+    #[test]
+    fn test_collision_detection_still_works_after_update() {
+        tokio_test::block_on(async {
+            let mut stored: serde_json::Value = serde_json::Value::Null;
+            let server = MockServer::start(3, move |request| {
+                let request: serde_json::Value = serde_json::from_str(request).unwrap();
+                if request["command"] == "update_value" {
+                    stored = request["value"].clone();
+                }
+                let response =
+                    serde_json::json!({ "status": true, "payload": stored, "error": null });
+                Some(response.to_string().into_bytes())
+            })
+            .await;
+
+            let engine = server.engine();
+            let accounts: Collection<Account> = Collection::from_keyspace(
+                PersistentKeyspace::new("accounts", &engine).with_collision_detection(true),
+            );
+            let account = Account {
+                email: "a@b.c".into(),
+                plan: "pro".into(),
+            };
+
+            accounts
+                .update_by_custom_key("a@b.c", account)
+                .await
+                .unwrap();
+            let read: Option<Account> = accounts.get_by_custom_key("a@b.c").await.unwrap();
+            // Another custom key whose hash resolves to the same stored value.
+            let collision: MontycatClientError =
+                accounts.get_by_custom_key("x@y.z").await.unwrap_err();
+
+            let update: serde_json::Value = server.json_requests().await.remove(0);
+            assert_eq!(update["key"], KeyHashing::default().hash("a@b.c").as_str());
+            assert_eq!(read.unwrap().plan, "pro");
+            assert!(matches!(
+                collision,
+                MontycatClientError::ClientCustomKeyCollision(ref key) if key == "a@b.c"
+            ));
+        });
+    }
+
+    #[test]
+    fn test_save_propagates_collisions_and_read_failures() {
+        tokio_test::block_on(async {
+            let server = MockServer::responses(vec![
+                    "{\"status\":true,\"payload\":\"{\\\"email\\\":\\\"x@y.z\\\",\\\"plan\\\":\\\"free\\\",\\\"__custom_key\\\":\\\"x@y.z\\\"}\",\"error\":null}\n",
+                    "{\"status\":false,\"payload\":null,\"error\":\"Keyspace not found\"}\n",
+                    "{\"status\":false,\"payload\":null,\"error\":\"Key not found\"}\n",
+                    "{\"status\":true,\"payload\":\"1\",\"error\":null}\n",
+                ]).await;
+
+            let engine = server.engine();
+            let accounts: Collection<Account> = Collection::from_keyspace(
+                PersistentKeyspace::new("accounts", &engine).with_collision_detection(true),
+            );
+            let account = Account {
+                email: "a@b.c".into(),
+                plan: "pro".into(),
+            };
+
+            let collision: MontycatClientError = accounts.save(&account).await.unwrap_err();
+            let rejected: MontycatClientError = accounts.save(&account).await.unwrap_err();
+            let inserted: SaveOutcome = accounts.save(&account).await.unwrap();
+
+            assert!(matches!(
+                collision,
+                MontycatClientError::ClientCustomKeyCollision(ref key) if key == "x@y.z"
+            ));
+            assert!(matches!(
+                rejected,
+                MontycatClientError::ClientServerError(ref message) if message == "Keyspace not found"
+            ));
+            assert_eq!(inserted, SaveOutcome::Inserted);
+
+            // Neither failed read is followed by a write.
+            let requests: Vec<String> = server.requests().await;
+            assert_eq!(requests.len(), 4);
+            assert!(requests[3].contains("insert_custom_key_value"));
+        });
+    }
+
+    #[test]
+    fn test_nested_schemas_are_only_sent_to_servers_advertising_them() {
+        tokio_test::block_on(async {
+            let ok: &str = "{\"status\":true,\"payload\":null,\"error\":null}\n";
+            let server = MockServer::responses([ok, ok]).await;
+
+            crate::register_enum_schema(crate::EnumSchema::new("TeamRole").unit("Lead"));
+            let team = || (HashMap::from([("members", "Vec<String>")]), "Team");
+            let member = || (HashMap::from([("role", "TeamRole")]), "Member");
+
+            let engine = server.engine();
+            let flat = PersistentKeyspace::new("teams", &engine);
+            flat.enforce_schema(team()).await.unwrap();
+            let unsupported: MontycatClientError = flat.enforce_schema(member()).await.unwrap_err();
+
+            let engine = engine.with_server_capabilities(ServerCapabilities::with_features(
+                None,
+                ["nested_schemas"],
+            ));
+            PersistentKeyspace::new("teams", &engine)
+                .enforce_schema(team())
+                .await
+                .unwrap();
+
+            assert!(matches!(
+                unsupported,
+                MontycatClientError::ClientUnsupported(_)
+            ));
+
+            let schemas: Vec<serde_json::Value> = server
+                .json_requests()
+                .await
+                .into_iter()
+                .map(|request| {
+                    let raw: &Vec<serde_json::Value> = request["raw"].as_array().unwrap();
+                    let content: usize = raw.iter().position(|t| t == "schema_content").unwrap();
+                    serde_json::from_str(raw[content + 1].as_str().unwrap()).unwrap()
+                })
+                .collect();
+
+            assert_eq!(
+                schemas,
+                vec![
+                    serde_json::json!({ "members": ["Array", false] }),
+                    serde_json::json!({
+                        "members": ["Array", false, { "items": ["String", false] }],
+                    }),
+                ]
+            );
+        });
+    }
+}
@@ -1,2 +1,3 @@
+pub mod collection;
 pub mod inmemory;
 pub mod persistent;
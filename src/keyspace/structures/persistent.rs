@@ -1,16 +1,24 @@
+use super::super::hashing::{KeyHashing, embed_custom_key, update_target};
+use super::super::keygen::{BulkInsert, KeyGenerator};
 use super::super::pubtrait::Keyspace;
+use super::super::registry::{KEY_REGISTRY_DISABLED, KeyRegistry, ResolvedKey, register_inserted};
+use crate::codec::Codec;
+use crate::engine::capabilities::ServerFeature;
+use crate::engine::credentials::Credentials;
 use crate::engine::structure::Engine;
 use crate::engine::utils::send_data;
 use crate::errors::MontycatClientError;
-use crate::request::store_request::structure::StoreRequestClient;
-use crate::request::utis::functions::{convert_custom_key, fulfil_subscription_request};
-use crate::request::{structure::Req, utis::functions::is_custom_type};
-use crate::tools::functions::{process_bulk_values, process_json_value, process_value};
+use crate::request::command::{Command, StoreCommand, StoreOperation};
+use crate::request::utis::functions::fulfil_subscription_request;
+use crate::request::utis::functions::is_custom_type;
+use crate::response::structure::{parse_key, parse_keys};
+use crate::tools::functions::{process_json_value, process_value, serialize_bulk_values};
 use crate::tools::structure::Limit;
 use crate::traits::RuntimeSchema;
 use serde::Serialize;
 use std::any::type_name;
 use std::collections::HashMap;
+use std::sync::Arc;
 
 /// Represents a persistent keyspace in the Montycat database.
 ///
@@ -19,6 +27,10 @@ use std::collections::HashMap;
 /// - `persistent`: A boolean indicating if the keyspace is persistent.
 /// - `distributed`: A boolean indicating if the keyspace is distributed.
 /// - `engine`: An instance of the `Engine` struct used for database operations.
+/// - `key_hashing`: The scheme used to hash custom keys. See `with_key_hashing`.
+/// - `collision_detection`: Whether custom keys are stored with values and verified on read. See `with_collision_detection`.
+/// - `key_registry`: Whether custom keys are recorded in a key registry. See `with_key_registry`.
+/// - `key_generator`: The generator of keys for values inserted without a custom key. See `with_key_generator`.
 ///
 /// # Examples
 /// ```rust, ignore
@@ -31,6 +43,10 @@ pub struct PersistentKeyspace {
     pub persistent: bool,
     pub distributed: bool,
     pub engine: Engine,
+    pub key_hashing: KeyHashing,
+    pub collision_detection: bool,
+    pub key_registry: bool,
+    pub key_generator: Option<Arc<dyn KeyGenerator>>,
 }
 
 impl Keyspace for PersistentKeyspace {
@@ -39,8 +55,8 @@ impl Keyspace for PersistentKeyspace {
     /// # Returns
     /// - `Engine`: The engine instance.
     ///
-    fn get_engine(&self) -> Engine {
-        self.engine.clone()
+    fn get_engine(&self) -> &Engine {
+        &self.engine
     }
 
     /// Retrieves the name of the keyspace.
@@ -72,6 +88,33 @@ impl Keyspace for PersistentKeyspace {
     fn get_distributed(&self) -> bool {
         self.distributed
     }
+
+    /// Retrieves the scheme used to hash custom keys.
+    ///
+    /// # Returns
+    /// - `KeyHashing`: The key hashing scheme of the keyspace.
+    ///
+    fn get_key_hashing(&self) -> KeyHashing {
+        self.key_hashing
+    }
+
+    /// Checks if values written by custom key carry their custom key and reads verify it.
+    ///
+    /// # Returns
+    /// - `bool`: True if collision detection is enabled, false otherwise.
+    ///
+    fn get_collision_detection(&self) -> bool {
+        self.collision_detection
+    }
+
+    /// Retrieves the key registry of the keyspace.
+    ///
+    /// # Returns
+    /// - `Option<KeyRegistry>`: The key registry, or None if the keyspace does not keep one.
+    ///
+    fn get_key_registry(&self) -> Option<KeyRegistry> {
+        self.key_registry.then(|| KeyRegistry::for_keyspace(self))
+    }
 }
 
 impl PersistentKeyspace {
@@ -95,9 +138,82 @@ impl PersistentKeyspace {
             persistent: true,
             distributed: false,
             engine: engine.clone(),
+            key_hashing: KeyHashing::default(),
+            collision_detection: false,
+            key_registry: false,
+            key_generator: None,
         }
     }
 
+    /// Sets the scheme used to hash custom keys.
+    ///
+    /// Keys written with one scheme cannot be read with another, so use the scheme the keyspace
+    /// was written with. `KeyHashing::Xxh32` is the default for compatibility with existing data.
+    ///
+    /// # Arguments
+    /// - `key_hashing: KeyHashing`: The key hashing scheme.
+    ///
+    /// # Examples
+    ///
+    /// ```rust, ignore
+    /// let keyspace: PersistentKeyspace = PersistentKeyspace::new("users", &engine).with_key_hashing(KeyHashing::Xxh3_128);
+    /// ```
+    ///
+    pub fn with_key_hashing(mut self, key_hashing: KeyHashing) -> Self {
+        self.key_hashing = key_hashing;
+        self
+    }
+
+    /// Enables or disables custom key collision detection.
+    ///
+    /// When enabled, values inserted under a custom key store it in the `CUSTOM_KEY_FIELD` field,
+    /// and `get_value` / `get_bulk` by custom key return
+    /// `MontycatClientError::ClientCustomKeyCollision` if a value belongs to another custom key.
+    /// Values must serialize to JSON objects, and an enforced schema must accept the extra field.
+    ///
+    /// # Arguments
+    /// - `enabled: bool`: Whether collision detection is enabled.
+    ///
+    pub fn with_collision_detection(mut self, enabled: bool) -> Self {
+        self.collision_detection = enabled;
+        self
+    }
+
+    /// Enables or disables the key registry.
+    ///
+    /// When enabled, inserts under a custom key record it in the `KeyRegistry` of the keyspace and
+    /// deletes remove it, so `get_keys_resolved` and `lookup_keys_where_resolved` can return the
+    /// original custom keys. Create the registry keyspace once with `KeyRegistry::create`. A failed
+    /// registry write is returned as `MontycatClientError::ClientKeyRegistryOutOfSync`.
+    ///
+    /// # Arguments
+    /// - `enabled: bool`: Whether the key registry is enabled.
+    ///
+    pub fn with_key_registry(mut self, enabled: bool) -> Self {
+        self.key_registry = enabled;
+        self
+    }
+
+    /// Sets the generator of keys for values inserted without a custom key.
+    ///
+    /// By default the server creates the keys. With a generator, `insert_value`, `insert_bulk` and
+    /// their `_no_schema` variants send the generated keys. A deterministic generator such as
+    /// `UuidV5Generator` makes retried inserts write the same entry instead of a duplicate.
+    ///
+    /// # Arguments
+    /// - `generator: G`: The key generator.
+    ///
+    /// # Examples
+    ///
+    /// ```rust, ignore
+    /// let keyspace: PersistentKeyspace = PersistentKeyspace::new("orders", &engine).with_key_generator(UuidV7Generator);
+    /// ```
+    ///
+    pub fn with_key_generator<G: KeyGenerator + 'static>(mut self, generator: G) -> Self {
+        self.key_generator = Some(Arc::new(generator));
+        self
+    }
+
     /// Subscribes to changes in the persistent keyspace.
     ///
     /// # Arguments
@@ -129,6 +245,7 @@ impl PersistentKeyspace {
     ///
     /// * `MontycatClientError::ClientStoreNotSet` - If the store is not set in the engine.
     /// * `MontycatClientError::ClientSelectedBothKeyAndCustomKey` - If both key and custom_key are provided.
+    /// * `MontycatClientError::ClientEngineError` - If the engine port is 65535.
     pub async fn subscribe(
         &self,
         key: Option<String>,
@@ -140,10 +257,9 @@ impl PersistentKeyspace {
         let engine = self.get_engine();
         let name = self.get_name();
         let store = engine
-            .store
-            .as_ref()
+            .store()
             .ok_or(MontycatClientError::ClientStoreNotSet)?;
-        let use_tls = engine.use_tls;
+        let use_tls = engine.use_tls();
 
         let key = {
             if key.is_some() && custom_key.is_some() {
@@ -152,11 +268,19 @@ impl PersistentKeyspace {
             key.or(custom_key)
         };
 
-        let port = engine.port + 1;
-        let request_bytes =
-            fulfil_subscription_request(store, name, key, &engine.username, &engine.password)?;
+        let port = engine.subscription_port()?;
+        let request_bytes = {
+            let credentials: Arc<Credentials> = engine.get_credentials().await?;
+            fulfil_subscription_request(
+                store,
+                name,
+                key,
+                &credentials.username,
+                credentials.password.expose_secret(),
+            )?
+        };
 
-        let host = engine.host.clone();
+        let host = engine.host().to_owned();
         tokio::spawn(async move {
             let _ = send_data(
                 &host,
@@ -165,6 +289,7 @@ impl PersistentKeyspace {
                 Some(callback),
                 Some(&mut stop_rx),
                 use_tls,
+                Codec::Json,
             )
             .await;
         });
@@ -201,47 +326,25 @@ impl PersistentKeyspace {
         cache: Option<usize>,
         compression: Option<bool>,
     ) -> Result<Option<Vec<u8>>, MontycatClientError> {
-        let engine: Engine = self.get_engine();
+        let engine: &Engine = self.get_engine();
         let name: &str = self.get_name();
         let persistent: bool = self.get_persistent();
         let distributed: bool = self.get_distributed();
 
-        let store: String = engine
-            .store
-            .clone()
+        let store: &str = engine
+            .store()
             .ok_or(MontycatClientError::ClientStoreNotSet)?;
-        let use_tls: bool = engine.use_tls;
-
-        let vec: Vec<String> = vec![
-            "create-keyspace".into(),
-            "store".into(),
-            store,
-            "keyspace".into(),
-            name.to_owned(),
-            "persistent".into(),
-            if persistent { "y".into() } else { "n".into() },
-            "distributed".into(),
-            if distributed { "y".into() } else { "n".into() },
-            "cache".into(),
-            cache.map_or("0".into(), |c| c.to_string()),
-            "compression".into(),
-            compression.map_or("n".into(), |c| if c { "y".into() } else { "n".into() }),
-        ];
-
-        let credentials: Vec<String> = engine.get_credentials();
-        let query: Req = Req::new_raw_command(vec, credentials);
-        let bytes: Vec<u8> = query.byte_down()?;
-        let response: Option<Vec<u8>> = send_data(
-            &engine.host,
-            engine.port,
-            bytes.as_slice(),
-            None,
-            None,
-            use_tls,
-        )
-        .await?;
-
-        Ok(response)
+
+        let command: Command = Command::CreateKeyspace {
+            store: store.to_owned(),
+            keyspace: name.to_owned(),
+            persistent,
+            distributed,
+            cache: Some(cache.unwrap_or(0)),
+            compression: Some(compression.unwrap_or(false)),
+        };
+
+        engine.send_command(&command).await
     }
 
     /// Updates the cache size and compression settings of the persistent keyspace.
@@ -273,40 +376,20 @@ impl PersistentKeyspace {
         cache: Option<usize>,
         compression: Option<bool>,
     ) -> Result<Option<Vec<u8>>, MontycatClientError> {
-        let engine: Engine = self.get_engine();
+        let engine: &Engine = self.get_engine();
         let name: &str = self.get_name();
-        let store: String = engine
-            .store
-            .clone()
+        let store: &str = engine
+            .store()
             .ok_or(MontycatClientError::ClientStoreNotSet)?;
-        let use_tls: bool = engine.use_tls;
-
-        let vec: Vec<String> = vec![
-            "update-cache-compression".into(),
-            "store".into(),
-            store,
-            "keyspace".into(),
-            name.to_owned(),
-            "cache".into(),
-            cache.map_or("0".into(), |c| c.to_string()),
-            "compression".into(),
-            compression.map_or("n".into(), |c| if c { "y".into() } else { "n".into() }),
-        ];
-
-        let credentials: Vec<String> = engine.get_credentials();
-        let query: Req = Req::new_raw_command(vec, credentials);
-        let bytes: Vec<u8> = query.byte_down()?;
-        let response: Option<Vec<u8>> = send_data(
-            &engine.host,
-            engine.port,
-            bytes.as_slice(),
-            None,
-            None,
-            use_tls,
-        )
-        .await?;
-
-        Ok(response)
+
+        let command: Command = Command::UpdateCacheCompression {
+            store: store.to_owned(),
+            keyspace: name.to_owned(),
+            cache: cache.unwrap_or(0),
+            compression: compression.unwrap_or(false),
+        };
+
+        engine.send_command(&command).await
     }
 
     /// Inserts a value into the persistent keyspace.
@@ -341,17 +424,15 @@ impl PersistentKeyspace {
         T: Serialize + RuntimeSchema + Send + 'static,
     {
         let mut key: Option<String> = None;
-        let engine: Engine = self.get_engine();
+        let engine: &Engine = self.get_engine();
         let name: &str = self.get_name();
         let persistent: bool = self.get_persistent();
         let distributed: bool = self.get_distributed();
-        let store: String = engine
-            .store
-            .clone()
+        let store: &str = engine
+            .store()
             .ok_or(MontycatClientError::ClientStoreNotSet)?;
-        let use_tls: bool = engine.use_tls;
         let mut schema: Option<String> = None;
-        let value_to_send: String = process_value(value)?;
+        let mut value_to_send: String = process_value(value)?;
 
         let type_name_retrieved: &str = type_name::<T>();
 
@@ -360,41 +441,74 @@ impl PersistentKeyspace {
         };
 
         if let Some(custom_key_str) = &custom_key {
-            key = Some(convert_custom_key(custom_key_str));
+            key = Some(self.key_hashing.hash(custom_key_str));
+
+            if self.collision_detection {
+                value_to_send = embed_custom_key(value_to_send, custom_key_str)?;
+            }
+        } else if let Some(generator) = &self.key_generator {
+            key = Some(generator.generate(&value_to_send)?);
         }
 
-        let command: String = if key.is_none() {
-            "insert_value".to_string()
+        let operation: StoreOperation = if key.is_none() {
+            StoreOperation::InsertValue
         } else {
-            "insert_custom_key_value".to_string()
+            StoreOperation::InsertCustomKeyValue
         };
 
-        let new_store_request: StoreRequestClient = StoreRequestClient {
+        let new_store_request: StoreCommand = StoreCommand {
             schema,
-            username: engine.username.clone(),
-            password: engine.password.clone(),
-            keyspace: name.to_owned(),
-            store,
+            keyspace: name.into(),
+            store: store.into(),
             persistent,
             distributed,
             value: value_to_send,
-            command,
+            operation,
             key,
             ..Default::default()
         };
 
-        let bytes: Vec<u8> = Req::new_store_command(new_store_request).byte_down()?;
-        let response: Option<Vec<u8>> = send_data(
-            &engine.host,
-            engine.port,
-            bytes.as_slice(),
-            None,
-            None,
-            use_tls,
-        )
-        .await?;
-
-        Ok(response)
+        let response: Option<Vec<u8>> = engine
+            .send_command(&Command::Store(Box::new(new_store_request)))
+            .await?;
+
+        register_inserted(self, custom_key.as_deref(), response).await
+    }
+
+    /// Inserts a value and returns the key the server created for it.
+    ///
+    /// # Arguments
+    ///
+    /// Same as `insert_value`.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<String, MontycatClientError>` - The key of the inserted value.
+    ///
+    /// # Examples
+    ///
+    /// ```rust, ignore
+    /// let key: String = keyspace.insert_value_returning_key(None, value).await?;
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// * Every error `insert_value` returns.
+    /// * `MontycatClientError::ClientServerError` - If the server answers with `status: false`.
+    /// * `MontycatClientError::ClientValueParsingError` - If the response does not contain a key.
+    ///
+    pub async fn insert_value_returning_key<T>(
+        &self,
+        custom_key: Option<String>,
+        value: T,
+    ) -> Result<String, MontycatClientError>
+    where
+        T: Serialize + RuntimeSchema + Send + 'static,
+    {
+        let response: Result<Option<Vec<u8>>, MontycatClientError> =
+            self.insert_value(custom_key, value).await;
+
+        parse_key(response)
     }
 
     /// Inserts a custom key into the persistent keyspace.
@@ -423,45 +537,34 @@ impl PersistentKeyspace {
         &self,
         custom_key: String,
     ) -> Result<Option<Vec<u8>>, MontycatClientError> {
-        let engine: Engine = self.get_engine();
+        let engine: &Engine = self.get_engine();
         let name: &str = self.get_name();
         let persistent: bool = self.get_persistent();
         let distributed: bool = self.get_distributed();
-        let store: String = engine
-            .store
-            .clone()
+        let store: &str = engine
+            .store()
             .ok_or(MontycatClientError::ClientStoreNotSet)?;
-        let use_tls: bool = engine.use_tls;
 
-        let key: String = convert_custom_key(&custom_key);
+        let key: String = self.key_hashing.hash(&custom_key);
 
-        let command: String = "insert_custom_key".to_string();
+        let operation: StoreOperation = StoreOperation::InsertCustomKey;
 
-        let new_store_request: StoreRequestClient = StoreRequestClient {
-            username: engine.username.clone(),
-            password: engine.password.clone(),
-            keyspace: name.to_owned(),
-            store,
+        let new_store_request: StoreCommand = StoreCommand {
+            keyspace: name.into(),
+            store: store.into(),
             persistent,
             distributed,
             value: String::new(),
-            command,
+            operation,
             key: Some(key),
             ..Default::default()
         };
 
-        let bytes: Vec<u8> = Req::new_store_command(new_store_request).byte_down()?;
-        let response: Option<Vec<u8>> = send_data(
-            &engine.host,
-            engine.port,
-            bytes.as_slice(),
-            None,
-            None,
-            use_tls,
-        )
-        .await?;
-
-        Ok(response)
+        let response: Option<Vec<u8>> = engine
+            .send_command(&Command::Store(Box::new(new_store_request)))
+            .await?;
+
+        register_inserted(self, Some(&custom_key), response).await
     }
 
     /// Inserts a value into the persistent keyspace without enforcing a schema.
@@ -499,52 +602,47 @@ impl PersistentKeyspace {
         T: Serialize + Send + 'static,
     {
         let mut key: Option<String> = None;
-        let engine: Engine = self.get_engine();
+        let engine: &Engine = self.get_engine();
         let name: &str = self.get_name();
         let persistent: bool = self.get_persistent();
         let distributed: bool = self.get_distributed();
-        let store: String = engine
-            .store
-            .clone()
+        let store: &str = engine
+            .store()
             .ok_or(MontycatClientError::ClientStoreNotSet)?;
-        let use_tls: bool = engine.use_tls;
-        let value_to_send: String = process_json_value(&value)?;
+        let mut value_to_send: String = process_json_value(&value)?;
 
         if let Some(custom_key_str) = &custom_key {
-            key = Some(convert_custom_key(custom_key_str));
+            key = Some(self.key_hashing.hash(custom_key_str));
+
+            if self.collision_detection {
+                value_to_send = embed_custom_key(value_to_send, custom_key_str)?;
+            }
+        } else if let Some(generator) = &self.key_generator {
+            key = Some(generator.generate(&value_to_send)?);
         }
 
-        let command: String = if key.is_none() {
-            "insert_value".to_string()
+        let operation: StoreOperation = if key.is_none() {
+            StoreOperation::InsertValue
         } else {
-            "insert_custom_key_value".to_string()
+            StoreOperation::InsertCustomKeyValue
         };
 
-        let new_store_request: StoreRequestClient = StoreRequestClient {
-            username: engine.username.clone(),
-            password: engine.password.clone(),
-            keyspace: name.to_owned(),
-            store,
+        let new_store_request: StoreCommand = StoreCommand {
+            keyspace: name.into(),
+            store: store.into(),
             persistent,
             distributed,
             value: value_to_send,
-            command,
+            operation,
             key,
             ..Default::default()
         };
 
-        let bytes: Vec<u8> = Req::new_store_command(new_store_request).byte_down()?;
-        let response: Option<Vec<u8>> = send_data(
-            &engine.host,
-            engine.port,
-            bytes.as_slice(),
-            None,
-            None,
-            use_tls,
-        )
-        .await?;
-
-        Ok(response)
+        let response: Option<Vec<u8>> = engine
+            .send_command(&Command::Store(Box::new(new_store_request)))
+            .await?;
+
+        register_inserted(self, custom_key.as_deref(), response).await
     }
 
     /// Retrieves keys from the persistent keyspace with optional filtering and limiting.
@@ -572,6 +670,7 @@ impl PersistentKeyspace {
     /// * `MontycatClientError::ClientStoreNotSet` - If the store is not set in the engine.
     /// * `MontycatClientError::ClientEngineError` - If there is an error with the engine.
     /// * `MontycatClientError::ClientValueParsingError` - If there is an error parsing the response.
+    /// * `MontycatClientError::ClientUnsupported` - If the server does not support volume filtering.
     ///
     pub async fn get_keys(
         &self,
@@ -579,16 +678,21 @@ impl PersistentKeyspace {
         volumes: Option<Vec<String>>,
         latest_volume: Option<bool>,
     ) -> Result<Option<Vec<u8>>, MontycatClientError> {
-        let engine: Engine = self.get_engine();
+        let engine: &Engine = self.get_engine();
+
+        if volumes.as_ref().is_some_and(|v| !v.is_empty()) {
+            engine.require(ServerFeature::Volumes).await?;
+        }
+        if latest_volume.unwrap_or(false) {
+            engine.require(ServerFeature::LatestVolume).await?;
+        }
         let name: &str = self.get_name();
         let persistent: bool = self.get_persistent();
         let distributed: bool = self.get_distributed();
-        let store: String = engine
-            .store
-            .clone()
+        let store: &str = engine
+            .store()
             .ok_or(MontycatClientError::ClientStoreNotSet)?;
-        let use_tls: bool = engine.use_tls;
-        let command: String = "get_keys".to_string();
+        let operation: StoreOperation = StoreOperation::GetKeys;
 
         let limit_map: HashMap<String, usize> = match limit {
             Some(lim) => {
@@ -603,32 +707,61 @@ impl PersistentKeyspace {
             None => Limit::default_limit().to_map(),
         };
 
-        let new_store_request: StoreRequestClient = StoreRequestClient {
-            username: engine.username.clone(),
-            password: engine.password.clone(),
-            keyspace: name.to_owned(),
-            store,
+        let new_store_request: StoreCommand = StoreCommand {
+            keyspace: name.into(),
+            store: store.into(),
             persistent,
             distributed,
-            command,
+            operation,
             volumes: volumes.unwrap_or_default(),
             latest_volume: latest_volume.unwrap_or_default(),
             limit_output: limit_map,
             ..Default::default()
         };
 
-        let bytes: Vec<u8> = Req::new_store_command(new_store_request).byte_down()?;
-        let response: Option<Vec<u8>> = send_data(
-            &engine.host,
-            engine.port,
-            bytes.as_slice(),
-            None,
-            None,
-            use_tls,
-        )
-        .await?;
-
-        Ok(response)
+        engine
+            .send_command(&Command::Store(Box::new(new_store_request)))
+            .await
+    }
+
+    /// Retrieves keys from the keyspace together with their original custom keys.
+    ///
+    /// Resolves the keys returned by `get_keys` through the key registry.
+    ///
+    /// # Arguments
+    ///
+    /// Same as `get_keys`.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<Vec<ResolvedKey>, MontycatClientError>` - The keys, with the custom key each was created from.
+    ///
+    /// # Examples
+    ///
+    /// ```rust, ignore,
+    /// let keys: Vec<ResolvedKey> = keyspace.get_keys_resolved(None, None, None).await?;
+    /// let custom_keys: Vec<String> = keys.into_iter().filter_map(|key| key.custom_key).collect();
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// * Every error `get_keys` returns.
+    /// * `MontycatClientError::ClientGenericError` - If the keyspace does not keep a key registry.
+    /// * `MontycatClientError::ClientServerError` - If the server answers with `status: false`.
+    ///
+    pub async fn get_keys_resolved(
+        &self,
+        limit: Option<Limit>,
+        volumes: Option<Vec<String>>,
+        latest_volume: Option<bool>,
+    ) -> Result<Vec<ResolvedKey>, MontycatClientError> {
+        let registry: KeyRegistry = self
+            .get_key_registry()
+            .ok_or_else(|| MontycatClientError::ClientGenericError(KEY_REGISTRY_DISABLED.into()))?;
+
+        let keys: Vec<String> = parse_keys(self.get_keys(limit, volumes, latest_volume).await)?;
+
+        registry.resolve(keys).await
     }
 
     /// Updates a value in the keyspace.
@@ -636,7 +769,8 @@ impl PersistentKeyspace {
     /// # Arguments
     ///
     /// * `key` - Optional key of the value to update.
-    /// * `custom_key` - Optional custom key of the value to update.
+    /// * `custom_key` - Optional custom key of the value to update. It is hashed with the keyspace's
+    ///   key hashing, and embedded in the value again when collision detection is enabled.
     /// * `value` - The new value to set. Must implement `Serialize`.
     ///
     /// # Returns
@@ -653,9 +787,11 @@ impl PersistentKeyspace {
     ///
     /// # Errors
     ///
+    /// * `MontycatClientError::ClientNoValidInputProvided` - If neither or both of `key` and `custom_key` are given.
     /// * `MontycatClientError::ClientStoreNotSet` - If the store is not set in the engine.
     /// * `MontycatClientError::ClientEngineError` - If there is an error with the engine.
-    /// * `MontycatClientError::ClientValueParsingError` - If there is an error parsing the response.
+    /// * `MontycatClientError::ClientValueParsingError` - If there is an error parsing the response, or if
+    ///   collision detection is on and the value does not serialize to a JSON object.
     ///
     pub async fn update_value<T>(
         &self,
@@ -666,51 +802,31 @@ impl PersistentKeyspace {
     where
         T: Serialize + Send + 'static,
     {
-        if key.is_none() && custom_key.is_none() || (key.is_some() && custom_key.is_some()) {
-            return Err(MontycatClientError::ClientNoValidInputProvided);
-        }
-
-        let key: String = key
-            .or(custom_key)
-            .ok_or(MontycatClientError::ClientNoValidInputProvided)?;
-
-        let engine: Engine = self.get_engine();
+        let engine: &Engine = self.get_engine();
         let name: &str = self.get_name();
         let persistent: bool = self.get_persistent();
         let distributed: bool = self.get_distributed();
-        let store: String = engine
-            .store
-            .clone()
+        let store: &str = engine
+            .store()
             .ok_or(MontycatClientError::ClientStoreNotSet)?;
-        let use_tls: bool = engine.use_tls;
-        let command: String = "update_value".to_string();
-        let value_to_send: String = process_json_value(&value)?;
+        let operation: StoreOperation = StoreOperation::UpdateValue;
+        let (key, value_to_send): (String, String) =
+            update_target(self, key, custom_key, process_json_value(&value)?)?;
 
-        let new_store_request: StoreRequestClient = StoreRequestClient {
+        let new_store_request: StoreCommand = StoreCommand {
             key: Some(key),
-            username: engine.username.clone(),
-            password: engine.password.clone(),
-            keyspace: name.to_owned(),
-            store,
+            keyspace: name.into(),
+            store: store.into(),
             persistent,
             distributed,
             value: value_to_send,
-            command,
+            operation,
             ..Default::default()
         };
 
-        let bytes: Vec<u8> = Req::new_store_command(new_store_request).byte_down()?;
-        let response: Option<Vec<u8>> = send_data(
-            &engine.host,
-            engine.port,
-            bytes.as_slice(),
-            None,
-            None,
-            use_tls,
-        )
-        .await?;
-
-        Ok(response)
+        engine
+            .send_command(&Command::Store(Box::new(new_store_request)))
+            .await
     }
 
     /// Inserts multiple values into the keyspace in bulk.
@@ -744,44 +860,35 @@ impl PersistentKeyspace {
     where
         T: Serialize + RuntimeSchema + Send + 'static + Clone,
     {
-        let engine: Engine = self.get_engine();
+        let engine: &Engine = self.get_engine();
         let name: &str = self.get_name();
         let persistent: bool = self.get_persistent();
         let distributed: bool = self.get_distributed();
-        let store: String = engine
-            .store
-            .clone()
+        let store: &str = engine
+            .store()
             .ok_or(MontycatClientError::ClientStoreNotSet)?;
-        let use_tls: bool = engine.use_tls;
-        let command: String = "insert_value".to_string();
+        let (serialized_values, schema) = serialize_bulk_values(bulk_values).await?;
 
-        let (value_to_send, schema) = process_bulk_values(bulk_values).await?;
+        let bulk: BulkInsert = match self.key_generator.as_deref() {
+            Some(generator) => BulkInsert::generated(serialized_values, generator)?,
+            None => BulkInsert::plain(process_json_value(&serialized_values)?),
+        };
 
-        let new_store_request: StoreRequestClient = StoreRequestClient {
+        let new_store_request: StoreCommand = StoreCommand {
             schema,
-            username: engine.username.clone(),
-            password: engine.password.clone(),
-            keyspace: name.to_owned(),
-            store,
+            keyspace: name.into(),
+            store: store.into(),
             persistent,
             distributed,
-            value: value_to_send,
-            command,
+            value: bulk.value,
+            bulk_keys_values: bulk.bulk_keys_values.into_iter().collect(),
+            operation: bulk.operation,
             ..Default::default()
         };
 
-        let bytes: Vec<u8> = Req::new_store_command(new_store_request).byte_down()?;
-        let response: Option<Vec<u8>> = send_data(
-            &engine.host,
-            engine.port,
-            bytes.as_slice(),
-            None,
-            None,
-            use_tls,
-        )
-        .await?;
-
-        Ok(response)
+        engine
+            .send_command(&Command::Store(Box::new(new_store_request)))
+            .await
     }
 
     /// Inserts multiple values into the keyspace in bulk without enforcing a schema.
@@ -815,42 +922,37 @@ impl PersistentKeyspace {
     where
         T: Serialize + Send + 'static,
     {
-        let engine: Engine = self.get_engine();
+        let engine: &Engine = self.get_engine();
         let name: &str = self.get_name();
         let persistent: bool = self.get_persistent();
         let distributed: bool = self.get_distributed();
-        let store: String = engine
-            .store
-            .clone()
+        let store: &str = engine
+            .store()
             .ok_or(MontycatClientError::ClientStoreNotSet)?;
-        let use_tls: bool = engine.use_tls;
-        let command: String = "insert_value".to_string();
-
-        let value_to_send: String = process_json_value(&bulk_values)?;
+        let bulk: BulkInsert = match self.key_generator.as_deref() {
+            Some(generator) => BulkInsert::generated(
+                bulk_values
+                    .iter()
+                    .map(process_json_value)
+                    .collect::<Result<Vec<String>, MontycatClientError>>()?,
+                generator,
+            )?,
+            None => BulkInsert::plain(process_json_value(&bulk_values)?),
+        };
 
-        let new_store_request: StoreRequestClient = StoreRequestClient {
-            username: engine.username.clone(),
-            password: engine.password.clone(),
-            keyspace: name.to_owned(),
-            store,
+        let new_store_request: StoreCommand = StoreCommand {
+            keyspace: name.into(),
+            store: store.into(),
             persistent,
             distributed,
-            value: value_to_send,
-            command,
+            value: bulk.value,
+            bulk_keys_values: bulk.bulk_keys_values.into_iter().collect(),
+            operation: bulk.operation,
             ..Default::default()
         };
 
-        let bytes: Vec<u8> = Req::new_store_command(new_store_request).byte_down()?;
-        let response: Option<Vec<u8>> = send_data(
-            &engine.host,
-            engine.port,
-            bytes.as_slice(),
-            None,
-            None,
-            use_tls,
-        )
-        .await?;
-
-        Ok(response)
+        engine
+            .send_command(&Command::Store(Box::new(new_store_request)))
+            .await
     }
 }
@@ -1,19 +1,57 @@
+pub mod codec;
 pub mod engine;
 pub mod errors;
 pub mod global;
 pub mod keyspace;
 pub mod request;
 pub mod response;
+#[cfg(all(test, not(feature = "tls")))]
+pub(crate) mod test_support;
 pub mod tools;
 pub mod traits;
 
-pub use engine::structure::{Engine, ValidPermissions};
+pub use codec::Codec;
+pub use engine::{
+    capabilities::{ServerCapabilities, ServerFeature, ServerVersion},
+    config::EngineConfig,
+    credentials::{
+        CallbackCredentials, CredentialProvider, Credentials, EnvCredentials, FileCredentials,
+        SecretString, StaticCredentials,
+    },
+    structure::{Engine, RequestKind, ValidPermissions},
+};
 pub use errors::MontycatClientError;
 pub use keyspace::{
+    dyntrait::DynKeyspace,
+    hashing::{CUSTOM_KEY_FIELD, KeyHashing},
+    integrity::{
+        AuditOptions, AuditReport, DanglingAction, DanglingPointer, DeletePolicy, DeleteReport,
+    },
+    keygen::{
+        KeyGenerator, SnowflakeGenerator, UlidGenerator, UuidV4Generator, UuidV5Generator,
+        UuidV7Generator,
+    },
     pubtrait::Keyspace,
-    structures::{inmemory::InMemoryKeyspace, persistent::PersistentKeyspace},
+    registry::{KEY_REGISTRY_CATALOG, KEY_REGISTRY_SUFFIX, KeyRegistry, ResolvedKey},
+    schematrait::SchemaKeyspace,
+    structures::{
+        collection::{Collection, SaveOutcome},
+        inmemory::InMemoryKeyspace,
+        persistent::PersistentKeyspace,
+    },
 };
 pub use montycat_serialization_derive::{BinaryConvert, RuntimeSchema};
-pub use response::structure::{MontycatResponse, MontycatStreamResponse};
-pub use tools::structure::{Limit, Pointer, Timestamp};
-pub use traits::RuntimeSchema;
+pub use request::command::{Command, StoreCommand, StoreOperation};
+pub use response::{
+    structure::{MontycatResponse, MontycatStreamResponse, NestedJsonPolicy},
+    typed::{Keyed, PointerMeta, ReadOptions, ReadShape, WithPointerMetadata},
+};
+pub use tools::{
+    field_types::{FieldType, register_field_type, unregister_field_type},
+    schema::{
+        ENUM_FIELD_TYPE, EnumSchema, EnumTagging, VariantShape, register_enum_schema,
+        register_schema, unregister_schema,
+    },
+    structure::{Limit, Pointer, Timestamp},
+};
+pub use traits::{MontycatEntity, RuntimeSchema};
@@ -0,0 +1,1014 @@
+use super::{
+    store_request::structure::StoreRequestClient, structure::Req,
+    utis::functions::fulfil_subscription_request,
+};
+use crate::{
+    codec::Codec,
+    engine::{
+        credentials::{Credentials, SecretString},
+        structure::ValidPermissions,
+    },
+    errors::MontycatClientError,
+};
+use serde::Deserialize;
+use std::{borrow::Cow, collections::HashMap};
+
+/// Every operation the client sends to the Montycat server.
+///
+/// A `Command` can be encoded to the exact bytes the client puts on the wire with `encode`,
+/// and decoded back from them with `decode`. This makes requests inspectable and loggable,
+/// and lets a local stand-in server reuse the same types.
+///
+/// # Variants
+///
+/// - Raw commands, sent as `{"raw": [...], "credentials": [username, password]}`:
+///   `CreateStore`, `RemoveStore`, `GetStructureAvailable`, `ListOwners`, `ServerVersion`, `CreateOwner`, `RemoveOwner`,
+///   `GrantTo`, `RevokeFrom`, `CreateKeyspace`, `UpdateCacheCompression`, `RemoveKeyspace`,
+///   `EnforceSchema`, `RemoveEnforcedSchema`, `DoSnapshots`, `CleanSnapshots` and `StopSnapshots`.
+/// - `Subscribe`: a subscription request, sent to the subscription port.
+/// - `Store(Box<StoreCommand>)`: a keyspace data operation such as `get_value` or `insert_value`.
+///   The keyspace methods send every data operation as a `Store` command.
+/// - `Raw(Vec<String>)`: a raw command this client version does not know.
+///
+/// # Examples
+///
+/// ```rust, ignore
+/// let command = Command::CreateStore { store: "mystore".into() };
+/// let bytes: Vec<u8> = command.encode(&Credentials::new("user", "pass"))?;
+///
+/// let (decoded, credentials) = Command::decode(&bytes)?;
+/// assert_eq!(decoded, command);
+/// ```
+///
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Command {
+    CreateStore {
+        store: String,
+    },
+    RemoveStore {
+        store: String,
+    },
+    GetStructureAvailable {
+        store: Option<String>,
+    },
+    ListOwners,
+    ServerVersion,
+    CreateOwner {
+        username: String,
+        password: SecretString,
+    },
+    RemoveOwner {
+        username: String,
+    },
+    GrantTo {
+        owner: String,
+        permission: ValidPermissions,
+        store: String,
+        keyspaces: Vec<String>,
+    },
+    RevokeFrom {
+        owner: String,
+        permission: ValidPermissions,
+        store: String,
+        keyspaces: Vec<String>,
+    },
+    CreateKeyspace {
+        store: String,
+        keyspace: String,
+        persistent: bool,
+        distributed: bool,
+        cache: Option<usize>,
+        compression: Option<bool>,
+    },
+    UpdateCacheCompression {
+        store: String,
+        keyspace: String,
+        cache: usize,
+        compression: bool,
+    },
+    RemoveKeyspace {
+        store: String,
+        keyspace: String,
+        persistent: bool,
+    },
+    EnforceSchema {
+        store: String,
+        keyspace: String,
+        persistent: bool,
+        schema_name: String,
+        schema_content: String,
+    },
+    RemoveEnforcedSchema {
+        store: String,
+        keyspace: String,
+        persistent: bool,
+        schema_name: String,
+    },
+    DoSnapshots {
+        store: String,
+        keyspace: String,
+    },
+    CleanSnapshots {
+        store: String,
+        keyspace: String,
+    },
+    StopSnapshots {
+        store: String,
+        keyspace: String,
+    },
+    Subscribe {
+        store: String,
+        keyspace: String,
+        key: Option<String>,
+    },
+    Store(Box<StoreCommand>),
+    Raw(Vec<String>),
+}
+
+/// The operation named in the `command` field of a store request.
+///
+/// Unknown operation names are kept in `Other`, so they survive a decode/encode round trip.
+///
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub enum StoreOperation {
+    #[default]
+    GetValue,
+    DeleteKey,
+    ListAllDependingKeys,
+    GetBulk,
+    DeleteBulk,
+    GetLen,
+    ListAllSchemasInKeyspace,
+    UpdateValue,
+    LookupKeys,
+    LookupValues,
+    InsertValue,
+    InsertCustomKeyValue,
+    InsertCustomKey,
+    GetKeys,
+    Other(String),
+}
+
+impl StoreOperation {
+    /// Returns the operation name used on the wire.
+    pub fn as_str(&self) -> &str {
+        match self {
+            StoreOperation::GetValue => "get_value",
+            StoreOperation::DeleteKey => "delete_key",
+            StoreOperation::ListAllDependingKeys => "list_all_depending_keys",
+            StoreOperation::GetBulk => "get_bulk",
+            StoreOperation::DeleteBulk => "delete_bulk",
+            StoreOperation::GetLen => "get_len",
+            StoreOperation::ListAllSchemasInKeyspace => "list_all_schemas_in_keyspace",
+            StoreOperation::UpdateValue => "update_value",
+            StoreOperation::LookupKeys => "lookup_keys",
+            StoreOperation::LookupValues => "lookup_values",
+            StoreOperation::InsertValue => "insert_value",
+            StoreOperation::InsertCustomKeyValue => "insert_custom_key_value",
+            StoreOperation::InsertCustomKey => "insert_custom_key",
+            StoreOperation::GetKeys => "get_keys",
+            StoreOperation::Other(name) => name,
+        }
+    }
+}
+
+impl From<&str> for StoreOperation {
+    fn from(name: &str) -> Self {
+        match name {
+            "get_value" => StoreOperation::GetValue,
+            "delete_key" => StoreOperation::DeleteKey,
+            "list_all_depending_keys" => StoreOperation::ListAllDependingKeys,
+            "get_bulk" => StoreOperation::GetBulk,
+            "delete_bulk" => StoreOperation::DeleteBulk,
+            "get_len" => StoreOperation::GetLen,
+            "list_all_schemas_in_keyspace" => StoreOperation::ListAllSchemasInKeyspace,
+            "update_value" => StoreOperation::UpdateValue,
+            "lookup_keys" => StoreOperation::LookupKeys,
+            "lookup_values" => StoreOperation::LookupValues,
+            "insert_value" => StoreOperation::InsertValue,
+            "insert_custom_key_value" => StoreOperation::InsertCustomKeyValue,
+            "insert_custom_key" => StoreOperation::InsertCustomKey,
+            "get_keys" => StoreOperation::GetKeys,
+            other => StoreOperation::Other(other.to_owned()),
+        }
+    }
+}
+
+/// A keyspace data operation, i.e. the body of a store request without its credentials.
+///
+/// # Fields
+/// - `operation`: The operation to run.
+/// - `store`, `keyspace`, `persistent`, `distributed`: The target keyspace.
+/// - `schema`: The schema name attached to inserted values.
+/// - `key`: The (hashed) key the operation applies to.
+/// - `value`: The JSON-encoded value to write.
+/// - `expire`: Expiration time in seconds for in-memory keyspaces, 0 for none.
+/// - `limit_output`: The `start`/`stop` limit of listing operations.
+/// - `bulk_values`, `bulk_keys`, `bulk_keys_values`: Inputs of bulk operations.
+/// - `search_criteria`: The JSON-encoded criteria of lookups.
+/// - `with_pointers`, `key_included`, `pointers_metadata`: Read options.
+/// - `volumes`, `latest_volume`: Volume filters of listing operations.
+///
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct StoreCommand {
+    pub operation: StoreOperation,
+    pub store: String,
+    pub keyspace: String,
+    pub persistent: bool,
+    pub distributed: bool,
+    pub schema: Option<String>,
+    pub key: Option<String>,
+    pub value: String,
+    pub expire: u64,
+    pub limit_output: HashMap<String, usize>,
+    pub bulk_values: Vec<String>,
+    pub bulk_keys: Vec<String>,
+    pub bulk_keys_values: HashMap<String, String>,
+    pub search_criteria: String,
+    pub with_pointers: bool,
+    pub key_included: bool,
+    pub volumes: Vec<String>,
+    pub latest_volume: bool,
+    pub pointers_metadata: bool,
+}
+
+impl StoreCommand {
+    /// Builds the wire request for this command, authenticated with `credentials`.
+    /// The request borrows every field, so nothing is copied.
+    pub(crate) fn to_request<'a>(&'a self, credentials: &'a Credentials) -> StoreRequestClient<'a> {
+        StoreRequestClient {
+            schema: self.schema.as_deref().map(Cow::Borrowed),
+            username: credentials.username.as_str().into(),
+            password: credentials.password.expose_secret().into(),
+            keyspace: self.keyspace.as_str().into(),
+            store: self.store.as_str().into(),
+            persistent: self.persistent,
+            distributed: self.distributed,
+            limit_output: Cow::Borrowed(&self.limit_output),
+            key: self.key.as_deref().map(Cow::Borrowed),
+            value: self.value.as_str().into(),
+            command: self.operation.as_str().into(),
+            expire: self.expire,
+            bulk_values: self.bulk_values.as_slice().into(),
+            bulk_keys: self.bulk_keys.as_slice().into(),
+            bulk_keys_values: Cow::Borrowed(&self.bulk_keys_values),
+            search_criteria: self.search_criteria.as_str().into(),
+            with_pointers: self.with_pointers,
+            key_included: self.key_included,
+            volumes: self.volumes.as_slice().into(),
+            latest_volume: self.latest_volume,
+            pointers_metadata: self.pointers_metadata,
+        }
+    }
+
+    /// Splits a wire request into the command and the credentials it was sent with.
+    pub(crate) fn from_request(request: StoreRequestClient<'_>) -> (Self, Credentials) {
+        let credentials: Credentials =
+            Credentials::new(request.username.into_owned(), request.password.into_owned());
+
+        let command: StoreCommand = StoreCommand {
+            operation: StoreOperation::from(request.command.as_ref()),
+            store: request.store.into_owned(),
+            keyspace: request.keyspace.into_owned(),
+            persistent: request.persistent,
+            distributed: request.distributed,
+            schema: request.schema.map(Cow::into_owned),
+            key: request.key.map(Cow::into_owned),
+            value: request.value.into_owned(),
+            expire: request.expire,
+            limit_output: request.limit_output.into_owned(),
+            bulk_values: request.bulk_values.into_owned(),
+            bulk_keys: request.bulk_keys.into_owned(),
+            bulk_keys_values: request.bulk_keys_values.into_owned(),
+            search_criteria: request.search_criteria.into_owned(),
+            with_pointers: request.with_pointers,
+            key_included: request.key_included,
+            volumes: request.volumes.into_owned(),
+            latest_volume: request.latest_volume,
+            pointers_metadata: request.pointers_metadata,
+        };
+
+        (command, credentials)
+    }
+}
+
+/// Wire shape of a raw command, used for decoding.
+#[derive(Deserialize)]
+struct RawWire {
+    raw: Vec<String>,
+    credentials: Vec<String>,
+}
+
+/// Wire shape of a subscription request, used for decoding.
+#[derive(Deserialize)]
+struct SubscribeWire {
+    store: String,
+    keyspace: String,
+    key: Option<String>,
+    username: String,
+    password: String,
+}
+
+impl Command {
+    /// Returns the raw command tokens, or `None` for `Subscribe` and `Store` commands,
+    /// which are not sent as token lists.
+    ///
+    /// # Examples
+    ///
+    /// ```rust, ignore
+    /// let command = Command::RemoveStore { store: "mystore".into() };
+    /// assert_eq!(command.tokens().unwrap(), vec!["remove-store", "store", "mystore"]);
+    /// ```
+    ///
+    pub fn tokens(&self) -> Option<Vec<Cow<'_, str>>> {
+        let tokens: Vec<Cow<'_, str>> = match self {
+            Command::CreateStore { store } => {
+                vec!["create-store".into(), "store".into(), store.into()]
+            }
+            Command::RemoveStore { store } => {
+                vec!["remove-store".into(), "store".into(), store.into()]
+            }
+            Command::GetStructureAvailable { store } => {
+                let mut tokens: Vec<Cow<'_, str>> = vec!["get-structure-available".into()];
+                if let Some(store) = store {
+                    tokens.push("store".into());
+                    tokens.push(store.into());
+                }
+                tokens
+            }
+            Command::ListOwners => vec!["list-owners".into()],
+            Command::ServerVersion => vec!["get-server-version".into()],
+            Command::CreateOwner { username, password } => vec![
+                "create-owner".into(),
+                "username".into(),
+                username.into(),
+                "password".into(),
+                password.expose_secret().into(),
+            ],
+            Command::RemoveOwner { username } => {
+                vec!["remove-owner".into(), "username".into(), username.into()]
+            }
+            Command::GrantTo {
+                owner,
+                permission,
+                store,
+                keyspaces,
+            } => permission_tokens("grant-to", owner, *permission, store, keyspaces),
+            Command::RevokeFrom {
+                owner,
+                permission,
+                store,
+                keyspaces,
+            } => permission_tokens("revoke-from", owner, *permission, store, keyspaces),
+            Command::CreateKeyspace {
+                store,
+                keyspace,
+                persistent,
+                distributed,
+                cache,
+                compression,
+            } => {
+                let mut tokens: Vec<Cow<'_, str>> = vec![
+                    "create-keyspace".into(),
+                    "store".into(),
+                    store.into(),
+                    "keyspace".into(),
+                    keyspace.into(),
+                    "persistent".into(),
+                    flag(*persistent),
+                    "distributed".into(),
+                    flag(*distributed),
+                ];
+                if let Some(cache) = cache {
+                    tokens.push("cache".into());
+                    tokens.push(cache.to_string().into());
+                }
+                if let Some(compression) = compression {
+                    tokens.push("compression".into());
+                    tokens.push(flag(*compression));
+                }
+                tokens
+            }
+            Command::UpdateCacheCompression {
+                store,
+                keyspace,
+                cache,
+                compression,
+            } => vec![
+                "update-cache-compression".into(),
+                "store".into(),
+                store.into(),
+                "keyspace".into(),
+                keyspace.into(),
+                "cache".into(),
+                cache.to_string().into(),
+                "compression".into(),
+                flag(*compression),
+            ],
+            Command::RemoveKeyspace {
+                store,
+                keyspace,
+                persistent,
+            } => vec![
+                "remove-keyspace".into(),
+                "store".into(),
+                store.into(),
+                "keyspace".into(),
+                keyspace.into(),
+                "persistent".into(),
+                flag(*persistent),
+            ],
+            Command::EnforceSchema {
+                store,
+                keyspace,
+                persistent,
+                schema_name,
+                schema_content,
+            } => vec![
+                "enforce-schema".into(),
+                "store".into(),
+                store.into(),
+                "keyspace".into(),
+                keyspace.into(),
+                "persistent".into(),
+                flag(*persistent),
+                "schema_name".into(),
+                schema_name.into(),
+                "schema_content".into(),
+                schema_content.into(),
+            ],
+            Command::RemoveEnforcedSchema {
+                store,
+                keyspace,
+                persistent,
+                schema_name,
+            } => vec![
+                "remove-enforced-schema".into(),
+                "store".into(),
+                store.into(),
+                "keyspace".into(),
+                keyspace.into(),
+                "persistent".into(),
+                flag(*persistent),
+                "schema_name".into(),
+                schema_name.into(),
+            ],
+            Command::DoSnapshots { store, keyspace } => {
+                snapshot_tokens("do-snapshots-for-keyspace", store, keyspace)
+            }
+            Command::CleanSnapshots { store, keyspace } => {
+                snapshot_tokens("clean-snapshots-for-keyspace", store, keyspace)
+            }
+            Command::StopSnapshots { store, keyspace } => {
+                snapshot_tokens("stop-snapshots-for-keyspace", store, keyspace)
+            }
+            Command::Raw(tokens) => tokens.iter().map(|token| token.into()).collect(),
+            Command::Subscribe { .. } | Command::Store(_) => return None,
+        };
+
+        Some(tokens)
+    }
+
+    /// Encodes the command into the exact JSON bytes sent to the server, authenticated with `credentials`.
+    ///
+    /// # Errors
+    ///
+    /// Returns `MontycatClientError::ClientEngineError` if serialization fails.
+    ///
+    pub fn encode(&self, credentials: &Credentials) -> Result<Vec<u8>, MontycatClientError> {
+        self.encode_with(credentials, Codec::Json)
+    }
+
+    /// Encodes the command into a frame for `codec`, authenticated with `credentials`.
+    /// Subscriptions are always encoded as JSON.
+    ///
+    /// # Errors
+    ///
+    /// Returns `MontycatClientError::ClientEngineError` if serialization fails.
+    ///
+    pub fn encode_with(
+        &self,
+        credentials: &Credentials,
+        codec: Codec,
+    ) -> Result<Vec<u8>, MontycatClientError> {
+        match self {
+            Command::Store(store_command) => {
+                Req::new_store_command(store_command.to_request(credentials)).encode(codec)
+            }
+            Command::Subscribe {
+                store,
+                keyspace,
+                key,
+            } => fulfil_subscription_request(
+                store,
+                keyspace,
+                key.clone(),
+                &credentials.username,
+                credentials.password.expose_secret(),
+            ),
+            _ => {
+                let tokens: Vec<Cow<'_, str>> = self.tokens().unwrap_or_default();
+                Req::new_raw_command(tokens, credentials).encode(codec)
+            }
+        }
+    }
+
+    /// Decodes a request produced by `encode` into the command and the credentials it carries.
+    ///
+    /// # Errors
+    ///
+    /// Returns `MontycatClientError::ClientValueParsingError` if the bytes are not a valid request,
+    /// or if a known raw command is missing an argument.
+    ///
+    pub fn decode(bytes: &[u8]) -> Result<(Command, Credentials), MontycatClientError> {
+        let parsing_error = |e: serde_json::Error| {
+            MontycatClientError::ClientValueParsingError(format!("Invalid request: {}", e))
+        };
+
+        let value: serde_json::Value = serde_json::from_slice(bytes).map_err(parsing_error)?;
+
+        if value.get("raw").is_some() {
+            let raw: RawWire = serde_json::from_value(value).map_err(parsing_error)?;
+            let credentials: Credentials = match raw.credentials.as_slice() {
+                [username, password] => Credentials::new(username.as_str(), password.as_str()),
+                _ => {
+                    return Err(MontycatClientError::ClientValueParsingError(
+                        "Raw command credentials must be [username, password]".into(),
+                    ));
+                }
+            };
+
+            return Ok((Self::from_tokens(raw.raw)?, credentials));
+        }
+
+        if value.get("subscribe").is_some() {
+            let subscribe: SubscribeWire = serde_json::from_value(value).map_err(parsing_error)?;
+            let command: Command = Command::Subscribe {
+                store: subscribe.store,
+                keyspace: subscribe.keyspace,
+                key: subscribe.key,
+            };
+
+            return Ok((
+                command,
+                Credentials::new(subscribe.username, subscribe.password),
+            ));
+        }
+
+        let request: StoreRequestClient =
+            StoreRequestClient::deserialize(&value).map_err(parsing_error)?;
+        let (command, credentials) = StoreCommand::from_request(request);
+
+        Ok((Command::Store(Box::new(command)), credentials))
+    }
+
+    /// Parses raw command tokens. Unknown command names are returned as `Command::Raw`.
+    ///
+    /// # Errors
+    ///
+    /// Returns `MontycatClientError::ClientValueParsingError` if a known command is missing an argument
+    /// or has an invalid one.
+    ///
+    pub fn from_tokens(tokens: Vec<String>) -> Result<Command, MontycatClientError> {
+        let Some((name, args)) = tokens.split_first() else {
+            return Err(MontycatClientError::ClientValueParsingError(
+                "Raw command must not be empty".into(),
+            ));
+        };
+
+        let args: TokenArgs = TokenArgs { name, args };
+
+        let command: Command = match name.as_str() {
+            "create-store" => Command::CreateStore {
+                store: args.required("store")?,
+            },
+            "remove-store" => Command::RemoveStore {
+                store: args.required("store")?,
+            },
+            "get-structure-available" => Command::GetStructureAvailable {
+                store: args.optional("store"),
+            },
+            "list-owners" => Command::ListOwners,
+            "get-server-version" => Command::ServerVersion,
+            "create-owner" => Command::CreateOwner {
+                username: args.required("username")?,
+                password: SecretString::new(args.required("password")?),
+            },
+            "remove-owner" => Command::RemoveOwner {
+                username: args.required("username")?,
+            },
+            "grant-to" => Command::GrantTo {
+                owner: args.required("owner")?,
+                permission: args.permission()?,
+                store: args.required("store")?,
+                keyspaces: args.list("keyspaces"),
+            },
+            "revoke-from" => Command::RevokeFrom {
+                owner: args.required("owner")?,
+                permission: args.permission()?,
+                store: args.required("store")?,
+                keyspaces: args.list("keyspaces"),
+            },
+            "create-keyspace" => Command::CreateKeyspace {
+                store: args.required("store")?,
+                keyspace: args.required("keyspace")?,
+                persistent: args.flag("persistent")?,
+                distributed: args.flag("distributed")?,
+                cache: args.optional_number("cache")?,
+                compression: args.optional_flag("compression")?,
+            },
+            "update-cache-compression" => Command::UpdateCacheCompression {
+                store: args.required("store")?,
+                keyspace: args.required("keyspace")?,
+                cache: args.optional_number("cache")?.unwrap_or(0),
+                compression: args.optional_flag("compression")?.unwrap_or(false),
+            },
+            "remove-keyspace" => Command::RemoveKeyspace {
+                store: args.required("store")?,
+                keyspace: args.required("keyspace")?,
+                persistent: args.flag("persistent")?,
+            },
+            "enforce-schema" => Command::EnforceSchema {
+                store: args.required("store")?,
+                keyspace: args.required("keyspace")?,
+                persistent: args.flag("persistent")?,
+                schema_name: args.required("schema_name")?,
+                schema_content: args.required("schema_content")?,
+            },
+            "remove-enforced-schema" => Command::RemoveEnforcedSchema {
+                store: args.required("store")?,
+                keyspace: args.required("keyspace")?,
+                persistent: args.flag("persistent")?,
+                schema_name: args.required("schema_name")?,
+            },
+            "do-snapshots-for-keyspace" => Command::DoSnapshots {
+                store: args.required("store")?,
+                keyspace: args.required("keyspace")?,
+            },
+            "clean-snapshots-for-keyspace" => Command::CleanSnapshots {
+                store: args.required("store")?,
+                keyspace: args.required("keyspace")?,
+            },
+            "stop-snapshots-for-keyspace" => Command::StopSnapshots {
+                store: args.required("store")?,
+                keyspace: args.required("keyspace")?,
+            },
+            _ => Command::Raw(tokens),
+        };
+
+        Ok(command)
+    }
+}
+
+fn flag(value: bool) -> Cow<'static, str> {
+    if value { "y".into() } else { "n".into() }
+}
+
+fn permission_tokens<'a>(
+    name: &'static str,
+    owner: &'a str,
+    permission: ValidPermissions,
+    store: &'a str,
+    keyspaces: &[String],
+) -> Vec<Cow<'a, str>> {
+    let mut tokens: Vec<Cow<'a, str>> = vec![
+        name.into(),
+        "owner".into(),
+        owner.into(),
+        "permission".into(),
+        permission.as_str().into(),
+        "store".into(),
+        store.into(),
+    ];
+
+    if !keyspaces.is_empty() {
+        tokens.push("keyspaces".into());
+        tokens.push(keyspaces.join(",").into());
+    }
+
+    tokens
+}
+
+fn snapshot_tokens<'a>(name: &'static str, store: &'a str, keyspace: &'a str) -> Vec<Cow<'a, str>> {
+    vec![
+        name.into(),
+        "store".into(),
+        store.into(),
+        "keyspace".into(),
+        keyspace.into(),
+    ]
+}
+
+/// `name value` argument pairs following the command name in a raw token list.
+struct TokenArgs<'a> {
+    name: &'a str,
+    args: &'a [String],
+}
+
+impl TokenArgs<'_> {
+    fn optional(&self, key: &str) -> Option<String> {
+        self.args
+            .chunks_exact(2)
+            .find(|pair| pair[0] == key)
+            .map(|pair| pair[1].clone())
+    }
+
+    fn required(&self, key: &str) -> Result<String, MontycatClientError> {
+        self.optional(key).ok_or_else(|| {
+            MontycatClientError::ClientValueParsingError(format!(
+                "Missing {} in {} command",
+                key, self.name
+            ))
+        })
+    }
+
+    fn invalid(&self, key: &str) -> MontycatClientError {
+        MontycatClientError::ClientValueParsingError(format!(
+            "Invalid {} in {} command",
+            key, self.name
+        ))
+    }
+
+    fn optional_flag(&self, key: &str) -> Result<Option<bool>, MontycatClientError> {
+        match self.optional(key).as_deref() {
+            Some("y") => Ok(Some(true)),
+            Some("n") => Ok(Some(false)),
+            Some(_) => Err(self.invalid(key)),
+            None => Ok(None),
+        }
+    }
+
+    fn flag(&self, key: &str) -> Result<bool, MontycatClientError> {
+        self.required(key)?;
+        Ok(self.optional_flag(key)?.unwrap_or_default())
+    }
+
+    fn optional_number(&self, key: &str) -> Result<Option<usize>, MontycatClientError> {
+        self.optional(key)
+            .map(|raw| raw.parse().map_err(|_| self.invalid(key)))
+            .transpose()
+    }
+
+    fn list(&self, key: &str) -> Vec<String> {
+        self.optional(key)
+            .map(|raw| raw.split(',').map(str::to_owned).collect())
+            .unwrap_or_default()
+    }
+
+    fn permission(&self) -> Result<ValidPermissions, MontycatClientError> {
+        let raw: String = self.required("permission")?;
+        ValidPermissions::from_wire(&raw).ok_or_else(|| self.invalid("permission"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn credentials() -> Credentials {
+        Credentials::new("user", "pass")
+    }
+
+    fn assert_round_trip(command: Command, expected_tokens: &[&str]) {
+        let tokens: Vec<Cow<str>> = command.tokens().unwrap();
+        assert_eq!(tokens, expected_tokens);
+
+        let legacy: Vec<u8> = Req::new_raw_command(
+            expected_tokens.iter().map(|t| Cow::Borrowed(*t)).collect(),
+            &credentials(),
+        )
+        .encode(Codec::Json)
+        .unwrap();
+        let encoded: Vec<u8> = command.encode(&credentials()).unwrap();
+        assert_eq!(encoded, legacy);
+
+        let (decoded, decoded_credentials) = Command::decode(&encoded).unwrap();
+        assert_eq!(decoded, command);
+        assert_eq!(decoded_credentials, credentials());
+    }
+
+    #[test]
+    fn test_store_commands_round_trip() {
+        assert_round_trip(
+            Command::CreateStore {
+                store: "mystore".into(),
+            },
+            &["create-store", "store", "mystore"],
+        );
+        assert_round_trip(
+            Command::GetStructureAvailable { store: None },
+            &["get-structure-available"],
+        );
+        assert_round_trip(Command::ListOwners, &["list-owners"]);
+        assert_round_trip(Command::ServerVersion, &["get-server-version"]);
+    }
+
+    #[test]
+    fn test_permission_commands_round_trip() {
+        assert_round_trip(
+            Command::GrantTo {
+                owner: "bob".into(),
+                permission: ValidPermissions::Read,
+                store: "mystore".into(),
+                keyspaces: vec!["a".into(), "b".into()],
+            },
+            &[
+                "grant-to",
+                "owner",
+                "bob",
+                "permission",
+                "read",
+                "store",
+                "mystore",
+                "keyspaces",
+                "a,b",
+            ],
+        );
+        assert_round_trip(
+            Command::RevokeFrom {
+                owner: "bob".into(),
+                permission: ValidPermissions::All,
+                store: "mystore".into(),
+                keyspaces: Vec::new(),
+            },
+            &[
+                "revoke-from",
+                "owner",
+                "bob",
+                "permission",
+                "all",
+                "store",
+                "mystore",
+            ],
+        );
+    }
+
+    #[test]
+    fn test_keyspace_commands_round_trip() {
+        assert_round_trip(
+            Command::CreateKeyspace {
+                store: "s".into(),
+                keyspace: "k".into(),
+                persistent: true,
+                distributed: false,
+                cache: Some(0),
+                compression: Some(false),
+            },
+            &[
+                "create-keyspace",
+                "store",
+                "s",
+                "keyspace",
+                "k",
+                "persistent",
+                "y",
+                "distributed",
+                "n",
+                "cache",
+                "0",
+                "compression",
+                "n",
+            ],
+        );
+        assert_round_trip(
+            Command::CreateKeyspace {
+                store: "s".into(),
+                keyspace: "k".into(),
+                persistent: false,
+                distributed: false,
+                cache: None,
+                compression: None,
+            },
+            &[
+                "create-keyspace",
+                "store",
+                "s",
+                "keyspace",
+                "k",
+                "persistent",
+                "n",
+                "distributed",
+                "n",
+            ],
+        );
+        assert_round_trip(
+            Command::RemoveEnforcedSchema {
+                store: "s".into(),
+                keyspace: "k".into(),
+                persistent: true,
+                schema_name: "User".into(),
+            },
+            &[
+                "remove-enforced-schema",
+                "store",
+                "s",
+                "keyspace",
+                "k",
+                "persistent",
+                "y",
+                "schema_name",
+                "User",
+            ],
+        );
+        assert_round_trip(
+            Command::StopSnapshots {
+                store: "s".into(),
+                keyspace: "k".into(),
+            },
+            &["stop-snapshots-for-keyspace", "store", "s", "keyspace", "k"],
+        );
+    }
+
+    #[test]
+    fn test_store_command_round_trip() {
+        let command = Command::Store(Box::new(StoreCommand {
+            operation: StoreOperation::GetBulk,
+            store: "s".into(),
+            keyspace: "k".into(),
+            persistent: true,
+            bulk_keys: vec!["1".into(), "2".into()],
+            key_included: true,
+            ..Default::default()
+        }));
+
+        let encoded = command.encode(&credentials()).unwrap();
+        let (decoded, decoded_credentials) = Command::decode(&encoded).unwrap();
+
+        assert_eq!(decoded, command);
+        assert_eq!(decoded_credentials, credentials());
+    }
+
+    #[test]
+    fn test_store_request_borrows_the_command_payload() {
+        let command = StoreCommand {
+            operation: StoreOperation::InsertCustomKeyValue,
+            key: Some("1".into()),
+            value: "{\"name\":\"Ann\"}".into(),
+            bulk_keys: vec!["2".into()],
+            bulk_keys_values: HashMap::from([("3".into(), "{}".into())]),
+            ..Default::default()
+        };
+
+        let credentials: Credentials = credentials();
+        let request: StoreRequestClient = command.to_request(&credentials);
+
+        assert!(matches!(request.key, Some(Cow::Borrowed("1"))));
+        assert!(matches!(request.value, Cow::Borrowed(_)));
+        assert!(matches!(request.bulk_keys, Cow::Borrowed(_)));
+        assert!(matches!(request.bulk_keys_values, Cow::Borrowed(_)));
+    }
+
+    #[test]
+    fn test_subscribe_round_trip() {
+        let command = Command::Subscribe {
+            store: "s".into(),
+            keyspace: "k".into(),
+            key: Some("42".into()),
+        };
+
+        let encoded = command.encode(&credentials()).unwrap();
+        let (decoded, _) = Command::decode(&encoded).unwrap();
+
+        assert_eq!(decoded, command);
+    }
+
+    #[test]
+    fn test_unknown_commands_are_preserved() {
+        let (raw, _) =
+            Command::decode(br#"{"raw":["new-command","arg","1"],"credentials":["user","pass"]}"#)
+                .unwrap();
+        assert_eq!(
+            raw,
+            Command::Raw(vec!["new-command".into(), "arg".into(), "1".into()])
+        );
+
+        assert_eq!(
+            StoreOperation::from("future_operation"),
+            StoreOperation::Other("future_operation".into())
+        );
+        assert_eq!(
+            StoreOperation::from("lookup_values").as_str(),
+            "lookup_values"
+        );
+    }
+
+    #[test]
+    fn test_missing_argument_is_rejected() {
+        let result = Command::from_tokens(vec!["create-store".into()]);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_create_owner_debug_redacts_password() {
+        let command = Command::CreateOwner {
+            username: "bob".into(),
+            password: "hunter2".into(),
+        };
+
+        assert!(!format!("{:?}", command).contains("hunter2"));
+        assert_eq!(
+            command.tokens().unwrap(),
+            vec!["create-owner", "username", "bob", "password", "hunter2"]
+        );
+    }
+}
@@ -1,3 +1,4 @@
+pub mod command;
 pub mod store_request;
 pub mod structure;
 pub mod utis;
@@ -1,53 +1,61 @@
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::{borrow::Cow, collections::HashMap};
 
 /// Represents a store request to be sent to the Montycat server from the client side.
 ///
 /// # Fields
-/// - `schema: Option<String>` : The schema to be used.
-/// - `username: String` : The username for authentication.
-/// - `password: String` : The password for authentication.
-/// - `keyspace: String` : The keyspace to be used.
-/// - `store: String` : The store to be used.
+/// - `schema: Option<Cow<str>>` : The schema to be used.
+/// - `username: Cow<str>` : The username for authentication.
+/// - `password: Cow<str>` : The password for authentication.
+/// - `keyspace: Cow<str>` : The keyspace to be used.
+/// - `store: Cow<str>` : The store to be used.
 /// - `persistent: bool` : Indicates if the store is persistent.
 /// - `distributed: bool` : Indicates if the store is distributed.
-/// - `limit_output: HashMap<String, usize>` : Limits for output.
-/// - `key: Option<String>` : The key for the operation.
-/// - `value: String` : The value for the operation.
-/// - `command: String` : The command to be executed.
+/// - `limit_output: Cow<HashMap<String, usize>>` : Limits for output.
+/// - `key: Option<Cow<str>>` : The key for the operation.
+/// - `value: Cow<str>` : The value for the operation.
+/// - `command: Cow<str>` : The command to be executed.
 /// - `expire: u64` : Expiration time for the key.
-/// - `bulk_values: Vec<String>` : Bulk values for the operation.
-/// - `bulk_keys: Vec<String>` : Bulk keys for the operation.
-/// - `bulk_keys_values: HashMap<String, String>` : Bulk key-value pairs for the operation.
-/// - `search_criteria: String` : Criteria for searching.
+/// - `bulk_values: Cow<[String]>` : Bulk values for the operation.
+/// - `bulk_keys: Cow<[String]>` : Bulk keys for the operation.
+/// - `bulk_keys_values: Cow<HashMap<String, String>>` : Bulk key-value pairs for the operation.
+/// - `search_criteria: Cow<str>` : Criteria for searching.
 /// - `with_pointers: bool` : Indicates if pointers should be included.
 /// - `key_included: bool` : Indicates if the key is included in the response.
-/// - `volumes: Vec<String>` : Volumes to be used.
+/// - `volumes: Cow<[String]>` : Volumes to be used.
 /// - `latest_volume: bool` : Indicates if the latest volume should be used.
 /// - `pointers_metadata: bool` : Indicates if pointers metadata should be included.
 ///
+/// Every string and list field is a `Cow`, so a request built from a `StoreCommand` borrows the
+/// command's payload and the credentials instead of copying them.
+///
 #[derive(Serialize, Deserialize, Debug, Clone, Default)]
-pub(crate) struct StoreRequestClient {
-    pub schema: Option<String>,
-    pub username: String,
-    pub password: String,
-    pub keyspace: String,
-    pub store: String,
+pub(crate) struct StoreRequestClient<'a> {
+    pub schema: Option<Cow<'a, str>>,
+    #[serde(borrow)]
+    pub username: Cow<'a, str>,
+    #[serde(borrow)]
+    pub password: Cow<'a, str>,
+    #[serde(borrow)]
+    pub keyspace: Cow<'a, str>,
+    #[serde(borrow)]
+    pub store: Cow<'a, str>,
     pub persistent: bool,
     pub distributed: bool,
-    pub limit_output: HashMap<String, usize>,
-    pub key: Option<String>,
-    pub value: String,
-    pub command: String,
+    pub limit_output: Cow<'a, HashMap<String, usize>>,
+    pub key: Option<Cow<'a, str>>,
+    pub value: Cow<'a, str>,
+    #[serde(borrow)]
+    pub command: Cow<'a, str>,
     pub expire: u64,
-    pub bulk_values: Vec<String>,
-    pub bulk_keys: Vec<String>,
-    pub bulk_keys_values: HashMap<String, String>,
-    pub search_criteria: String,
+    pub bulk_values: Cow<'a, [String]>,
+    pub bulk_keys: Cow<'a, [String]>,
+    pub bulk_keys_values: Cow<'a, HashMap<String, String>>,
+    pub search_criteria: Cow<'a, str>,
     pub with_pointers: bool,
 
     pub key_included: bool,
-    pub volumes: Vec<String>,
+    pub volumes: Cow<'a, [String]>,
     pub latest_volume: bool,
     pub pointers_metadata: bool,
 }
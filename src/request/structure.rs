@@ -1,43 +1,59 @@
 use super::store_request::structure::StoreRequestClient;
-use crate::errors::MontycatClientError;
-use indexmap::IndexMap;
-use simd_json;
+use crate::{codec::Codec, engine::credentials::Credentials, errors::MontycatClientError};
+use serde::Serialize;
+use std::borrow::Cow;
 
 /// Represents a request to be sent to the Montycat server.
 ///
 /// # Variants
-/// - `Raw(IndexMap<String, Vec<String>>)` : A raw command represented as a map.
+/// - `Raw(RawRequest)` : A raw command with the credentials it is sent with.
 /// - `Store(StoreRequestClient)` : A store command represented by a `StoreRequestClient`.
 ///
 /// Methods:
-/// - `new_raw_command(command: Vec<String>, credentials: Vec<String>) -> Self` : Creates a new raw command request.
+/// - `new_raw_command(command: Vec<Cow<str>>, credentials: &Credentials) -> Self` : Creates a new raw command request.
 /// - `new_store_command(store_request: StoreRequestClient) -> Self` : Creates a new store command request.
-/// - `byte_down(&self) -> Result<Vec<u8>, MontycatClientError>` : Serializes the request into a byte vector.   
+/// - `encode(&self, codec: Codec) -> Result<Vec<u8>, MontycatClientError>` : Serializes the request with `codec`.
 ///
 /// Errors:
 /// - `MontycatClientError::ClientEngineError(String)` : Returned if serialization fails.
 ///
 #[derive(Debug, Clone)]
-pub(crate) enum Req {
-    Raw(IndexMap<String, Vec<String>>),
-    Store(Box<StoreRequestClient>),
+pub(crate) enum Req<'a> {
+    Raw(RawRequest<'a>),
+    Store(Box<StoreRequestClient<'a>>),
 }
 
-impl Req {
+/// Wire representation of a raw command: `{"raw": [...], "credentials": [username, password]}`.
+///
+/// The credentials are borrowed, so they are written straight into the output buffer.
+///
+#[derive(Serialize, Debug, Clone)]
+pub(crate) struct RawRequest<'a> {
+    pub raw: Vec<Cow<'a, str>>,
+    pub credentials: [&'a str; 2],
+}
+
+impl<'a> Req<'a> {
     /// Creates a new raw command request.
     ///
     /// # Arguments
-    /// - `command: Vec<String>` : The command to be sent.
-    /// - `credentials: Vec<String>` : The credentials for authentication.
+    /// - `command: Vec<Cow<str>>` : The command to be sent.
+    /// - `credentials: &Credentials` : The credentials for authentication.
     ///
     /// # Returns
     /// - `Self` : A new instance of `Req` representing the raw command.
     ///
-    pub(crate) fn new_raw_command(command: Vec<String>, credentials: Vec<String>) -> Self {
-        let mut map: IndexMap<String, Vec<String>> = IndexMap::new();
-        map.insert("raw".to_string(), command);
-        map.insert("credentials".to_string(), credentials);
-        Req::Raw(map)
+    pub(crate) fn new_raw_command(
+        command: Vec<Cow<'a, str>>,
+        credentials: &'a Credentials,
+    ) -> Self {
+        Req::Raw(RawRequest {
+            raw: command,
+            credentials: [
+                credentials.username.as_str(),
+                credentials.password.expose_secret(),
+            ],
+        })
     }
 
     /// Creates a new store command request.
@@ -48,31 +64,60 @@ impl Req {
     /// # Returns
     /// - `Self` : A new instance of `Req` representing the store command.
     ///
-    pub(crate) fn new_store_command(store_request: StoreRequestClient) -> Self {
+    pub(crate) fn new_store_command(store_request: StoreRequestClient<'a>) -> Self {
         Req::Store(Box::new(store_request))
     }
 
-    /// Serializes the request into a byte vector.
+    /// Serializes the request into a complete frame for `codec`.
     ///
     /// # Returns
-    /// - `Result<Vec<u8>, MontycatClientError>` : The serialized byte vector or an error if serialization fails.
+    /// - `Result<Vec<u8>, MontycatClientError>` : The serialized frame or an error if serialization fails.
     ///
-    pub(crate) fn byte_down(&self) -> Result<Vec<u8>, MontycatClientError> {
+    pub(crate) fn encode(&self, codec: Codec) -> Result<Vec<u8>, MontycatClientError> {
         match self {
-            Req::Raw(map) => {
-                let json_str: String = simd_json::to_string(map)
-                    .map_err(|e| MontycatClientError::ClientEngineError(e.to_string()))?;
-                let mut bytes: Vec<u8> = json_str.into_bytes();
-                bytes.push(b'\n');
-                Ok(bytes)
-            }
-            Req::Store(map) => {
-                let json_str: String = simd_json::to_string(map)
-                    .map_err(|e| MontycatClientError::ClientEngineError(e.to_string()))?;
-                let mut bytes: Vec<u8> = json_str.into_bytes();
-                bytes.push(b'\n');
-                Ok(bytes)
-            }
+            Req::Raw(raw) => codec.encode(raw),
+            Req::Store(store_request) => codec.encode(store_request),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_raw_command_wire_format() {
+        let credentials = Credentials::new("user", "pass");
+        let request = Req::new_raw_command(
+            vec!["create-store".into(), "store".into(), "mystore".into()],
+            &credentials,
+        );
+
+        let bytes = request.encode(Codec::Json).unwrap();
+        assert_eq!(
+            String::from_utf8(bytes).unwrap(),
+            "{\"raw\":[\"create-store\",\"store\",\"mystore\"],\"credentials\":[\"user\",\"pass\"]}\n"
+        );
+    }
+
+    #[test]
+    fn test_store_command_round_trip() {
+        let request = StoreRequestClient {
+            username: "user".into(),
+            password: "pass".into(),
+            keyspace: "employees".into(),
+            store: "mystore".into(),
+            command: "get_value".into(),
+            key: Some("123".into()),
+            ..Default::default()
+        };
+
+        let bytes = Req::new_store_command(request).encode(Codec::Json).unwrap();
+        let parsed: StoreRequestClient = serde_json::from_slice(&bytes).unwrap();
+
+        assert_eq!(parsed.username, "user");
+        assert_eq!(parsed.keyspace, "employees");
+        assert_eq!(parsed.command, "get_value");
+        assert_eq!(parsed.key.as_deref(), Some("123"));
+    }
+}
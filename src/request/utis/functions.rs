@@ -1,26 +1,11 @@
-use crate::{MontycatClientError, global::PRIMITIVE_TYPES, tools::functions::process_json_value};
+use crate::{
+    MontycatClientError, global::PRIMITIVE_TYPES, keyspace::hashing::KeyHashing,
+    tools::functions::process_json_value,
+};
 use indexmap::IndexMap;
 use rayon::prelude::*;
 use serde::Serialize;
 use std::collections::HashMap;
-use std::fmt::Display;
-use xxhash_rust::xxh32::xxh32;
-
-/// Converts a custom key (integer or string) into a hashed value using xxHash.
-///
-/// # Arguments
-///
-/// * `key` - A key that implements `Display` (e.g., integer, string, etc.)
-///
-/// # Returns
-///
-/// * `String` - The xxHash digest of the provided key, returned as a string.
-///
-/// This function ensures consistent hashing for any key type.
-pub(crate) fn convert_custom_key<T: Display>(key: T) -> String {
-    let key_str = key.to_string();
-    xxh32(key_str.as_bytes(), 0).to_string()
-}
 
 /// Determines if a given type name is a custom type (not a primitive type).
 ///
@@ -44,6 +29,7 @@ pub(crate) fn is_custom_type(type_name: &str) -> Option<&str> {
 /// # Arguments
 /// * `bulk_keys: Option<Vec<String>>` - A vector of bulk keys.
 /// * `bulk_custom_keys: Option<Vec<String>>` - A vector of custom keys.
+/// * `hashing: KeyHashing` - The scheme used to hash the custom keys.
 ///
 /// # Returns
 /// * `Result<Vec<String>, MontycatClientError>` - A result containing the merged vector of keys or an error if no valid input is provided.
@@ -51,6 +37,7 @@ pub(crate) fn is_custom_type(type_name: &str) -> Option<&str> {
 pub(crate) async fn merge_keys(
     bulk_keys: Option<Vec<String>>,
     bulk_custom_keys: Option<Vec<String>>,
+    hashing: KeyHashing,
 ) -> Result<Vec<String>, MontycatClientError> {
     if bulk_keys.is_none() && bulk_custom_keys.is_none() {
         return Err(MontycatClientError::ClientNoValidInputProvided);
@@ -70,7 +57,7 @@ pub(crate) async fn merge_keys(
         }
 
         if let Some(custom) = custom_keys_clone {
-            keys_merged.extend(custom.into_iter().map(convert_custom_key));
+            keys_merged.extend(custom.into_iter().map(|key| hashing.hash(key)));
         }
 
         keys_merged
@@ -90,6 +77,7 @@ pub(crate) async fn merge_keys(
 /// # Arguments
 /// * `bulk_keys_values: Vec<HashMap<String, T>>` - A vector of HashMaps containing bulk key-value pairs.
 /// * `bulk_custom_keys_values: Vec<HashMap<String, T>>` - A vector of HashMaps containing custom key-value pairs.
+/// * `hashing: KeyHashing` - The scheme used to hash the custom keys.
 ///
 /// # Returns
 /// * `Result<HashMap<String, String>, MontycatClientError>` - A result containing the merged HashMap of key-value pairs or an error if serialization fails.
@@ -97,6 +85,7 @@ pub(crate) async fn merge_keys(
 pub(crate) async fn merge_bulk_keys_values<T>(
     bulk_keys_values: Vec<HashMap<String, T>>,
     bulk_custom_keys_values: Vec<HashMap<String, T>>,
+    hashing: KeyHashing,
 ) -> Result<HashMap<String, String>, MontycatClientError>
 where
     T: Serialize + Send + 'static,
@@ -107,7 +96,7 @@ where
         if !bulk_custom_keys_values.is_empty() {
             for custom_key_value in bulk_custom_keys_values {
                 for (custom_key, value) in custom_key_value {
-                    let internal_key = convert_custom_key(&custom_key);
+                    let internal_key = hashing.hash(&custom_key);
                     let mut map = HashMap::new();
                     map.insert(internal_key, value);
                     bulk_keys_values.push(map);
@@ -1 +1,3 @@
+pub(crate) mod nested;
 pub mod structure;
+pub mod typed;
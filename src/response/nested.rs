@@ -0,0 +1,633 @@
+use super::structure::NestedJsonPolicy;
+use crate::{codec::Codec, errors::MontycatClientError};
+use serde::de::{
+    self, Deserialize, DeserializeOwned, DeserializeSeed, Deserializer, EnumAccess,
+    IntoDeserializer, MapAccess, SeqAccess, VariantAccess, Visitor,
+};
+use std::{borrow::Cow, collections::VecDeque, fmt};
+
+/// Sections of a stored value that hold pointer and timestamp fields, see `process_value`.
+const SECTIONS: [&str; 2] = ["pointers", "timestamps"];
+
+/// Deserializes a complete message body into `T` in a single pass, unwrapping nested JSON strings
+/// according to `policy`. The codec is detected from the bytes, and `T` may borrow unescaped strings from them.
+///
+/// # Errors
+///
+/// Returns `MontycatClientError::ClientValueParsingError` if the bytes are not a valid `T`.
+///
+pub(crate) fn from_slice<'de, T: Deserialize<'de>>(
+    bytes: &'de [u8],
+    policy: NestedJsonPolicy,
+) -> Result<T, MontycatClientError> {
+    let scope: Scope = Scope::root(policy);
+
+    match Codec::detect(bytes) {
+        Codec::Json => {
+            let mut deserializer = serde_json::Deserializer::from_slice(bytes);
+            let value: T = T::deserialize(NestedJson::new(&mut deserializer, scope))
+                .and_then(|value| deserializer.end().map(|_| value))
+                .map_err(|e| MontycatClientError::ClientValueParsingError(e.to_string()))?;
+            Ok(value)
+        }
+        #[cfg(not(feature = "msgpack"))]
+        Codec::MessagePack => Err(crate::codec::msgpack_unsupported()),
+        #[cfg(feature = "msgpack")]
+        Codec::MessagePack => {
+            let mut deserializer = rmp_serde::Deserializer::from_read_ref(bytes);
+            T::deserialize(NestedJson::new(&mut deserializer, scope))
+                .map_err(|e| MontycatClientError::ClientValueParsingError(e.to_string()))
+        }
+    }
+}
+
+/// Deserializes `T` from a stored document that was already parsed, the same way as a document
+/// read from a response: typed containers unwrap JSON strings and structs restore their pointer
+/// and timestamp fields.
+///
+/// # Errors
+///
+/// Returns `MontycatClientError::ClientValueParsingError` if the document is not a valid `T`.
+///
+pub(crate) fn from_value<T: DeserializeOwned>(
+    document: serde_json::Value,
+) -> Result<T, MontycatClientError> {
+    let scope: Scope = Scope::root(NestedJsonPolicy::default()).document();
+
+    T::deserialize(NestedJson::new(document, scope))
+        .map_err(|e| MontycatClientError::ClientValueParsingError(e.to_string()))
+}
+
+/// Deserializer adapter that unwraps values the server sends as JSON-encoded strings.
+///
+/// Montycat stores values as JSON text, so a stored struct comes back as the string `"{\"id\":1}"`
+/// rather than as an object. `NestedJson` wraps any deserializer and, whenever the target type asks
+/// for a struct, map, sequence or tuple but the input holds a string, parses that string as JSON and
+/// deserializes the target from it. Everything else is forwarded untouched, so the response is read
+/// in a single pass, unescaped strings can be borrowed from the input, and `String` fields keep their
+/// exact contents even if they look like JSON.
+///
+/// Self-describing targets such as `serde_json::Value` cannot say what they expect, so `Scope` decides
+/// for them, see `NestedJsonPolicy`.
+///
+struct NestedJson<D> {
+    inner: D,
+    scope: Scope,
+}
+
+impl<D> NestedJson<D> {
+    fn new(inner: D, scope: Scope) -> Self {
+        Self { inner, scope }
+    }
+}
+
+/// Position of a value in the response, relative to where the protocol nests stored documents.
+#[derive(Clone, Copy)]
+struct Scope {
+    policy: NestedJsonPolicy,
+    /// 0 for the envelope, 1 for the payload, 2 for the payload elements.
+    depth: usize,
+    /// True inside a document that was already unwrapped from a string.
+    in_document: bool,
+}
+
+impl Scope {
+    fn root(policy: NestedJsonPolicy) -> Self {
+        Self {
+            policy,
+            depth: 0,
+            in_document: false,
+        }
+    }
+
+    fn child(self) -> Self {
+        Self {
+            depth: self.depth.saturating_add(1),
+            ..self
+        }
+    }
+
+    fn document(self) -> Self {
+        Self {
+            in_document: true,
+            ..self
+        }
+    }
+
+    /// Returns true if a JSON-looking string read into a self-describing target should be unwrapped.
+    fn unwraps_untyped(self) -> bool {
+        match self.policy {
+            NestedJsonPolicy::Protocol => !self.in_document && matches!(self.depth, 1 | 2),
+            NestedJsonPolicy::Heuristic => true,
+        }
+    }
+}
+
+/// What the target type asked the wrapped deserializer for.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Expect {
+    /// A struct, map, sequence or tuple: JSON strings are unwrapped.
+    Container,
+    /// Anything, the target is self-describing: JSON-looking strings are unwrapped where the scope allows it.
+    Any,
+    /// A scalar, option, newtype or enum: strings are kept as they are.
+    Scalar,
+}
+
+/// Visitor wrapper that unwraps JSON strings according to `expect` and wraps nested accesses.
+struct NestedVisitor<V> {
+    inner: V,
+    expect: Expect,
+    scope: Scope,
+    /// Fields of the struct being read, whose values may be stored in the `SECTIONS`.
+    fields: &'static [&'static str],
+}
+
+impl<V> NestedVisitor<V> {
+    fn new(inner: V, expect: Expect, scope: Scope) -> Self {
+        Self {
+            inner,
+            expect,
+            scope,
+            fields: &[],
+        }
+    }
+
+    fn with_fields(self, fields: &'static [&'static str]) -> Self {
+        Self { fields, ..self }
+    }
+
+    /// Structs read their pointer and timestamp fields back from the `SECTIONS`, unless they
+    /// declare fields with those names.
+    fn restores_sections(&self) -> bool {
+        !self.fields.is_empty() && !self.fields.iter().any(|field| SECTIONS.contains(field))
+    }
+}
+
+impl<'de, V: Visitor<'de>> NestedVisitor<V> {
+    /// Deserializes the target from `text`, if it is nested JSON the target should be read from.
+    fn unwrap_str<E: de::Error>(self, text: &str) -> Result<Result<V::Value, E>, Self> {
+        match self.expect {
+            Expect::Container => {}
+            Expect::Any if self.scope.unwraps_untyped() && looks_like_json(text) => {}
+            _ => return Err(self),
+        }
+
+        let value: serde_json::Value = match serde_json::from_str(text) {
+            Ok(value) => value,
+            Err(e) if self.expect == Expect::Container => return Ok(Err(E::custom(e))),
+            Err(_) => return Err(self),
+        };
+
+        let visitor: Self = Self {
+            scope: self.scope.document(),
+            ..self
+        };
+        Ok(value.deserialize_any(visitor).map_err(E::custom))
+    }
+}
+
+fn looks_like_json(text: &str) -> bool {
+    (text.starts_with('{') && text.ends_with('}')) || (text.starts_with('[') && text.ends_with(']'))
+}
+
+macro_rules! forward_visit {
+    ($($method:ident($ty:ty)),* $(,)?) => {
+        $(
+            fn $method<E: de::Error>(self, v: $ty) -> Result<Self::Value, E> {
+                self.inner.$method(v)
+            }
+        )*
+    };
+}
+
+impl<'de, V: Visitor<'de>> Visitor<'de> for NestedVisitor<V> {
+    type Value = V::Value;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.inner.expecting(f)
+    }
+
+    forward_visit!(
+        visit_bool(bool),
+        visit_i8(i8),
+        visit_i16(i16),
+        visit_i32(i32),
+        visit_i64(i64),
+        visit_i128(i128),
+        visit_u8(u8),
+        visit_u16(u16),
+        visit_u32(u32),
+        visit_u64(u64),
+        visit_u128(u128),
+        visit_f32(f32),
+        visit_f64(f64),
+        visit_char(char),
+        visit_bytes(&[u8]),
+        visit_borrowed_bytes(&'de [u8]),
+        visit_byte_buf(Vec<u8>),
+    );
+
+    fn visit_str<E: de::Error>(self, v: &str) -> Result<Self::Value, E> {
+        match self.unwrap_str(v) {
+            Ok(result) => result,
+            Err(visitor) => visitor.inner.visit_str(v),
+        }
+    }
+
+    fn visit_borrowed_str<E: de::Error>(self, v: &'de str) -> Result<Self::Value, E> {
+        match self.unwrap_str(v) {
+            Ok(result) => result,
+            Err(visitor) => visitor.inner.visit_borrowed_str(v),
+        }
+    }
+
+    fn visit_string<E: de::Error>(self, v: String) -> Result<Self::Value, E> {
+        match self.unwrap_str(&v) {
+            Ok(result) => result,
+            Err(visitor) => visitor.inner.visit_string(v),
+        }
+    }
+
+    fn visit_none<E: de::Error>(self) -> Result<Self::Value, E> {
+        self.inner.visit_none()
+    }
+
+    fn visit_some<D: Deserializer<'de>>(self, deserializer: D) -> Result<Self::Value, D::Error> {
+        self.inner
+            .visit_some(NestedJson::new(deserializer, self.scope))
+    }
+
+    fn visit_unit<E: de::Error>(self) -> Result<Self::Value, E> {
+        self.inner.visit_unit()
+    }
+
+    fn visit_newtype_struct<D: Deserializer<'de>>(
+        self,
+        deserializer: D,
+    ) -> Result<Self::Value, D::Error> {
+        self.inner
+            .visit_newtype_struct(NestedJson::new(deserializer, self.scope))
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, seq: A) -> Result<Self::Value, A::Error> {
+        self.inner
+            .visit_seq(NestedJson::new(seq, self.scope.child()))
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, map: A) -> Result<Self::Value, A::Error> {
+        let map: NestedJson<A> = NestedJson::new(map, self.scope.child());
+
+        if self.restores_sections() {
+            self.inner.visit_map(SectionMap::new(map, self.fields))
+        } else {
+            self.inner.visit_map(map)
+        }
+    }
+
+    fn visit_enum<A: EnumAccess<'de>>(self, data: A) -> Result<Self::Value, A::Error> {
+        self.inner.visit_enum(NestedJson::new(data, self.scope))
+    }
+}
+
+macro_rules! forward_scalar {
+    ($($method:ident),* $(,)?) => {
+        $(
+            fn $method<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+                self.inner.$method(visitor)
+            }
+        )*
+    };
+}
+
+impl<'de, D: Deserializer<'de>> Deserializer<'de> for NestedJson<D> {
+    type Error = D::Error;
+
+    forward_scalar!(
+        deserialize_bool,
+        deserialize_i8,
+        deserialize_i16,
+        deserialize_i32,
+        deserialize_i64,
+        deserialize_i128,
+        deserialize_u8,
+        deserialize_u16,
+        deserialize_u32,
+        deserialize_u64,
+        deserialize_u128,
+        deserialize_f32,
+        deserialize_f64,
+        deserialize_char,
+        deserialize_str,
+        deserialize_string,
+        deserialize_bytes,
+        deserialize_byte_buf,
+        deserialize_unit,
+        deserialize_identifier,
+        deserialize_ignored_any,
+    );
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        self.inner
+            .deserialize_any(NestedVisitor::new(visitor, Expect::Any, self.scope))
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        self.inner
+            .deserialize_option(NestedVisitor::new(visitor, Expect::Scalar, self.scope))
+    }
+
+    fn deserialize_unit_struct<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        self.inner.deserialize_unit_struct(name, visitor)
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        self.inner.deserialize_newtype_struct(
+            name,
+            NestedVisitor::new(visitor, Expect::Scalar, self.scope),
+        )
+    }
+
+    // Containers are read with `deserialize_any`, because most formats reject a string before the
+    // visitor sees it when asked for a struct or a sequence.
+
+    fn deserialize_seq<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        self.inner
+            .deserialize_any(NestedVisitor::new(visitor, Expect::Container, self.scope))
+    }
+
+    fn deserialize_tuple<V: Visitor<'de>>(
+        self,
+        _len: usize,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        self.deserialize_seq(visitor)
+    }
+
+    fn deserialize_tuple_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _len: usize,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        self.deserialize_seq(visitor)
+    }
+
+    fn deserialize_map<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        self.deserialize_seq(visitor)
+    }
+
+    fn deserialize_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        self.inner.deserialize_any(
+            NestedVisitor::new(visitor, Expect::Container, self.scope).with_fields(fields),
+        )
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        self.inner.deserialize_enum(
+            name,
+            variants,
+            NestedVisitor::new(visitor, Expect::Scalar, self.scope),
+        )
+    }
+
+    fn is_human_readable(&self) -> bool {
+        self.inner.is_human_readable()
+    }
+}
+
+/// Seed wrapper that deserializes nested values through `NestedJson`.
+struct NestedSeed<S> {
+    inner: S,
+    scope: Scope,
+}
+
+impl<'de, S: DeserializeSeed<'de>> DeserializeSeed<'de> for NestedSeed<S> {
+    type Value = S::Value;
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<Self::Value, D::Error> {
+        self.inner
+            .deserialize(NestedJson::new(deserializer, self.scope))
+    }
+}
+
+impl<'de, A: SeqAccess<'de>> SeqAccess<'de> for NestedJson<A> {
+    type Error = A::Error;
+
+    fn next_element_seed<T: DeserializeSeed<'de>>(
+        &mut self,
+        seed: T,
+    ) -> Result<Option<T::Value>, Self::Error> {
+        self.inner.next_element_seed(NestedSeed {
+            inner: seed,
+            scope: self.scope,
+        })
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        self.inner.size_hint()
+    }
+}
+
+impl<'de, A: MapAccess<'de>> MapAccess<'de> for NestedJson<A> {
+    type Error = A::Error;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(
+        &mut self,
+        seed: K,
+    ) -> Result<Option<K::Value>, Self::Error> {
+        self.inner.next_key_seed(seed)
+    }
+
+    fn next_value_seed<T: DeserializeSeed<'de>>(
+        &mut self,
+        seed: T,
+    ) -> Result<T::Value, Self::Error> {
+        self.inner.next_value_seed(NestedSeed {
+            inner: seed,
+            scope: self.scope,
+        })
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        self.inner.size_hint()
+    }
+}
+
+impl<'de, A: EnumAccess<'de>> EnumAccess<'de> for NestedJson<A> {
+    type Error = A::Error;
+    type Variant = NestedJson<A::Variant>;
+
+    fn variant_seed<T: DeserializeSeed<'de>>(
+        self,
+        seed: T,
+    ) -> Result<(T::Value, Self::Variant), Self::Error> {
+        let (value, variant) = self.inner.variant_seed(seed)?;
+        Ok((value, NestedJson::new(variant, self.scope)))
+    }
+}
+
+impl<'de, A: VariantAccess<'de>> VariantAccess<'de> for NestedJson<A> {
+    type Error = A::Error;
+
+    fn unit_variant(self) -> Result<(), Self::Error> {
+        self.inner.unit_variant()
+    }
+
+    fn newtype_variant_seed<T: DeserializeSeed<'de>>(
+        self,
+        seed: T,
+    ) -> Result<T::Value, Self::Error> {
+        self.inner.newtype_variant_seed(NestedSeed {
+            inner: seed,
+            scope: self.scope,
+        })
+    }
+
+    fn tuple_variant<V: Visitor<'de>>(
+        self,
+        len: usize,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        self.inner.tuple_variant(
+            len,
+            NestedVisitor::new(visitor, Expect::Container, self.scope),
+        )
+    }
+
+    fn struct_variant<V: Visitor<'de>>(
+        self,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        self.inner.struct_variant(
+            fields,
+            NestedVisitor::new(visitor, Expect::Container, self.scope),
+        )
+    }
+}
+
+/// Map access over a stored struct that yields the entries of its `pointers` and `timestamps`
+/// sections as the fields they were taken from, so the struct reads them back in place.
+struct SectionMap<A> {
+    inner: NestedJson<A>,
+    fields: &'static [&'static str],
+    restored: VecDeque<(String, serde_json::Value)>,
+    pending: Option<serde_json::Value>,
+}
+
+impl<A> SectionMap<A> {
+    fn new(inner: NestedJson<A>, fields: &'static [&'static str]) -> Self {
+        Self {
+            inner,
+            fields,
+            restored: VecDeque::new(),
+            pending: None,
+        }
+    }
+}
+
+/// A map key, borrowed from the input when possible.
+struct KeyName<'de>(Cow<'de, str>);
+
+impl<'de> Deserialize<'de> for KeyName<'de> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct KeyVisitor;
+
+        impl<'de> Visitor<'de> for KeyVisitor {
+            type Value = KeyName<'de>;
+
+            fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str("a field name")
+            }
+
+            fn visit_borrowed_str<E: de::Error>(self, v: &'de str) -> Result<Self::Value, E> {
+                Ok(KeyName(Cow::Borrowed(v)))
+            }
+
+            fn visit_str<E: de::Error>(self, v: &str) -> Result<Self::Value, E> {
+                Ok(KeyName(Cow::Owned(v.to_owned())))
+            }
+
+            fn visit_string<E: de::Error>(self, v: String) -> Result<Self::Value, E> {
+                Ok(KeyName(Cow::Owned(v)))
+            }
+        }
+
+        deserializer.deserialize_str(KeyVisitor)
+    }
+}
+
+impl<'de, A: MapAccess<'de>> MapAccess<'de> for SectionMap<A> {
+    type Error = A::Error;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(
+        &mut self,
+        seed: K,
+    ) -> Result<Option<K::Value>, Self::Error> {
+        loop {
+            if let Some((field, value)) = self.restored.pop_front() {
+                self.pending = Some(value);
+                return seed.deserialize(field.into_deserializer()).map(Some);
+            }
+
+            let Some(KeyName(key)) = self.inner.next_key::<KeyName>()? else {
+                return Ok(None);
+            };
+
+            if !SECTIONS.contains(&key.as_ref()) {
+                return match key {
+                    Cow::Borrowed(key) => {
+                        seed.deserialize(de::value::BorrowedStrDeserializer::new(key))
+                    }
+                    Cow::Owned(key) => seed.deserialize(key.into_deserializer()),
+                }
+                .map(Some);
+            }
+
+            let section: Option<serde_json::Map<String, serde_json::Value>> =
+                self.inner.next_value()?;
+
+            self.restored.extend(
+                section
+                    .into_iter()
+                    .flatten()
+                    .filter(|(field, _)| self.fields.contains(&field.as_str())),
+            );
+        }
+    }
+
+    fn next_value_seed<T: DeserializeSeed<'de>>(
+        &mut self,
+        seed: T,
+    ) -> Result<T::Value, Self::Error> {
+        match self.pending.take() {
+            Some(value) => seed
+                .deserialize(NestedJson::new(value, self.inner.scope.document()))
+                .map_err(de::Error::custom),
+            None => self.inner.next_value_seed(seed),
+        }
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        self.inner.size_hint()
+    }
+}
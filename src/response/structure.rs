@@ -1,7 +1,7 @@
+use super::nested;
 use crate::errors::MontycatClientError;
 use core::fmt;
-use serde::{Deserialize, Serialize};
-use simd_json;
+use serde::{Deserialize, Serialize, de::DeserializeOwned};
 
 /// Represents a response from the Montycat server.
 ///
@@ -35,14 +35,125 @@ pub struct MontycatStreamResponse<T = serde_json::Value> {
     pub error: Option<String>,
 }
 
+/// How JSON-encoded strings are unwrapped when the target type does not say what it expects.
+///
+/// Typed targets are always parsed exactly: a string is only parsed as JSON where the type declares
+/// a struct, map, sequence or tuple (the field types `RuntimeSchema` reports), so a `String` field
+/// holding `"[draft]"` round-trips byte-for-byte. The policy only applies to self-describing targets
+/// such as `serde_json::Value`.
+///
+/// # Variants
+///
+/// - `Protocol`: Unwraps only where the protocol nests stored values: the payload itself and the
+///   elements of a payload list or map. Strings inside a stored value are never touched. The default.
+/// - `Heuristic`: Unwraps every string that starts and ends with `{}` or `[]` and parses as JSON,
+///   at any depth. This was the behaviour of earlier releases.
+///
+/// # Examples
+///
+/// ```rust, ignore
+/// let parsed: MontycatResponse<Vec<serde_json::Value>> =
+///     MontycatResponse::parse_response_with(res, NestedJsonPolicy::Heuristic)?;
+/// ```
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum NestedJsonPolicy {
+    #[default]
+    Protocol,
+    Heuristic,
+}
+
+/// Wire envelope shared by both response types. A missing payload is decoded as if it were null.
+#[derive(Deserialize)]
+struct Envelope<T> {
+    message: Option<String>,
+    status: bool,
+    payload: Option<T>,
+    error: Option<String>,
+}
+
+impl<'de, T: Deserialize<'de>> Envelope<T> {
+    fn decode(
+        bytes: &'de [u8],
+        policy: NestedJsonPolicy,
+    ) -> Result<(Self, T), MontycatClientError> {
+        let mut envelope: Envelope<T> = nested::from_slice(bytes, policy)?;
+
+        let payload: T = match envelope.payload.take() {
+            Some(payload) => payload,
+            None => T::deserialize(serde_json::Value::Null)
+                .map_err(|e| MontycatClientError::ClientValueParsingError(e.to_string()))?,
+        };
+
+        Ok((envelope, payload))
+    }
+}
+
+impl<'de, T> MontycatResponse<T>
+where
+    T: Deserialize<'de>,
+{
+    /// Deserializes a response directly from the received bytes, in a single pass.
+    ///
+    /// Values the server returns as JSON-encoded strings are unwrapped only where `T` expects a
+    /// struct, map, sequence or tuple, so `String` fields keep their exact contents. Untyped values
+    /// follow `NestedJsonPolicy::Protocol`.
+    /// The payload may borrow from `bytes`, e.g. `MontycatResponse<&str>` or a struct with `&str` fields.
+    /// Responses sent with the MessagePack codec are detected and decoded automatically.
+    ///
+    /// # Arguments
+    ///
+    /// * `bytes` - The complete response body
+    ///
+    /// # Errors
+    ///
+    /// - Returns `MontycatClientError::ClientValueParsingError` if the bytes are not a valid response.
+    ///
+    /// # Example
+    ///
+    /// ```rust, ignore
+    /// let bytes: Vec<u8> = res?.unwrap_or_default();
+    /// let parsed: MontycatResponse<&str> = MontycatResponse::from_slice(&bytes)?;
+    /// ```
+    ///
+    pub fn from_slice(bytes: &'de [u8]) -> Result<Self, MontycatClientError> {
+        Self::from_slice_with(bytes, NestedJsonPolicy::default())
+    }
+
+    /// Same as `MontycatResponse::from_slice`, with an explicit policy for untyped values.
+    ///
+    /// # Arguments
+    ///
+    /// * `bytes` - The complete response body
+    /// * `policy` - How JSON-encoded strings in untyped values are unwrapped
+    ///
+    /// # Errors
+    ///
+    /// - Returns `MontycatClientError::ClientValueParsingError` if the bytes are not a valid response.
+    ///
+    pub fn from_slice_with(
+        bytes: &'de [u8],
+        policy: NestedJsonPolicy,
+    ) -> Result<Self, MontycatClientError> {
+        let (envelope, payload): (Envelope<T>, T) = Envelope::decode(bytes, policy)?;
+
+        Ok(MontycatResponse {
+            status: envelope.status,
+            payload,
+            error: envelope.error,
+        })
+    }
+}
+
 impl<T> MontycatResponse<T>
 where
     for<'de> T: Deserialize<'de> + Clone + 'static + fmt::Debug,
 {
     /// Parses the response bytes into a MontycatResponse<T>.
     ///
-    /// This function handles nested JSON strings by recursively parsing them.
-    /// If the payload contains JSON strings, they will be parsed into their respective structures.
+    /// This function deserializes the response in a single pass, see `MontycatResponse::from_slice`.
+    /// If the payload contains JSON strings where `T` expects structured data, they will be parsed into their
+    /// respective structures. Responses sent with the MessagePack codec are detected and decoded automatically.
     ///
     /// # Errors
     ///
@@ -58,67 +169,100 @@ where
     pub fn parse_response(
         bytes: Result<Option<Vec<u8>>, MontycatClientError>,
     ) -> Result<Self, MontycatClientError> {
-        let mut bytes_unwrapped: Vec<u8> = bytes?.ok_or_else(|| {
+        let bytes_unwrapped: Vec<u8> = bytes?.ok_or_else(|| {
+            MontycatClientError::ClientValueParsingError("No data received".into())
+        })?;
+
+        Self::from_slice(&bytes_unwrapped)
+    }
+
+    /// Same as `MontycatResponse::parse_response`, with an explicit policy for untyped values.
+    /// Pass `NestedJsonPolicy::Heuristic` to keep the behaviour of earlier releases.
+    ///
+    /// # Errors
+    ///
+    /// - Returns `MontycatClientError::ClientValueParsingError` if parsing fails at any step.
+    ///
+    pub fn parse_response_with(
+        bytes: Result<Option<Vec<u8>>, MontycatClientError>,
+        policy: NestedJsonPolicy,
+    ) -> Result<Self, MontycatClientError> {
+        let bytes_unwrapped: Vec<u8> = bytes?.ok_or_else(|| {
             MontycatClientError::ClientValueParsingError("No data received".into())
         })?;
-        let slice: &mut [u8] = bytes_unwrapped.as_mut_slice();
-
-        let mut response: MontycatResponse<simd_json::OwnedValue> = simd_json::from_slice(slice)
-            .map_err(|e| MontycatClientError::ClientValueParsingError(e.to_string()))?;
-
-        fn recursively_parse_json(v: simd_json::OwnedValue) -> simd_json::OwnedValue {
-            match v {
-                simd_json::OwnedValue::String(s) => {
-                    if (s.starts_with('{') && s.ends_with('}'))
-                        || (s.starts_with('[') && s.ends_with(']'))
-                    {
-                        let mut bytes = s.as_bytes().to_vec();
-                        if let Ok(inner) =
-                            simd_json::from_slice::<simd_json::OwnedValue>(bytes.as_mut_slice())
-                        {
-                            return recursively_parse_json(inner);
-                        }
-                    }
-
-                    simd_json::OwnedValue::String(s)
-                }
-
-                simd_json::OwnedValue::Array(boxed_vec) => {
-                    let vec = *boxed_vec;
-                    let new_vec = vec
-                        .into_iter()
-                        .map(recursively_parse_json)
-                        .collect::<Vec<_>>();
-                    simd_json::OwnedValue::Array(Box::new(new_vec))
-                }
-
-                simd_json::OwnedValue::Object(boxed_map) => {
-                    let map = *boxed_map;
-                    let new_map = map
-                        .into_iter()
-                        .map(|(k, v)| (k, recursively_parse_json(v)))
-                        .collect::<_>();
-                    simd_json::OwnedValue::Object(Box::new(new_map))
-                }
-
-                other => other,
-            }
+
+        Self::from_slice_with(&bytes_unwrapped, policy)
+    }
+}
+
+impl<T> MontycatResponse<T> {
+    /// Returns the payload of a successful response.
+    ///
+    /// # Errors
+    ///
+    /// - Returns `MontycatClientError::ClientServerError` with the server's error message if `status` is false.
+    ///
+    /// # Example
+    ///
+    /// ```rust, ignore
+    /// let employee: Option<Employee> = MontycatResponse::parse_response(res)?.into_result()?;
+    /// ```
+    ///
+    pub fn into_result(self) -> Result<T, MontycatClientError> {
+        if self.status {
+            Ok(self.payload)
+        } else {
+            Err(MontycatClientError::ClientServerError(
+                self.error
+                    .unwrap_or_else(|| "Request failed without an error message".into()),
+            ))
         }
+    }
+}
 
-        let normalized_payload: simd_json::OwnedValue =
-            recursively_parse_json(response.payload.clone());
+/// Parses a raw keyspace response and returns its payload, turning `status: false` into an error.
+pub(crate) fn parse_payload<T: DeserializeOwned>(
+    bytes: Result<Option<Vec<u8>>, MontycatClientError>,
+) -> Result<T, MontycatClientError> {
+    let bytes_unwrapped: Vec<u8> = bytes?
+        .ok_or_else(|| MontycatClientError::ClientValueParsingError("No data received".into()))?;
 
-        let s = simd_json::to_string(&normalized_payload)
-            .map_err(|e| MontycatClientError::ClientValueParsingError(format!("{}", e)))?;
+    // Failed responses usually carry a null payload, so check the status before requiring a `T`.
+    let response: MontycatResponse<Option<T>> = MontycatResponse::from_slice(&bytes_unwrapped)?;
 
-        let payload: T = serde_json::from_str(&s)
-            .map_err(|e| MontycatClientError::ClientValueParsingError(format!("{}", e)))?;
+    match response.into_result()? {
+        Some(payload) => Ok(payload),
+        None => T::deserialize(serde_json::Value::Null)
+            .map_err(|e| MontycatClientError::ClientValueParsingError(e.to_string())),
+    }
+}
 
-        Ok(MontycatResponse {
-            status: response.status,
-            payload,
-            error: response.error.take(),
-        })
+/// Parses a response whose payload is a key, e.g. the key an insert created.
+pub(crate) fn parse_key(
+    bytes: Result<Option<Vec<u8>>, MontycatClientError>,
+) -> Result<String, MontycatClientError> {
+    key_from_value(parse_payload(bytes)?)
+}
+
+/// Parses a response whose payload is a list of keys.
+pub(crate) fn parse_keys(
+    bytes: Result<Option<Vec<u8>>, MontycatClientError>,
+) -> Result<Vec<String>, MontycatClientError> {
+    parse_payload::<Vec<serde_json::Value>>(bytes)?
+        .into_iter()
+        .map(key_from_value)
+        .collect()
+}
+
+/// Keys are sent as strings, older servers may send them as numbers.
+fn key_from_value(value: serde_json::Value) -> Result<String, MontycatClientError> {
+    match value {
+        serde_json::Value::String(key) => Ok(key),
+        serde_json::Value::Number(key) => Ok(key.to_string()),
+        other => Err(MontycatClientError::ClientValueParsingError(format!(
+            "Expected a key, got {}",
+            other
+        ))),
     }
 }
 
@@ -128,8 +272,8 @@ where
 {
     /// Parses the response bytes into a MontycatStreamResponse<T>.
     ///
-    /// This function handles nested JSON strings by recursively parsing them.
-    /// If the payload contains JSON strings, they will be parsed into their respective structures.
+    /// This function deserializes the response in a single pass, unwrapping JSON strings
+    /// where `T` expects structured data, exactly like `MontycatResponse::parse_response`.
     ///
     /// # Errors
     ///
@@ -143,63 +287,26 @@ where
     /// ```
     ///
     pub fn parse_response(bytes: &mut [u8]) -> Result<Self, MontycatClientError> {
-        let mut response: MontycatStreamResponse<simd_json::OwnedValue> =
-            simd_json::from_slice(bytes)
-                .map_err(|e| MontycatClientError::ClientValueParsingError(e.to_string()))?;
-
-        fn recursively_parse_json(v: simd_json::OwnedValue) -> simd_json::OwnedValue {
-            match v {
-                simd_json::OwnedValue::String(s) => {
-                    if (s.starts_with('{') && s.ends_with('}'))
-                        || (s.starts_with('[') && s.ends_with(']'))
-                    {
-                        let mut bytes = s.as_bytes().to_vec();
-                        if let Ok(inner) =
-                            simd_json::from_slice::<simd_json::OwnedValue>(bytes.as_mut_slice())
-                        {
-                            return recursively_parse_json(inner);
-                        }
-                    }
-
-                    simd_json::OwnedValue::String(s)
-                }
-
-                simd_json::OwnedValue::Array(boxed_vec) => {
-                    let vec = *boxed_vec;
-                    let new_vec = vec
-                        .into_iter()
-                        .map(recursively_parse_json)
-                        .collect::<Vec<_>>();
-                    simd_json::OwnedValue::Array(Box::new(new_vec))
-                }
-
-                simd_json::OwnedValue::Object(boxed_map) => {
-                    let map = *boxed_map;
-                    let new_map = map
-                        .into_iter()
-                        .map(|(k, v)| (k, recursively_parse_json(v)))
-                        .collect::<_>();
-                    simd_json::OwnedValue::Object(Box::new(new_map))
-                }
-
-                other => other,
-            }
-        }
-
-        let normalized_payload: simd_json::OwnedValue =
-            recursively_parse_json(response.payload.clone());
-
-        let s = simd_json::to_string(&normalized_payload)
-            .map_err(|e| MontycatClientError::ClientValueParsingError(format!("{}", e)))?;
+        Self::parse_response_with(bytes, NestedJsonPolicy::default())
+    }
 
-        let payload: T = serde_json::from_str(&s)
-            .map_err(|e| MontycatClientError::ClientValueParsingError(format!("{}", e)))?;
+    /// Same as `MontycatStreamResponse::parse_response`, with an explicit policy for untyped values.
+    ///
+    /// # Errors
+    ///
+    /// If the response cannot be parsed, an error will be returned.
+    ///
+    pub fn parse_response_with(
+        bytes: &[u8],
+        policy: NestedJsonPolicy,
+    ) -> Result<Self, MontycatClientError> {
+        let (envelope, payload): (Envelope<T>, T) = Envelope::decode(bytes, policy)?;
 
         Ok(MontycatStreamResponse {
-            status: response.status,
-            message: response.message.take(),
+            message: envelope.message,
+            status: envelope.status,
             payload,
-            error: response.error.take(),
+            error: envelope.error,
         })
     }
 }
@@ -207,7 +314,9 @@ where
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::{Pointer, RuntimeSchema, Timestamp, tools::functions::process_value};
     use serde::{Deserialize, Serialize};
+    use std::collections::HashMap;
 
     #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
     struct TestStruct {
@@ -439,4 +548,186 @@ mod tests {
         assert_eq!(response.payload[0].id, 1);
         assert_eq!(response.payload[1].name, "item2");
     }
+
+    #[test]
+    fn test_from_slice_borrows_payload() {
+        let bytes = br#"{"status":true,"payload":"borrowed","error":null}"#;
+
+        let response: MontycatResponse<&str> = MontycatResponse::from_slice(bytes).unwrap();
+
+        assert!(response.status);
+        assert_eq!(response.payload, "borrowed");
+        assert!(bytes.as_ptr_range().contains(&response.payload.as_ptr()));
+    }
+
+    #[test]
+    fn test_string_payloads_that_look_like_json_are_kept() {
+        let json_str = r#"{"status":true,"payload":["[draft]","{\"id\":1}"],"error":null}"#;
+        let bytes = Ok(Some(json_str.as_bytes().to_vec()));
+
+        let response: MontycatResponse<Vec<String>> =
+            MontycatResponse::parse_response(bytes).unwrap();
+
+        assert_eq!(response.payload, vec!["[draft]", "{\"id\":1}"]);
+    }
+
+    #[test]
+    fn test_nested_json_inside_collections() {
+        let json_str = r#"{"status":true,"payload":{"a":"{\"id\":1,\"name\":\"[draft]\"}","b":null},"error":null}"#;
+        let bytes = Ok(Some(json_str.as_bytes().to_vec()));
+
+        let response: MontycatResponse<HashMap<String, Option<TestStruct>>> =
+            MontycatResponse::parse_response(bytes).unwrap();
+
+        assert_eq!(
+            response.payload["a"],
+            Some(TestStruct {
+                id: 1,
+                name: "[draft]".into()
+            })
+        );
+        assert_eq!(response.payload["b"], None);
+    }
+
+    #[test]
+    fn test_untyped_payloads_still_unwrap_json_strings() {
+        let json_str = r#"{"status":true,"payload":["{\"id\":1}","[draft]","plain"],"error":null}"#;
+        let bytes = Ok(Some(json_str.as_bytes().to_vec()));
+
+        let response: MontycatResponse = MontycatResponse::parse_response(bytes).unwrap();
+
+        assert_eq!(
+            response.payload,
+            serde_json::json!([{"id": 1}, "[draft]", "plain"])
+        );
+    }
+
+    #[test]
+    fn test_missing_payload_is_null() {
+        let bytes = br#"{"status":false,"error":"Key not found"}"#;
+
+        let response: MontycatResponse<Option<TestStruct>> =
+            MontycatResponse::from_slice(bytes).unwrap();
+        let result: Result<MontycatResponse<String>, MontycatClientError> =
+            MontycatResponse::from_slice(bytes);
+
+        assert!(response.payload.is_none());
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_protocol_policy_keeps_strings_inside_stored_values() {
+        let json_str =
+            r#"{"status":true,"payload":["{\"id\":1,\"note\":\"{\\\"a\\\":1}\"}"],"error":null}"#;
+
+        let exact: MontycatResponse = MontycatResponse::from_slice(json_str.as_bytes()).unwrap();
+        let heuristic: MontycatResponse =
+            MontycatResponse::from_slice_with(json_str.as_bytes(), NestedJsonPolicy::Heuristic)
+                .unwrap();
+
+        assert_eq!(
+            exact.payload,
+            serde_json::json!([{"id": 1, "note": "{\"a\":1}"}])
+        );
+        assert_eq!(
+            heuristic.payload,
+            serde_json::json!([{"id": 1, "note": {"a": 1}}])
+        );
+    }
+
+    #[test]
+    fn test_parse_payload_turns_failed_status_into_error() {
+        let failed = br#"{"status":false,"payload":null,"error":"Key not found"}"#.to_vec();
+        let empty = br#"{"status":true,"payload":null,"error":null}"#.to_vec();
+
+        let error = parse_payload::<Vec<TestStruct>>(Ok(Some(failed))).unwrap_err();
+        let value: Option<TestStruct> = parse_payload(Ok(Some(empty))).unwrap();
+
+        assert!(matches!(error, MontycatClientError::ClientServerError(_)));
+        assert_eq!(error.message(), "Key not found");
+        assert!(value.is_none());
+    }
+
+    #[test]
+    fn test_parse_keys() {
+        let text =
+            br#"{"status":true,"payload":"298989599989124434694729184587200373152","error":null}"#;
+        let number = br#"{"status":true,"payload":42,"error":null}"#;
+        let list = br#"{"status":true,"payload":["1",2],"error":null}"#;
+
+        assert_eq!(
+            parse_key(Ok(Some(text.to_vec()))).unwrap(),
+            "298989599989124434694729184587200373152"
+        );
+        assert_eq!(parse_key(Ok(Some(number.to_vec()))).unwrap(), "42");
+        assert_eq!(parse_keys(Ok(Some(list.to_vec()))).unwrap(), vec!["1", "2"]);
+    }
+
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize, RuntimeSchema)]
+    struct Linked {
+        name: String,
+        owner: Pointer,
+        reviewer: Option<Pointer>,
+        watchers: Vec<Pointer>,
+        by_role: HashMap<String, Option<Pointer>>,
+        created: Timestamp,
+        closed: Option<Timestamp>,
+        history: Vec<Timestamp>,
+    }
+
+    fn linked() -> Linked {
+        Linked {
+            name: "doc".into(),
+            owner: Pointer::new("users", "1"),
+            reviewer: None,
+            watchers: vec![Pointer::new("users", "2"), Pointer::new("bots", "3")],
+            by_role: HashMap::from([
+                ("admin".to_string(), Some(Pointer::new("users", "1"))),
+                ("guest".to_string(), None),
+            ]),
+            created: Timestamp::new("2024-01-01T00:00:00Z"),
+            closed: Some(Timestamp::new("2024-02-01T00:00:00Z")),
+            history: vec![Timestamp::new("2024-01-15T00:00:00Z")],
+        }
+    }
+
+    #[test]
+    fn test_pointer_and_timestamp_sections_keep_their_shape() {
+        let stored: serde_json::Value =
+            serde_json::from_str(&process_value(linked()).unwrap()).unwrap();
+
+        assert_eq!(
+            stored,
+            serde_json::json!({
+                "name": "doc",
+                "reviewer": null,
+                "pointers": {
+                    "owner": ["users", "1"],
+                    "watchers": [["users", "2"], ["bots", "3"]],
+                    "by_role": { "admin": ["users", "1"], "guest": null },
+                },
+                "timestamps": {
+                    "created": "2024-01-01T00:00:00Z",
+                    "closed": "2024-02-01T00:00:00Z",
+                    "history": ["2024-01-15T00:00:00Z"],
+                },
+            })
+        );
+    }
+
+    #[test]
+    fn test_typed_reads_restore_pointer_and_timestamp_fields() {
+        let stored: String = process_value(linked()).unwrap();
+        let response: String = serde_json::json!({
+            "status": true,
+            "payload": [stored.clone(), stored],
+            "error": null,
+        })
+        .to_string();
+
+        let parsed: MontycatResponse<Vec<Linked>> =
+            MontycatResponse::from_slice(response.as_bytes()).unwrap();
+
+        assert_eq!(parsed.payload, vec![linked(), linked()]);
+    }
 }
@@ -0,0 +1,461 @@
+use serde::{
+    Deserialize, Deserializer, Serialize,
+    de::{self, DeserializeOwned, MapAccess, SeqAccess, Visitor},
+};
+use std::{collections::HashMap, fmt, marker::PhantomData};
+
+/// A value returned together with its key, for reads made with `key_included`.
+///
+/// Deserializes from `{"key": "...", "value": ...}` or from a single-entry map `{"<key>": ...}`.
+///
+/// # Fields
+/// - `key: String` : The key the value is stored under.
+/// - `value: T` : The value.
+///
+/// # Examples
+///
+/// ```rust, ignore
+/// let employees: Vec<Keyed<Employee>> = keyspace
+///     .get_bulk_as(Some(keys), None, ReadOptions::new().key_included(), None, None, None)
+///     .await?;
+/// ```
+///
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Keyed<T> {
+    pub key: String,
+    pub value: T,
+}
+
+impl<'de, T: Deserialize<'de>> Deserialize<'de> for Keyed<T> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_map(KeyedVisitor(PhantomData))
+    }
+}
+
+struct KeyedVisitor<T>(PhantomData<T>);
+
+impl<'de, T: Deserialize<'de>> Visitor<'de> for KeyedVisitor<T> {
+    type Value = Keyed<T>;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("a keyed value")
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+        let first: String = map
+            .next_key()?
+            .ok_or_else(|| de::Error::custom("expected a key and a value, got an empty map"))?;
+
+        if first != "key" && first != "value" {
+            let value: T = map.next_value()?;
+            if map.next_key::<de::IgnoredAny>()?.is_some() {
+                return Err(de::Error::custom("expected a single key and value"));
+            }
+            return Ok(Keyed { key: first, value });
+        }
+
+        let mut key: Option<String> = None;
+        let mut value: Option<T> = None;
+        let mut field: Option<String> = Some(first);
+
+        while let Some(name) = field {
+            match name.as_str() {
+                "key" => key = Some(map.next_value::<KeyString>()?.0),
+                "value" => value = Some(map.next_value()?),
+                _ => {
+                    map.next_value::<de::IgnoredAny>()?;
+                }
+            }
+            field = map.next_key()?;
+        }
+
+        Ok(Keyed {
+            key: key.ok_or_else(|| de::Error::missing_field("key"))?,
+            value: value.ok_or_else(|| de::Error::missing_field("value"))?,
+        })
+    }
+}
+
+/// Keys are sent as strings, older servers may send them as numbers.
+struct KeyString(String);
+
+impl<'de> Deserialize<'de> for KeyString {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        match serde_json::Value::deserialize(deserializer)? {
+            serde_json::Value::String(key) => Ok(KeyString(key)),
+            serde_json::Value::Number(key) => Ok(KeyString(key.to_string())),
+            other => Err(de::Error::custom(format!("expected a key, got {}", other))),
+        }
+    }
+}
+
+/// Keyed values returned as a list of keyed values or as a map from key to value.
+#[derive(Debug, Clone, PartialEq)]
+pub struct KeyedList<T>(pub Vec<Keyed<T>>);
+
+impl<'de, T: Deserialize<'de>> Deserialize<'de> for KeyedList<T> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_any(KeyedListVisitor(PhantomData))
+    }
+}
+
+struct KeyedListVisitor<T>(PhantomData<T>);
+
+impl<'de, T: Deserialize<'de>> Visitor<'de> for KeyedListVisitor<T> {
+    type Value = KeyedList<T>;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("a list of keyed values or a map from key to value")
+    }
+
+    fn visit_unit<E: de::Error>(self) -> Result<Self::Value, E> {
+        Ok(KeyedList(Vec::new()))
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+        let mut values: Vec<Keyed<T>> = Vec::with_capacity(seq.size_hint().unwrap_or(0));
+        while let Some(value) = seq.next_element()? {
+            values.push(value);
+        }
+        Ok(KeyedList(values))
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+        let mut values: Vec<Keyed<T>> = Vec::with_capacity(map.size_hint().unwrap_or(0));
+        while let Some((key, value)) = map.next_entry()? {
+            values.push(Keyed { key, value });
+        }
+        Ok(KeyedList(values))
+    }
+}
+
+/// Metadata the server returns for a pointer field instead of the value it points to.
+///
+/// # Fields
+/// - `keyspace: String` : The keyspace the pointer refers to.
+/// - `key: String` : The key the pointer refers to.
+/// - `extra: HashMap<String, serde_json::Value>` : Any other metadata the server sends.
+///
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+pub struct PointerMeta {
+    #[serde(default)]
+    pub keyspace: String,
+    #[serde(default)]
+    pub key: String,
+    #[serde(flatten)]
+    pub extra: HashMap<String, serde_json::Value>,
+}
+
+/// A value returned together with the metadata of its pointer fields, for reads made with
+/// `pointers_metadata`.
+///
+/// Deserializes from `{"value": ..., "pointers": {...}}`, or from the document itself with the
+/// metadata under `pointers` or `pointers_metadata`.
+///
+/// # Fields
+/// - `value: T` : The value.
+/// - `pointers: HashMap<String, PointerMeta>` : Pointer metadata, by field name.
+///
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct WithPointerMetadata<T> {
+    pub value: T,
+    pub pointers: HashMap<String, PointerMeta>,
+}
+
+impl<'de, T: DeserializeOwned> Deserialize<'de> for WithPointerMetadata<T> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_map(WithPointerMetadataVisitor(PhantomData))
+    }
+}
+
+struct WithPointerMetadataVisitor<T>(PhantomData<T>);
+
+impl<'de, T: DeserializeOwned> Visitor<'de> for WithPointerMetadataVisitor<T> {
+    type Value = WithPointerMetadata<T>;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("a value with pointer metadata")
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+        let mut value: Option<T> = None;
+        let mut pointers: HashMap<String, PointerMeta> = HashMap::new();
+        let mut document: serde_json::Map<String, serde_json::Value> = serde_json::Map::new();
+
+        while let Some(field) = map.next_key::<String>()? {
+            match field.as_str() {
+                "value" => value = Some(map.next_value()?),
+                "pointers" | "pointers_metadata" => pointers = map.next_value()?,
+                _ => {
+                    document.insert(field, map.next_value()?);
+                }
+            }
+        }
+
+        let value: T = match value {
+            Some(value) => value,
+            None => serde_json::from_value(serde_json::Value::Object(document))
+                .map_err(de::Error::custom)?,
+        };
+
+        Ok(WithPointerMetadata { value, pointers })
+    }
+}
+
+/// Payload shape of a typed read, selected by the options of `ReadOptions`.
+///
+/// `get_value_as`, `get_bulk_as` and `lookup_values_where_as` deserialize each value as `Value<T>`:
+///
+/// | Options                                  | Shape                  | `Value<T>`                       |
+/// |------------------------------------------|------------------------|----------------------------------|
+/// | none                                     | `Plain`                | `T`                              |
+/// | `key_included()`                         | `WithKey`              | `Keyed<T>`                       |
+/// | `pointers_metadata()`                    | `WithMetadata`         | `WithPointerMetadata<T>`         |
+/// | `key_included()` and `pointers_metadata()` | `WithKeyAndMetadata` | `Keyed<WithPointerMetadata<T>>`  |
+///
+pub trait ReadShape: Send + Sync + 'static {
+    /// One value as returned by the read.
+    type Value<T: DeserializeOwned + Send>: DeserializeOwned + Send;
+    /// The payload of a bulk read or lookup.
+    type List<T: DeserializeOwned + Send>: DeserializeOwned + Send;
+    /// The shape after also requesting `key_included`.
+    type KeyIncluded: ReadShape;
+    /// The shape after also requesting `pointers_metadata`.
+    type PointersMetadata: ReadShape;
+
+    /// Whether the shape needs `key_included` to be sent.
+    const KEY_INCLUDED: bool;
+    /// Whether the shape needs `pointers_metadata` to be sent.
+    const POINTERS_METADATA: bool;
+
+    /// Converts a bulk payload into its values.
+    fn into_values<T: DeserializeOwned + Send>(list: Self::List<T>) -> Vec<Self::Value<T>>;
+}
+
+/// Values as stored.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Plain;
+
+/// Values together with their keys.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct WithKey;
+
+/// Values together with their pointer metadata.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct WithMetadata;
+
+/// Values together with their keys and pointer metadata.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct WithKeyAndMetadata;
+
+impl ReadShape for Plain {
+    type Value<T: DeserializeOwned + Send> = T;
+    type List<T: DeserializeOwned + Send> = Vec<T>;
+    type KeyIncluded = WithKey;
+    type PointersMetadata = WithMetadata;
+
+    const KEY_INCLUDED: bool = false;
+    const POINTERS_METADATA: bool = false;
+
+    fn into_values<T: DeserializeOwned + Send>(list: Vec<T>) -> Vec<T> {
+        list
+    }
+}
+
+impl ReadShape for WithKey {
+    type Value<T: DeserializeOwned + Send> = Keyed<T>;
+    type List<T: DeserializeOwned + Send> = KeyedList<T>;
+    type KeyIncluded = WithKey;
+    type PointersMetadata = WithKeyAndMetadata;
+
+    const KEY_INCLUDED: bool = true;
+    const POINTERS_METADATA: bool = false;
+
+    fn into_values<T: DeserializeOwned + Send>(list: KeyedList<T>) -> Vec<Keyed<T>> {
+        list.0
+    }
+}
+
+impl ReadShape for WithMetadata {
+    type Value<T: DeserializeOwned + Send> = WithPointerMetadata<T>;
+    type List<T: DeserializeOwned + Send> = Vec<WithPointerMetadata<T>>;
+    type KeyIncluded = WithKeyAndMetadata;
+    type PointersMetadata = WithMetadata;
+
+    const KEY_INCLUDED: bool = false;
+    const POINTERS_METADATA: bool = true;
+
+    fn into_values<T: DeserializeOwned + Send>(
+        list: Vec<WithPointerMetadata<T>>,
+    ) -> Vec<WithPointerMetadata<T>> {
+        list
+    }
+}
+
+impl ReadShape for WithKeyAndMetadata {
+    type Value<T: DeserializeOwned + Send> = Keyed<WithPointerMetadata<T>>;
+    type List<T: DeserializeOwned + Send> = KeyedList<WithPointerMetadata<T>>;
+    type KeyIncluded = WithKeyAndMetadata;
+    type PointersMetadata = WithKeyAndMetadata;
+
+    const KEY_INCLUDED: bool = true;
+    const POINTERS_METADATA: bool = true;
+
+    fn into_values<T: DeserializeOwned + Send>(
+        list: KeyedList<WithPointerMetadata<T>>,
+    ) -> Vec<Keyed<WithPointerMetadata<T>>> {
+        list.0
+    }
+}
+
+/// Options of the typed reads `get_value_as`, `get_bulk_as` and `lookup_values_where_as`.
+///
+/// Requesting `key_included` or `pointers_metadata` changes the type the values are returned as,
+/// see `ReadShape`, so the payload layout never has to be guessed.
+///
+/// # Examples
+///
+/// ```rust, ignore
+/// let employees: Vec<Employee> = keyspace
+///     .get_bulk_as(Some(keys.clone()), None, ReadOptions::new(), None, None, None)
+///     .await?;
+///
+/// let employees: Vec<Keyed<Employee>> = keyspace
+///     .get_bulk_as(Some(keys), None, ReadOptions::new().key_included(), None, None, None)
+///     .await?;
+/// ```
+///
+#[derive(Debug, Clone, Copy)]
+pub struct ReadOptions<S: ReadShape = Plain> {
+    with_pointers: bool,
+    shape: PhantomData<S>,
+}
+
+impl ReadOptions {
+    /// Options that read the values as stored.
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl Default for ReadOptions {
+    fn default() -> Self {
+        Self {
+            with_pointers: false,
+            shape: PhantomData,
+        }
+    }
+}
+
+impl<S: ReadShape> ReadOptions<S> {
+    /// Replaces pointers with the values they point to.
+    pub fn with_pointers(self) -> Self {
+        Self {
+            with_pointers: true,
+            ..self
+        }
+    }
+
+    /// Returns every value together with its key, as `Keyed<T>`.
+    pub fn key_included(self) -> ReadOptions<S::KeyIncluded> {
+        ReadOptions {
+            with_pointers: self.with_pointers,
+            shape: PhantomData,
+        }
+    }
+
+    /// Returns every value together with the metadata of its pointers, as `WithPointerMetadata<T>`.
+    pub fn pointers_metadata(self) -> ReadOptions<S::PointersMetadata> {
+        ReadOptions {
+            with_pointers: self.with_pointers,
+            shape: PhantomData,
+        }
+    }
+
+    pub(crate) fn resolves_pointers(&self) -> bool {
+        self.with_pointers
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::response::structure::MontycatResponse;
+
+    #[derive(Debug, Clone, PartialEq, Deserialize)]
+    struct Employee {
+        id: u32,
+        name: String,
+    }
+
+    #[test]
+    fn test_keyed_accepts_both_layouts() {
+        let bytes = br#"{"status":true,"payload":[{"key":"1","value":"{\"id\":1,\"name\":\"a\"}"},{"2":{"id":2,"name":"b"}}],"error":null}"#;
+
+        let response: MontycatResponse<Vec<Keyed<Employee>>> =
+            MontycatResponse::from_slice(bytes).unwrap();
+
+        assert_eq!(response.payload[0].key, "1");
+        assert_eq!(response.payload[0].value.name, "a");
+        assert_eq!(response.payload[1].key, "2");
+        assert_eq!(response.payload[1].value.id, 2);
+    }
+
+    #[test]
+    fn test_keyed_list_accepts_a_map() {
+        let bytes = br#"{"status":true,"payload":{"7":"{\"id\":7,\"name\":\"c\"}"},"error":null}"#;
+
+        let response: MontycatResponse<KeyedList<Employee>> =
+            MontycatResponse::from_slice(bytes).unwrap();
+
+        assert_eq!(response.payload.0.len(), 1);
+        assert_eq!(response.payload.0[0].key, "7");
+        assert_eq!(response.payload.0[0].value.name, "c");
+    }
+
+    #[test]
+    fn test_pointer_metadata_is_split_from_the_value() {
+        let bytes = br#"{"status":true,"payload":"{\"id\":1,\"name\":\"a\",\"pointers\":{\"manager\":{\"keyspace\":\"staff\",\"key\":\"9\",\"exists\":true}}}","error":null}"#;
+
+        let response: MontycatResponse<WithPointerMetadata<Employee>> =
+            MontycatResponse::from_slice(bytes).unwrap();
+
+        let manager: &PointerMeta = &response.payload.pointers["manager"];
+        assert_eq!(response.payload.value.id, 1);
+        assert_eq!(
+            (manager.keyspace.as_str(), manager.key.as_str()),
+            ("staff", "9")
+        );
+        assert_eq!(manager.extra["exists"], serde_json::json!(true));
+    }
+
+    #[test]
+    fn test_read_options_select_the_shape() {
+        fn flags<S: ReadShape>(_: ReadOptions<S>) -> (bool, bool) {
+            (S::KEY_INCLUDED, S::POINTERS_METADATA)
+        }
+
+        assert_eq!(flags(ReadOptions::new()), (false, false));
+        assert_eq!(flags(ReadOptions::new().key_included()), (true, false));
+        assert_eq!(flags(ReadOptions::new().pointers_metadata()), (false, true));
+        assert_eq!(
+            flags(ReadOptions::new().pointers_metadata().key_included()),
+            (true, true)
+        );
+        assert!(
+            ReadOptions::new()
+                .with_pointers()
+                .key_included()
+                .resolves_pointers()
+        );
+
+        let bytes =
+            br#"{"status":true,"payload":{"1":{"id":1,"name":"a","pointers":{}}},"error":null}"#;
+        let response: MontycatResponse<<WithKeyAndMetadata as ReadShape>::List<Employee>> =
+            MontycatResponse::from_slice(bytes).unwrap();
+        let values = WithKeyAndMetadata::into_values(response.payload);
+
+        assert_eq!(values[0].key, "1");
+        assert_eq!(values[0].value.value.name, "a");
+    }
+}
@@ -0,0 +1,106 @@
+use crate::engine::{capabilities::ServerCapabilities, structure::Engine};
+use serde_json::{Value, json};
+use tokio::{
+    io::{AsyncBufReadExt, AsyncWriteExt, BufReader},
+    net::TcpListener,
+    task::JoinHandle,
+};
+
+/// Mock server for tests that go through a real connection.
+///
+/// The engine opens one connection per request, so the server accepts a fixed number of
+/// connections, reads one request line from each and answers it. The request lines are returned
+/// once every connection was served.
+pub(crate) struct MockServer {
+    port: u16,
+    server: JoinHandle<Vec<String>>,
+}
+
+impl MockServer {
+    /// Accepts `count` connections and writes what `answer` returns for each request line.
+    /// `None` closes the connection without answering.
+    pub(crate) async fn start<F>(count: usize, mut answer: F) -> Self
+    where
+        F: FnMut(&str) -> Option<Vec<u8>> + Send + 'static,
+    {
+        let listener: TcpListener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port: u16 = listener.local_addr().unwrap().port();
+
+        let server: JoinHandle<Vec<String>> = tokio::spawn(async move {
+            let mut requests: Vec<String> = Vec::new();
+            for _ in 0..count {
+                let (stream, _) = listener.accept().await.unwrap();
+                let mut reader = BufReader::new(stream);
+                let mut request: String = String::new();
+                reader.read_line(&mut request).await.unwrap();
+
+                if let Some(mut response) = answer(&request) {
+                    if !response.ends_with(b"\n") {
+                        response.push(b'\n');
+                    }
+                    reader.get_mut().write_all(&response).await.unwrap();
+                }
+
+                requests.push(request);
+            }
+            requests
+        });
+
+        Self { port, server }
+    }
+
+    /// Answers one connection per response, in order.
+    pub(crate) async fn responses<R>(responses: impl IntoIterator<Item = R>) -> Self
+    where
+        R: Into<Vec<u8>>,
+    {
+        let mut responses: Vec<Vec<u8>> = responses.into_iter().map(Into::into).collect();
+        responses.reverse();
+
+        Self::start(responses.len(), move |_| responses.pop()).await
+    }
+
+    /// Answers `count` connections with a successful response carrying the payload `respond`
+    /// returns for the request.
+    pub(crate) async fn payloads(count: usize, respond: fn(&Value) -> Value) -> Self {
+        Self::start(count, move |request| {
+            let request: Value = serde_json::from_str(request).unwrap();
+            let response: Value =
+                json!({ "status": true, "payload": respond(&request), "error": null });
+            Some(response.to_string().into_bytes())
+        })
+        .await
+    }
+
+    pub(crate) fn port(&self) -> u16 {
+        self.port
+    }
+
+    /// Returns an engine connected to the server, with the store `store`. The capabilities are
+    /// pinned, so the server only sees the requests of the test and no handshake.
+    pub(crate) fn engine(&self) -> Engine {
+        Engine::new(
+            "127.0.0.1".to_string(),
+            self.port,
+            "user".to_string(),
+            "pass".to_string(),
+            Some("store".to_string()),
+            false,
+        )
+        .with_server_capabilities(ServerCapabilities::unknown())
+    }
+
+    /// Waits for every connection to be served and returns the request lines.
+    pub(crate) async fn requests(self) -> Vec<String> {
+        self.server.await.unwrap()
+    }
+
+    /// Waits for every connection to be served and returns the parsed requests.
+    pub(crate) async fn json_requests(self) -> Vec<Value> {
+        self.requests()
+            .await
+            .iter()
+            .map(|request| serde_json::from_str(request).unwrap())
+            .collect()
+    }
+}
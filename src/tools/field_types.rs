@@ -0,0 +1,199 @@
+use std::{
+    collections::HashMap,
+    sync::{LazyLock, RwLock},
+};
+
+/// Schema type of a field, as sent to the server by `enforce_schema`.
+///
+/// # Variants
+/// - `String`, `Number`, `Boolean` : Scalar values.
+/// - `Array` : Lists, sets, tuples and arrays.
+/// - `Object` : Maps and JSON objects.
+/// - `Pointer` : A `Pointer` to a value in another keyspace.
+/// - `Timestamp` : A `Timestamp`.
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum FieldType {
+    String,
+    Number,
+    Boolean,
+    Array,
+    Object,
+    Pointer,
+    Timestamp,
+}
+
+impl FieldType {
+    /// Returns the name of the schema type.
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            FieldType::String => "String",
+            FieldType::Number => "Number",
+            FieldType::Boolean => "Boolean",
+            FieldType::Array => "Array",
+            FieldType::Object => "Object",
+            FieldType::Pointer => "Pointer",
+            FieldType::Timestamp => "Timestamp",
+        }
+    }
+}
+
+static FIELD_TYPES: LazyLock<RwLock<HashMap<String, FieldType>>> =
+    LazyLock::new(|| RwLock::new(HashMap::new()));
+
+/// Maps a Rust type name to a schema type for `enforce_schema`.
+///
+/// Registered types take precedence over the built-in mapping. A name without a path (`Money`)
+/// matches the type under any path (`billing::Money`); a name with a path only matches that path.
+/// Generic types are matched by their name without parameters (`Tagged` matches `Tagged<u32>`).
+///
+/// # Arguments
+/// - `type_name: &str` : The Rust type name, as written in the struct.
+/// - `field_type: FieldType` : The schema type to send for it.
+///
+/// # Examples
+///
+/// ```rust, ignore
+/// register_field_type("Money", FieldType::Number);
+///
+/// #[derive(Serialize, Deserialize, RuntimeSchema)]
+/// struct Invoice {
+///     total: Money,
+/// }
+///
+/// keyspace.enforce_schema(Invoice::schema_params()).await?;
+/// ```
+///
+pub fn register_field_type(type_name: &str, field_type: FieldType) {
+    FIELD_TYPES
+        .write()
+        .unwrap_or_else(|e| e.into_inner())
+        .insert(type_name.replace(' ', ""), field_type);
+}
+
+/// Removes a mapping added with `register_field_type`.
+///
+/// # Returns
+/// - `Option<FieldType>` : The schema type that was registered, if any.
+///
+pub fn unregister_field_type(type_name: &str) -> Option<FieldType> {
+    FIELD_TYPES
+        .write()
+        .unwrap_or_else(|e| e.into_inner())
+        .remove(&type_name.replace(' ', ""))
+}
+
+/// Looks up a registered type by its full name, then by its name without path and parameters.
+pub(crate) fn registered_field_type(type_name: &str, base_name: &str) -> Option<FieldType> {
+    let field_types = FIELD_TYPES.read().unwrap_or_else(|e| e.into_inner());
+
+    if field_types.is_empty() {
+        return None;
+    }
+
+    field_types
+        .get(type_name)
+        .or_else(|| field_types.get(base_name))
+        .copied()
+}
+
+/// Schema types of well-known types from other crates, enabled by the `types-*` cargo features.
+pub(crate) fn ecosystem_field_type(base_name: &str) -> Option<FieldType> {
+    #[cfg(feature = "types-uuid")]
+    if base_name == "Uuid" {
+        return Some(FieldType::String);
+    }
+
+    #[cfg(feature = "types-chrono")]
+    if matches!(
+        base_name,
+        "DateTime" | "NaiveDateTime" | "NaiveDate" | "NaiveTime"
+    ) {
+        return Some(FieldType::String);
+    }
+
+    #[cfg(feature = "types-decimal")]
+    if base_name == "Decimal" {
+        return Some(FieldType::String);
+    }
+
+    let _ = base_name;
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tools::functions::define_type;
+
+    #[test]
+    fn test_define_type_maps_std_and_serde_json_types() {
+        let cases: [(&str, &str, bool); 12] = [
+            ("VecDeque<u8>", "Array", false),
+            ("std::collections::LinkedList<String>", "Array", false),
+            ("[u8; 32]", "Array", false),
+            ("(String, i64)", "Array", false),
+            ("&'static str", "String", false),
+            ("& 'a [u8]", "Array", false),
+            ("Box<Option<f64>>", "Number", true),
+            ("Option<Arc<Vec<u8>>>", "Array", true),
+            ("Cow<'a, str>", "String", false),
+            ("std::num::NonZeroU64", "Number", false),
+            ("serde_json::Value", "Object", false),
+            ("Map<String, Value>", "Object", false),
+        ];
+
+        for (field_type, expected, nullable) in cases {
+            assert_eq!(
+                define_type(field_type).unwrap(),
+                (expected, nullable),
+                "{}",
+                field_type
+            );
+        }
+
+        assert!(define_type("()").is_err());
+        assert!(define_type("Vec").is_err());
+    }
+
+    #[test]
+    fn test_registered_types_take_precedence() {
+        assert!(define_type("Money").is_err());
+
+        register_field_type("Money", FieldType::Number);
+        register_field_type("geo::Point", FieldType::Object);
+        register_field_type("Number", FieldType::String);
+
+        assert_eq!(define_type("billing::Money").unwrap(), ("Number", false));
+        assert_eq!(define_type("Option<Money>").unwrap(), ("Number", true));
+        assert_eq!(define_type("geo::Point").unwrap(), ("Object", false));
+        assert!(define_type("other::Point").is_err());
+        assert_eq!(
+            define_type("serde_json::Number").unwrap(),
+            ("String", false)
+        );
+
+        assert_eq!(unregister_field_type("Money"), Some(FieldType::Number));
+        unregister_field_type("geo::Point");
+        unregister_field_type("Number");
+
+        assert!(define_type("Money").is_err());
+        assert_eq!(define_type("Number").unwrap(), ("Number", false));
+    }
+
+    #[test]
+    fn test_ecosystem_types_follow_features() {
+        assert_eq!(
+            define_type("uuid::Uuid").is_ok(),
+            cfg!(feature = "types-uuid")
+        );
+        assert_eq!(
+            define_type("DateTime<Utc>").is_ok(),
+            cfg!(feature = "types-chrono")
+        );
+        assert_eq!(
+            define_type("rust_decimal::Decimal").is_ok(),
+            cfg!(feature = "types-decimal")
+        );
+    }
+}
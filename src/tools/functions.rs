@@ -1,5 +1,6 @@
 use crate::errors::MontycatClientError;
 use crate::request::utis::functions::is_custom_type;
+use crate::tools::field_types::{FieldType, ecosystem_field_type, registered_field_type};
 use crate::traits::RuntimeSchema;
 use rayon::prelude::*;
 use serde::Serialize;
@@ -27,159 +28,321 @@ where
 
 /// Processes a value into a JSON string, handling special fields for pointers and timestamps.
 ///
+/// Fields holding a `Pointer` or `Timestamp`, directly or inside `Option`, lists and maps, are moved
+/// into the `pointers` and `timestamps` sections, keeping their shape:
+/// - `Pointer` : `[keyspace, key]`.
+/// - `Timestamp` : The timestamp string, or null if it is not set.
+/// - Lists and maps : A list or map of the encoded elements. `None` elements are null.
+///
+/// Fields that are `None` stay in the value as null. Typed reads move the sections back.
+///
 /// # Arguments
 /// - `value: T` : The value to be processed.
 ///
 /// # Returns
 /// - `Result<String, MontycatClientError>` : The processed JSON string or an error if processing fails.
 ///
+/// # Errors
+/// - `MontycatClientError::ClientNoValidInputProvided` : If a pointer or timestamp field does not
+///   serialize like `Pointer` or `Timestamp`.
+///
 pub(crate) fn process_value<T>(value: T) -> Result<String, MontycatClientError>
 where
     T: Serialize + RuntimeSchema,
 {
-    let pointer_and_timestamp_fields: Vec<(&'static str, &'static str)> =
-        value.pointer_and_timestamp_fields();
-    let mut val_as_map: Map<String, Value> = Map::new();
-
-    if !pointer_and_timestamp_fields.is_empty() {
-        let mut pointers: Map<String, Value> = Map::new();
-        let mut timestamps: Map<String, Value> = Map::new();
-
-        let val_as_serde = serde_json::to_value(&value)
-            .map_err(|e| MontycatClientError::ClientValueParsingError(e.to_string()))?;
+    let special_fields: Vec<(&'static str, &'static str, SpecialField)> = value
+        .field_names_and_types()
+        .into_iter()
+        .filter_map(|(field_name, field_type)| {
+            special_field(field_type).map(|special| (field_name, field_type, special))
+        })
+        .collect();
+
+    if special_fields.is_empty() {
+        return simd_json::to_string(&value)
+            .map_err(|e| MontycatClientError::ClientValueParsingError(e.to_string()));
+    }
 
-        if let Some(obj) = val_as_serde.as_object() {
-            val_as_map = obj.to_owned();
-        }
+    let mut val_as_map: Map<String, Value> = match serde_json::to_value(&value)
+        .map_err(|e| MontycatClientError::ClientValueParsingError(e.to_string()))?
+    {
+        Value::Object(obj) => obj,
+        _ => return Err(MontycatClientError::ClientNoValidInputProvided),
+    };
 
-        let mut removal: Vec<&str> = Vec::new();
-
-        for (field_name, field_type) in pointer_and_timestamp_fields {
-            if let Some(field_value) = val_as_map.get(field_name) {
-                if field_type == "Pointer" {
-                    let pointing_key = field_value.get("key");
-                    let pointing_keyspace = field_value.get("keyspace");
-
-                    match (pointing_key, pointing_keyspace) {
-                        (Some(key), Some(keyspace)) => {
-                            let content: Value = serde_json::json!([keyspace, key]);
-
-                            pointers.insert(field_name.to_string(), content);
-                        }
-                        _ => {
-                            return Err(MontycatClientError::ClientNoValidInputProvided);
-                        }
-                    }
-
-                    removal.push(field_name);
-                } else if field_type == "Timestamp" {
-                    let timestamp_value: &Value = field_value
-                        .get("timestamp")
-                        .ok_or(MontycatClientError::ClientNoValidInputProvided)?;
-
-                    timestamps.insert(field_name.to_string(), timestamp_value.clone());
-                    removal.push(field_name);
-                }
+    let mut pointers: Map<String, Value> = Map::new();
+    let mut timestamps: Map<String, Value> = Map::new();
+
+    for (field_name, field_type, special) in special_fields {
+        match val_as_map.get(field_name) {
+            Some(Value::Null) | None => continue,
+            Some(field_value) => {
+                let encoded: Value = encode_special(field_type, field_value)?;
+                match special {
+                    SpecialField::Pointer => pointers.insert(field_name.to_string(), encoded),
+                    SpecialField::Timestamp => timestamps.insert(field_name.to_string(), encoded),
+                };
             }
         }
 
-        for field in removal {
-            val_as_map.remove(field);
-        }
+        val_as_map.remove(field_name);
+    }
 
-        if !pointers.is_empty() {
-            val_as_map.insert("pointers".into(), pointers.into());
-        }
+    if !pointers.is_empty() {
+        val_as_map.insert("pointers".into(), pointers.into());
+    }
 
-        if !timestamps.is_empty() {
-            val_as_map.insert("timestamps".into(), timestamps.into());
-        }
+    if !timestamps.is_empty() {
+        val_as_map.insert("timestamps".into(), timestamps.into());
     }
 
-    let value_to_send: String = {
-        if val_as_map.is_empty() {
-            simd_json::to_string(&value)
-                .map_err(|e| MontycatClientError::ClientValueParsingError(e.to_string()))?
-        } else {
-            simd_json::to_string(&val_as_map)
-                .map_err(|e| MontycatClientError::ClientValueParsingError(e.to_string()))?
-        }
-    };
+    simd_json::to_string(&val_as_map)
+        .map_err(|e| MontycatClientError::ClientValueParsingError(e.to_string()))
+}
 
-    Ok(value_to_send)
+/// Section a field is stored in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SpecialField {
+    Pointer,
+    Timestamp,
+}
+
+/// Returns the section of a field type that holds pointers or timestamps, possibly nested in
+/// `Option`, wrappers, lists and maps.
+fn special_field(field_type: &str) -> Option<SpecialField> {
+    let TypeName { base, generics, .. } = parse_type_name(field_type).ok()?;
+
+    match (base.as_str(), generics.as_deref()) {
+        ("Pointer", _) => Some(SpecialField::Pointer),
+        ("Timestamp", None) => Some(SpecialField::Timestamp),
+        ("Option", Some(inner)) => special_field(inner),
+        (s, Some(inner)) if is_wrapper(s) || is_map(s) => special_field(last_generic(inner)),
+        (s, Some(inner)) if is_list(s) => special_field(inner),
+        _ => None,
+    }
+}
+
+/// Encodes a serialized pointer or timestamp field into its section form, following its type.
+fn encode_special(field_type: &str, value: &Value) -> Result<Value, MontycatClientError> {
+    let TypeName { base, generics, .. } = parse_type_name(field_type)?;
+
+    match (base.as_str(), generics.as_deref(), value) {
+        (_, _, Value::Null) => Ok(Value::Null),
+        ("Pointer", _, _) => match (value.get("keyspace"), value.get("key")) {
+            (Some(keyspace), Some(key)) => Ok(serde_json::json!([keyspace, key])),
+            _ => Err(MontycatClientError::ClientNoValidInputProvided),
+        },
+        ("Timestamp", None, _) => value
+            .get("timestamp")
+            .cloned()
+            .ok_or(MontycatClientError::ClientNoValidInputProvided),
+        ("Option", Some(inner), _) => encode_special(inner, value),
+        (s, Some(inner), _) if is_wrapper(s) => encode_special(last_generic(inner), value),
+        (s, Some(inner), Value::Array(items)) if is_list(s) => items
+            .iter()
+            .map(|item| encode_special(inner, item))
+            .collect::<Result<Vec<Value>, MontycatClientError>>()
+            .map(Value::Array),
+        (s, Some(inner), Value::Object(entries)) if is_map(s) => entries
+            .iter()
+            .map(|(key, entry)| Ok((key.clone(), encode_special(last_generic(inner), entry)?)))
+            .collect::<Result<Map<String, Value>, MontycatClientError>>()
+            .map(Value::Object),
+        _ => Err(MontycatClientError::ClientNoValidInputProvided),
+    }
 }
 
 /// Determines the Montycat field type for a given Rust type name.
 ///
+/// Types registered with `register_field_type` are checked first. Then come the built-in types:
+/// primitives, std collections, tuples and arrays, smart pointers (mapped to the type they wrap),
+/// `serde_json` values, `Pointer`, `Timestamp`, and the types of the enabled `types-*` features.
+///
 /// # Arguments
 /// * `field_type: &str` - The name of the Rust type.
 ///
 /// # Returns
-/// * `Result<&'static str, MontycatClientError>` - The corresponding Montycat field type or an error if the type is unsupported.
+/// * `Result<(&'static str, bool), MontycatClientError>` - The corresponding Montycat field type and whether it is nullable, or an error if the type is unsupported.
 ///
 pub(crate) fn define_type(field_type: &str) -> Result<(&'static str, bool), MontycatClientError> {
-    let cleaned = field_type.replace(' ', "");
+    let unsupported = || MontycatClientError::ClientUnsupportedFieldType(field_type.to_owned());
+    let TypeName {
+        full,
+        base,
+        generics,
+    } = parse_type_name(field_type)?;
+
+    if let Some(registered) = registered_field_type(&full, &base) {
+        return Ok((registered.as_str(), false));
+    }
 
-    let mut nullable = false;
+    // Arrays, slices and tuples.
+    if is_sequence_literal(&full) {
+        return match full.as_str() {
+            "()" => Err(unsupported()),
+            _ => Ok((FieldType::Array.as_str(), false)),
+        };
+    }
 
-    // Detect Option<T>
-    if let Some(inner) = cleaned
-        .strip_prefix("Option<")
-        .and_then(|s| s.strip_suffix('>'))
-    {
-        let inner_type = define_type(inner)?;
-        nullable = true;
-        return Ok((inner_type.0, nullable));
+    if let Some(inner) = generics.as_deref() {
+        match base.as_str() {
+            // Detect Option<T>
+            "Option" => return define_type(inner).map(|(inner_type, _)| (inner_type, true)),
+
+            // Wrappers serialize as the value they hold. `Cow<'a, str>` holds the last parameter.
+            s if is_wrapper(s) => return define_type(last_generic(inner)),
+            _ => {}
+        }
     }
 
-    let ty = match cleaned.as_str() {
+    let field: FieldType = match base.as_str() {
         // Strings
-        "String" | "&str" | "char" => "String",
+        "String" | "str" | "char" => FieldType::String,
 
         // Numbers
         "i8" | "i16" | "i32" | "i64" | "i128" | "u8" | "u16" | "u32" | "u64" | "u128" | "isize"
-        | "usize" | "f32" | "f64" => "Number",
+        | "usize" | "f32" | "f64" | "Number" => FieldType::Number,
+        s if s.starts_with("NonZero") => FieldType::Number,
 
         // Boolean
-        "bool" => "Boolean",
+        "bool" => FieldType::Boolean,
 
         // Collections
-        s if s.starts_with("Vec<") => "Array",
-        s if s.starts_with("HashSet<") => "Array",
-        s if s.starts_with("BTreeSet<") => "Array",
+        s if is_list(s) && generics.is_some() => FieldType::Array,
+        s if is_map(s) && generics.is_some() => FieldType::Object,
 
-        s if s.starts_with("HashMap<") => "Object",
-        s if s.starts_with("BTreeMap<") => "Object",
+        // serde_json values; register another type for `Value` to narrow it down.
+        "Value" | "Map" => FieldType::Object,
 
         // Custom types
-        "Pointer" => "Pointer",
-        "Timestamp" => "Timestamp",
+        "Pointer" => FieldType::Pointer,
+        "Timestamp" => FieldType::Timestamp,
+
+        _ => ecosystem_field_type(&base).ok_or_else(unsupported)?,
+    };
+
+    Ok((field.as_str(), false))
+}
+
+/// A Rust type name without references, lifetimes and spaces.
+pub(crate) struct TypeName {
+    /// The type with its path and generic parameters, e.g. `std::collections::HashMap<String,u32>`.
+    pub full: String,
+    /// The last path segment without generic parameters, e.g. `HashMap`.
+    pub base: String,
+    /// The generic parameters, e.g. `String,u32`.
+    pub generics: Option<String>,
+}
 
-        _ => {
-            return Err(MontycatClientError::ClientUnsupportedFieldType(
-                field_type.to_owned(),
-            ));
+/// Parses a type name as written in a struct (`stringify!` output included).
+///
+/// # Errors
+/// * `MontycatClientError::ClientUnsupportedFieldType` - If the generic parameters are not closed.
+///
+pub(crate) fn parse_type_name(field_type: &str) -> Result<TypeName, MontycatClientError> {
+    let unsupported = || MontycatClientError::ClientUnsupportedFieldType(field_type.to_owned());
+
+    // References and their lifetimes: `&'a str`, `&mut [u8]`.
+    let mut ty: &str = field_type.trim();
+    if let Some(referenced) = ty.strip_prefix('&') {
+        ty = referenced.trim_start();
+        if ty.starts_with('\'') {
+            ty = ty
+                .split_once(char::is_whitespace)
+                .ok_or_else(unsupported)?
+                .1;
         }
+        ty = ty.strip_prefix("mut ").unwrap_or(ty);
+    }
+
+    let full: String = ty.replace(' ', "");
+
+    let (path, generics): (&str, Option<&str>) = match full.find('<') {
+        Some(open) if !is_sequence_literal(&full) => (
+            &full[..open],
+            Some(full[open + 1..].strip_suffix('>').ok_or_else(unsupported)?),
+        ),
+        _ => (full.as_str(), None),
     };
 
-    Ok((ty, nullable))
+    Ok(TypeName {
+        base: path.rsplit("::").next().unwrap_or(path).to_owned(),
+        generics: generics.map(str::to_owned),
+        full,
+    })
+}
+
+/// Splits generic parameters or tuple elements at their top-level commas.
+pub(crate) fn split_generics(params: &str) -> Vec<&str> {
+    let mut parts: Vec<&str> = Vec::new();
+    let mut depth: i32 = 0;
+    let mut start: usize = 0;
+
+    for (i, c) in params.char_indices() {
+        match c {
+            '<' | '(' | '[' => depth += 1,
+            '>' | ')' | ']' => depth -= 1,
+            ',' if depth == 0 => {
+                parts.push(&params[start..i]);
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+
+    if start < params.len() {
+        parts.push(&params[start..]);
+    }
+
+    parts
+}
+
+/// Returns the last generic parameter, the value held by `Cow<'a, T>`-style wrappers.
+pub(crate) fn last_generic(params: &str) -> &str {
+    split_generics(params).pop().unwrap_or(params)
+}
+
+/// Arrays `[T; N]`, slices `[T]` and tuples `(A, B)`.
+pub(crate) fn is_sequence_literal(ty: &str) -> bool {
+    (ty.starts_with('[') && ty.ends_with(']')) || (ty.starts_with('(') && ty.ends_with(')'))
+}
+
+/// Types that serialize as the value they hold.
+pub(crate) fn is_wrapper(base: &str) -> bool {
+    matches!(
+        base,
+        "Box" | "Rc" | "Arc" | "Cow" | "Reverse" | "Wrapping" | "Saturating"
+    )
+}
+
+/// Generic collections that serialize as a JSON array.
+pub(crate) fn is_list(base: &str) -> bool {
+    matches!(
+        base,
+        "Vec" | "VecDeque" | "LinkedList" | "BinaryHeap" | "HashSet" | "BTreeSet" | "IndexSet"
+    )
+}
+
+/// Generic collections that serialize as a JSON object.
+pub(crate) fn is_map(base: &str) -> bool {
+    matches!(base, "HashMap" | "BTreeMap" | "IndexMap")
 }
 
-/// Processes a bulk of JSON-serializable values into a single JSON string and optional schema.
+/// Serializes a bulk of values one by one and determines their common schema.
 ///
 /// # Arguments
 /// - `values: Vec<T>` : A vector of values to be processed.
 ///
 /// # Returns
-/// - `Result<(String, Option<String>), MontycatClientError>` : A result containing the processed JSON string and an optional schema, or an error if processing fails.
+/// - `Result<(Vec<String>, Option<String>), MontycatClientError>` : A result containing the serialized values and an optional schema, or an error if processing fails.
 ///
-pub(crate) async fn process_bulk_values<T>(
+pub(crate) async fn serialize_bulk_values<T>(
     values: Vec<T>,
-) -> Result<(String, Option<String>), MontycatClientError>
+) -> Result<(Vec<String>, Option<String>), MontycatClientError>
 where
     T: Serialize + RuntimeSchema + Send + 'static,
 {
-    let res: (String, Option<String>) = tokio::task::spawn_blocking(move || {
+    let res: (Vec<String>, Option<String>) = tokio::task::spawn_blocking(move || {
         let serialized_and_schemas: Result<Vec<(String, Option<String>)>, MontycatClientError> =
             values
                 .into_par_iter()
@@ -208,9 +371,7 @@ where
             _ => return Err(MontycatClientError::ClientMultipleSchemasFound),
         };
 
-        let value_to_send: String = process_json_value(&serialized_values)?;
-
-        Ok((value_to_send, schema))
+        Ok((serialized_values, schema))
     })
     .await
     .map_err(|e| MontycatClientError::ClientAsyncRuntimeError(e.to_string()))??;
@@ -1,2 +1,4 @@
+pub mod field_types;
 pub mod functions;
+pub mod schema;
 pub mod structure;
@@ -0,0 +1,568 @@
+use crate::{
+    errors::MontycatClientError,
+    tools::{
+        field_types::{FieldType, registered_field_type},
+        functions::{
+            TypeName, define_type, is_list, is_map, is_sequence_literal, is_wrapper, last_generic,
+            parse_type_name, split_generics,
+        },
+    },
+    traits::RuntimeSchema,
+};
+use serde::{Serialize, Serializer, ser::SerializeTuple};
+use serde_json::{Map, Value, json};
+use std::{
+    collections::HashMap,
+    sync::{LazyLock, RwLock},
+};
+
+/// Schema type sent for fields holding a registered enum.
+pub const ENUM_FIELD_TYPE: &str = "Enum";
+
+/// How an enum is represented in JSON, mirroring serde's enum attributes.
+///
+/// # Variants
+/// - `External` : The default, `{"Variant": content}`; unit variants are a plain string.
+/// - `Internal { tag }` : `#[serde(tag = "...")]`, `{"tag": "Variant", ...fields}`.
+/// - `Adjacent { tag, content }` : `#[serde(tag = "...", content = "...")]`.
+/// - `Untagged` : `#[serde(untagged)]`, the content alone.
+///
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum EnumTagging {
+    #[default]
+    External,
+    Internal {
+        tag: &'static str,
+    },
+    Adjacent {
+        tag: &'static str,
+        content: &'static str,
+    },
+    Untagged,
+}
+
+/// Shape of one enum variant. Types are written as in the enum definition.
+///
+/// # Variants
+/// - `Unit` : `Variant`.
+/// - `Newtype(ty)` : `Variant(T)`.
+/// - `Tuple(types)` : `Variant(A, B)`.
+/// - `Struct(fields)` : `Variant { name: T }`.
+///
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum VariantShape {
+    Unit,
+    Newtype(&'static str),
+    Tuple(Vec<&'static str>),
+    Struct(Vec<(&'static str, &'static str)>),
+}
+
+/// Description of a serde-style enum, so fields holding it can be validated by the server.
+///
+/// `RuntimeSchema` cannot be derived for enums, so their variants are listed with this builder and
+/// registered with `register_enum_schema`.
+///
+/// # Examples
+///
+/// ```rust, ignore
+/// #[derive(Serialize, Deserialize)]
+/// #[serde(tag = "type")]
+/// enum Shape {
+///     Empty,
+///     Circle { radius: f64 },
+///     Polygon { points: Vec<Point> },
+/// }
+///
+/// register_schema::<Point>();
+/// register_enum_schema(
+///     EnumSchema::new("Shape")
+///         .with_tagging(EnumTagging::Internal { tag: "type" })
+///         .unit("Empty")
+///         .structure("Circle", &[("radius", "f64")])
+///         .structure("Polygon", &[("points", "Vec<Point>")]),
+/// );
+/// ```
+///
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EnumSchema {
+    name: &'static str,
+    tagging: EnumTagging,
+    variants: Vec<(&'static str, VariantShape)>,
+}
+
+impl EnumSchema {
+    /// Creates an externally tagged enum schema without variants.
+    pub fn new(name: &'static str) -> Self {
+        Self {
+            name,
+            tagging: EnumTagging::default(),
+            variants: Vec::new(),
+        }
+    }
+
+    /// Sets how the enum is tagged.
+    pub fn with_tagging(mut self, tagging: EnumTagging) -> Self {
+        self.tagging = tagging;
+        self
+    }
+
+    /// Adds a variant.
+    pub fn variant(mut self, name: &'static str, shape: VariantShape) -> Self {
+        self.variants.push((name, shape));
+        self
+    }
+
+    /// Adds a unit variant.
+    pub fn unit(self, name: &'static str) -> Self {
+        self.variant(name, VariantShape::Unit)
+    }
+
+    /// Adds a newtype variant holding `ty`.
+    pub fn newtype(self, name: &'static str, ty: &'static str) -> Self {
+        self.variant(name, VariantShape::Newtype(ty))
+    }
+
+    /// Adds a tuple variant holding `types`.
+    pub fn tuple(self, name: &'static str, types: &[&'static str]) -> Self {
+        self.variant(name, VariantShape::Tuple(types.to_vec()))
+    }
+
+    /// Adds a struct variant with named `fields`.
+    pub fn structure(self, name: &'static str, fields: &[(&'static str, &'static str)]) -> Self {
+        self.variant(name, VariantShape::Struct(fields.to_vec()))
+    }
+
+    /// Returns the name of the enum.
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+}
+
+#[derive(Debug, Clone)]
+enum NestedSchema {
+    Struct(HashMap<&'static str, &'static str>),
+    Enum(EnumSchema),
+}
+
+static SCHEMAS: LazyLock<RwLock<HashMap<String, NestedSchema>>> =
+    LazyLock::new(|| RwLock::new(HashMap::new()));
+
+/// Registers a `RuntimeSchema` struct, so fields of that type are described by its own fields.
+///
+/// # Examples
+///
+/// ```rust, ignore
+/// #[derive(Serialize, Deserialize, RuntimeSchema)]
+/// struct Address {
+///     city: String,
+/// }
+///
+/// #[derive(Serialize, Deserialize, RuntimeSchema)]
+/// struct Customer {
+///     address: Address,
+///     previous: Vec<Address>,
+/// }
+///
+/// register_schema::<Address>();
+/// keyspace.enforce_schema(Customer::schema_params()).await?;
+/// ```
+///
+pub fn register_schema<T: RuntimeSchema>() {
+    let (fields, name) = T::schema_params();
+
+    SCHEMAS
+        .write()
+        .unwrap_or_else(|e| e.into_inner())
+        .insert(name.to_owned(), NestedSchema::Struct(fields));
+}
+
+/// Registers an enum, so fields of that type are described by its variants.
+pub fn register_enum_schema(schema: EnumSchema) {
+    SCHEMAS
+        .write()
+        .unwrap_or_else(|e| e.into_inner())
+        .insert(schema.name.to_owned(), NestedSchema::Enum(schema));
+}
+
+/// Removes a struct or enum registered with `register_schema` or `register_enum_schema`.
+///
+/// # Returns
+/// - `bool` : Whether a schema was registered under the name.
+///
+pub fn unregister_schema(name: &str) -> bool {
+    SCHEMAS
+        .write()
+        .unwrap_or_else(|e| e.into_inner())
+        .remove(name)
+        .is_some()
+}
+
+fn registered_schema(base_name: &str) -> Option<NestedSchema> {
+    SCHEMAS
+        .read()
+        .unwrap_or_else(|e| e.into_inner())
+        .get(base_name)
+        .cloned()
+}
+
+/// Schema definition of one field, serialized as `[type, nullable]` or, for fields with an inner
+/// shape, `[type, nullable, shape]`. The shaped format is only sent to servers advertising
+/// `ServerFeature::NestedSchemas`; other servers get the flat format from `flatten`.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct FieldSchema {
+    field_type: &'static str,
+    nullable: bool,
+    shape: Option<Value>,
+}
+
+impl Serialize for FieldSchema {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut tuple = serializer.serialize_tuple(2 + usize::from(self.shape.is_some()))?;
+        tuple.serialize_element(self.field_type)?;
+        tuple.serialize_element(&self.nullable)?;
+        if let Some(shape) = &self.shape {
+            tuple.serialize_element(shape)?;
+        }
+        tuple.end()
+    }
+}
+
+impl FieldSchema {
+    fn flat(field_type: &'static str) -> Self {
+        Self {
+            field_type,
+            nullable: false,
+            shape: None,
+        }
+    }
+
+    fn shaped(field_type: FieldType, shape: Value) -> Self {
+        Self {
+            field_type: field_type.as_str(),
+            nullable: false,
+            shape: Some(shape),
+        }
+    }
+
+    /// Returns true if the field holds an enum registered with `register_enum_schema`.
+    pub(crate) fn is_enum(&self) -> bool {
+        self.field_type == ENUM_FIELD_TYPE
+    }
+
+    /// Drops the inner shape, so the field is sent as `[type, nullable]`.
+    pub(crate) fn flatten(&mut self) {
+        self.shape = None;
+    }
+
+    fn to_value(&self) -> Result<Value, MontycatClientError> {
+        serde_json::to_value(self)
+            .map_err(|e| MontycatClientError::ClientValueParsingError(e.to_string()))
+    }
+}
+
+/// Describes the fields of a schema, including the shapes of nested structs, enums and collections.
+///
+/// # Arguments
+/// - `fields: HashMap<&str, &str>` : Field names and their Rust types, as in `schema_params`.
+///
+/// # Returns
+/// - `Result<HashMap<String, FieldSchema>, MontycatClientError>` : The definition of each field.
+///
+/// # Errors
+/// - `MontycatClientError::ClientUnsupportedFieldType` : If a field type is neither built in nor
+///   registered.
+///
+pub(crate) fn describe_fields(
+    fields: HashMap<&str, &str>,
+) -> Result<HashMap<String, FieldSchema>, MontycatClientError> {
+    let mut visiting: Vec<String> = Vec::new();
+
+    fields
+        .into_iter()
+        .map(|(name, ty)| Ok((name.to_owned(), describe_type(ty, &mut visiting)?)))
+        .collect()
+}
+
+fn describe_type(
+    field_type: &str,
+    visiting: &mut Vec<String>,
+) -> Result<FieldSchema, MontycatClientError> {
+    let TypeName {
+        full,
+        base,
+        generics,
+    } = parse_type_name(field_type)?;
+
+    // Registered field types are leaves, whatever they hold.
+    if registered_field_type(&full, &base).is_some() {
+        return define_type(field_type).map(|(ty, nullable)| FieldSchema {
+            nullable,
+            ..FieldSchema::flat(ty)
+        });
+    }
+
+    if let Some(schema) = registered_schema(&base) {
+        return describe_nested(&base, schema, visiting);
+    }
+
+    if is_sequence_literal(&full) && full != "()" {
+        let inner: &str = &full[1..full.len() - 1];
+
+        let items: Value = if full.starts_with('[') {
+            // `[T; N]` and `[T]`
+            let item: &str = inner.split_once(';').map_or(inner, |(item, _)| item);
+            describe_type(item, visiting)?.to_value()?
+        } else {
+            describe_all(&split_generics(inner), visiting)?
+        };
+
+        return Ok(FieldSchema::shaped(
+            FieldType::Array,
+            json!({ "items": items }),
+        ));
+    }
+
+    if let Some(inner) = generics.as_deref() {
+        match base.as_str() {
+            "Option" => {
+                return describe_type(inner, visiting).map(|inner| FieldSchema {
+                    nullable: true,
+                    ..inner
+                });
+            }
+            s if is_wrapper(s) => return describe_type(last_generic(inner), visiting),
+            s if is_list(s) => {
+                let items: Value = describe_type(inner, visiting)?.to_value()?;
+                return Ok(FieldSchema::shaped(
+                    FieldType::Array,
+                    json!({ "items": items }),
+                ));
+            }
+            s if is_map(s) => {
+                if let [key, value] = split_generics(inner)[..] {
+                    let keys: Value = describe_type(key, visiting)?.to_value()?;
+                    let values: Value = describe_type(value, visiting)?.to_value()?;
+                    return Ok(FieldSchema::shaped(
+                        FieldType::Object,
+                        json!({ "keys": keys, "values": values }),
+                    ));
+                }
+            }
+            _ => {}
+        }
+    }
+
+    define_type(field_type).map(|(ty, nullable)| FieldSchema {
+        nullable,
+        ..FieldSchema::flat(ty)
+    })
+}
+
+fn describe_all(types: &[&str], visiting: &mut Vec<String>) -> Result<Value, MontycatClientError> {
+    types
+        .iter()
+        .map(|ty| describe_type(ty, visiting)?.to_value())
+        .collect::<Result<Vec<Value>, MontycatClientError>>()
+        .map(Value::Array)
+}
+
+fn describe_named(
+    fields: &[(&str, &str)],
+    visiting: &mut Vec<String>,
+) -> Result<Value, MontycatClientError> {
+    fields
+        .iter()
+        .map(|(name, ty)| Ok(((*name).to_owned(), describe_type(ty, visiting)?.to_value()?)))
+        .collect::<Result<Map<String, Value>, MontycatClientError>>()
+        .map(Value::Object)
+}
+
+fn describe_nested(
+    name: &str,
+    schema: NestedSchema,
+    visiting: &mut Vec<String>,
+) -> Result<FieldSchema, MontycatClientError> {
+    let field_type: &'static str = match schema {
+        NestedSchema::Struct(_) => FieldType::Object.as_str(),
+        NestedSchema::Enum(_) => ENUM_FIELD_TYPE,
+    };
+
+    // Recursive types reference the enclosing definition by name instead of repeating it.
+    if visiting.iter().any(|visited| visited == name) {
+        return Ok(FieldSchema {
+            shape: Some(json!({ "schema": name })),
+            ..FieldSchema::flat(field_type)
+        });
+    }
+
+    visiting.push(name.to_owned());
+
+    let shape: Result<Value, MontycatClientError> = match schema {
+        NestedSchema::Struct(fields) => {
+            let mut fields: Vec<(&str, &str)> = fields.into_iter().collect();
+            fields.sort_unstable();
+            describe_named(&fields, visiting).map(|fields| json!({ "schema": name, "fields": fields }))
+        }
+        NestedSchema::Enum(schema) => schema
+            .variants
+            .iter()
+            .map(|(variant, shape)| Ok(((*variant).to_owned(), describe_variant(shape, visiting)?)))
+            .collect::<Result<Map<String, Value>, MontycatClientError>>()
+            .map(|variants| {
+                json!({ "schema": name, "tagging": schema.tagging, "variants": variants })
+            }),
+    };
+
+    visiting.pop();
+
+    Ok(FieldSchema {
+        shape: Some(shape?),
+        ..FieldSchema::flat(field_type)
+    })
+}
+
+fn describe_variant(
+    shape: &VariantShape,
+    visiting: &mut Vec<String>,
+) -> Result<Value, MontycatClientError> {
+    Ok(match shape {
+        VariantShape::Unit => json!({ "kind": "unit" }),
+        VariantShape::Newtype(ty) => {
+            json!({ "kind": "newtype", "value": describe_type(ty, visiting)?.to_value()? })
+        }
+        VariantShape::Tuple(types) => {
+            json!({ "kind": "tuple", "items": describe_all(types, visiting)? })
+        }
+        VariantShape::Struct(fields) => {
+            json!({ "kind": "struct", "fields": describe_named(fields, visiting)? })
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Pointer, Timestamp};
+    use serde::Deserialize;
+
+    #[derive(Debug, Clone, Serialize, Deserialize, crate::RuntimeSchema)]
+    struct GeoPoint {
+        lat: f64,
+        lon: f64,
+    }
+
+    #[derive(Debug, Clone, Serialize, Deserialize, crate::RuntimeSchema)]
+    struct TreeNode {
+        label: String,
+        children: Vec<TreeNode>,
+    }
+
+    fn describe(ty: &str) -> Value {
+        describe_type(ty, &mut Vec::new())
+            .unwrap()
+            .to_value()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_collections_capture_element_types() {
+        assert_eq!(describe("String"), json!(["String", false]));
+        assert_eq!(
+            describe("Option<Vec<u32>>"),
+            json!(["Array", true, { "items": ["Number", false] }])
+        );
+        assert_eq!(
+            describe("HashMap<String, Vec<bool>>"),
+            json!(["Object", false, {
+                "keys": ["String", false],
+                "values": ["Array", false, { "items": ["Boolean", false] }],
+            }])
+        );
+        assert_eq!(
+            describe("(String, Option<i64>)"),
+            json!(["Array", false, { "items": [["String", false], ["Number", true]] }])
+        );
+        assert_eq!(
+            describe("[u8; 16]"),
+            json!(["Array", false, { "items": ["Number", false] }])
+        );
+    }
+
+    #[test]
+    fn test_registered_structs_and_enums_are_nested() {
+        assert!(describe_type("GeoPoint", &mut Vec::new()).is_err());
+
+        register_schema::<GeoPoint>();
+        register_schema::<TreeNode>();
+        register_enum_schema(
+            EnumSchema::new("GeoShape")
+                .with_tagging(EnumTagging::Internal { tag: "type" })
+                .unit("Empty")
+                .newtype("Center", "GeoPoint")
+                .tuple("Segment", &["GeoPoint", "GeoPoint"])
+                .structure("Polygon", &[("points", "Vec<GeoPoint>")]),
+        );
+
+        let point: Value = json!(["Object", false, {
+            "schema": "GeoPoint",
+            "fields": { "lat": ["Number", false], "lon": ["Number", false] },
+        }]);
+
+        assert_eq!(describe("Option<Box<GeoPoint>>")[2], point[2]);
+        assert_eq!(
+            describe("Vec<GeoPoint>"),
+            json!(["Array", false, { "items": point }])
+        );
+        assert_eq!(
+            describe("GeoShape"),
+            json!(["Enum", false, {
+                "schema": "GeoShape",
+                "tagging": { "kind": "internal", "tag": "type" },
+                "variants": {
+                    "Empty": { "kind": "unit" },
+                    "Center": { "kind": "newtype", "value": point },
+                    "Segment": { "kind": "tuple", "items": [point, point] },
+                    "Polygon": {
+                        "kind": "struct",
+                        "fields": { "points": ["Array", false, { "items": point }] },
+                    },
+                },
+            }])
+        );
+        assert_eq!(
+            describe("TreeNode")[2]["fields"]["children"],
+            json!(["Array", false, { "items": ["Object", false, { "schema": "TreeNode" }] }])
+        );
+
+        assert!(unregister_schema("GeoShape"));
+        assert_eq!(describe("GeoPoint"), point);
+    }
+
+    #[test]
+    fn test_flat_fields_keep_their_wire_format() {
+        let fields: HashMap<&str, &str> =
+            HashMap::from([("name", "String"), ("age", "Option<u8>")]);
+
+        assert_eq!(
+            serde_json::to_value(describe_fields(fields).unwrap()).unwrap(),
+            json!({ "name": ["String", false], "age": ["Number", true] })
+        );
+        assert!(describe_fields(HashMap::from([("x", "UnknownType")])).is_err());
+    }
+
+    #[test]
+    fn test_flattened_fields_drop_their_shape() {
+        let mut fields: HashMap<String, FieldSchema> = describe_fields(HashMap::from([
+            ("tags", "Vec<String>"),
+            ("scores", "Option<HashMap<String, u32>>"),
+        ]))
+        .unwrap();
+        fields.values_mut().for_each(FieldSchema::flatten);
+
+        assert_eq!(
+            serde_json::to_value(fields).unwrap(),
+            json!({ "tags": ["Array", false], "scores": ["Object", true] })
+        );
+    }
+}
@@ -1,5 +1,25 @@
-use serde::{Deserialize, Serialize};
-use std::{collections::HashMap, hash::Hash};
+use crate::{
+    MontycatClientError,
+    engine::structure::Engine,
+    keyspace::{
+        pubtrait::Keyspace,
+        resolver::store_keyspaces,
+        structures::{inmemory::InMemoryKeyspace, persistent::PersistentKeyspace},
+    },
+    response::typed::ReadOptions,
+};
+use serde::{
+    Deserialize, Deserializer, Serialize, Serializer,
+    de::{self, DeserializeOwned, MapAccess, SeqAccess, Visitor},
+    ser::SerializeStruct,
+};
+use serde_json::Value;
+use std::{
+    collections::HashMap,
+    fmt,
+    hash::{Hash, Hasher},
+    marker::PhantomData,
+};
 
 /// Represents a limit with start and stop values.
 ///
@@ -49,7 +69,11 @@ impl Limit {
     }
 }
 
-/// Represents a pointer with keyspace and key.
+/// Represents a pointer with keyspace and key, typed by the value it points to.
+///
+/// `Pointer` alone points to any value (`serde_json::Value`); `Pointer<T>` remembers that the target
+/// is a `T`, so `resolve` returns a `T`. A pointer read with `get_bulk_resolved` or
+/// `lookup_values_where_resolved` also holds its target, see `Pointer::value`.
 ///
 /// # Fields
 /// - `keyspace: String` : The keyspace of the pointer.
@@ -58,12 +82,25 @@ impl Limit {
 /// # Examples
 /// ```rust, ignore
 /// let pointer = Pointer::new("my_keyspace", "my_key");
+///
+/// #[derive(Serialize, Deserialize, RuntimeSchema)]
+/// struct Order {
+///     customer: Pointer<Customer>,
+/// }
+///
+/// let customer: Option<Customer> = order.customer.resolve(&engine).await?;
 /// ```
 ///
-#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize, Default)]
-pub struct Pointer {
+/// # Notes
+/// Deserializes from `{"keyspace": ..., "key": ...}` (with an optional resolved `value`) or from
+/// `[keyspace, key]`, the form stored in the `pointers` section of a value. The resolved value is
+/// never serialized, and pointers are equal when their keyspace and key are.
+///
+#[derive(Debug, Clone)]
+pub struct Pointer<T = Value> {
     pub keyspace: String,
     pub key: String,
+    value: Option<Box<T>>,
 }
 
 impl Pointer {
@@ -77,10 +114,7 @@ impl Pointer {
     /// - `Self` : A new instance of `Pointer` with the specified values.
     ///
     pub fn new(keyspace: &str, key: &str) -> Self {
-        Self {
-            keyspace: keyspace.to_owned(),
-            key: key.to_owned(),
-        }
+        Self::typed(keyspace, key)
     }
 
     /// Sets the pointer values and returns them as a tuple.
@@ -105,6 +139,112 @@ impl Pointer {
     }
 }
 
+impl<T> Pointer<T> {
+    /// Creates a new pointer to a value of type `T`.
+    ///
+    /// # Examples
+    /// ```rust, ignore
+    /// let customer: Pointer<Customer> = Pointer::typed("customers", &key);
+    /// ```
+    ///
+    pub fn typed(keyspace: &str, key: &str) -> Self {
+        Self {
+            keyspace: keyspace.to_owned(),
+            key: key.to_owned(),
+            value: None,
+        }
+    }
+
+    /// Returns the same pointer, typed by another target type. The resolved value is dropped.
+    pub fn cast<U>(self) -> Pointer<U> {
+        Pointer {
+            keyspace: self.keyspace,
+            key: self.key,
+            value: None,
+        }
+    }
+
+    /// Returns the target value, if the pointer was read with eager resolution and the target exists.
+    pub fn value(&self) -> Option<&T> {
+        self.value.as_deref()
+    }
+
+    /// Returns the target value, consuming the pointer.
+    pub fn into_value(self) -> Option<T> {
+        self.value.map(|value| *value)
+    }
+
+    /// Reads the target value from the keyspace of the pointer. Whether the keyspace is persistent
+    /// or in-memory is looked up with `get_structure_available`.
+    ///
+    /// # Arguments
+    /// - `engine: &Engine` : The engine of the store the target is in.
+    ///
+    /// # Returns
+    /// - `Result<Option<T>, MontycatClientError>` : The target value, or None if it or its keyspace
+    ///   does not exist.
+    ///
+    /// # Errors
+    /// - `MontycatClientError::ClientStoreNotSet` : If the store is not set in the engine.
+    /// - `MontycatClientError::ClientValueParsingError` : If the store structure cannot be parsed.
+    /// - Every error `Keyspace::get_value_as` returns.
+    ///
+    pub async fn resolve(&self, engine: &Engine) -> Result<Option<T>, MontycatClientError>
+    where
+        T: DeserializeOwned + Send,
+    {
+        let persistent: Option<bool> = store_keyspaces(engine)
+            .await?
+            .into_iter()
+            .find(|(name, _)| *name == self.keyspace)
+            .map(|(_, persistent)| persistent);
+
+        match persistent {
+            Some(true) => {
+                PersistentKeyspace::new(&self.keyspace, engine)
+                    .get_value_as(Some(&self.key), None, ReadOptions::new())
+                    .await
+            }
+            Some(false) => {
+                InMemoryKeyspace::new(&self.keyspace, engine)
+                    .get_value_as(Some(&self.key), None, ReadOptions::new())
+                    .await
+            }
+            None => Ok(None),
+        }
+    }
+}
+
+impl<T> Default for Pointer<T> {
+    fn default() -> Self {
+        Self::typed("", "")
+    }
+}
+
+impl<T, U> PartialEq<Pointer<U>> for Pointer<T> {
+    fn eq(&self, other: &Pointer<U>) -> bool {
+        self.keyspace == other.keyspace && self.key == other.key
+    }
+}
+
+impl<T> Eq for Pointer<T> {}
+
+impl<T> Hash for Pointer<T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.keyspace.hash(state);
+        self.key.hash(state);
+    }
+}
+
+impl<T> Serialize for Pointer<T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut pointer = serializer.serialize_struct("Pointer", 2)?;
+        pointer.serialize_field("keyspace", &self.keyspace)?;
+        pointer.serialize_field("key", &self.key)?;
+        pointer.end()
+    }
+}
+
 /// Represents a timestamp with an optional timestamp string.
 ///
 /// # Fields
@@ -115,7 +255,11 @@ impl Pointer {
 /// let ts = Timestamp::new("2024-01-01T00:00:00Z");
 /// ```
 ///
-#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize, Default)]
+/// # Notes
+/// Deserializes from `{"timestamp": ...}` or from the timestamp string (or null) alone, the form
+/// stored in the `timestamps` section of a value.
+///
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Default)]
 pub struct Timestamp {
     timestamp: Option<String>,
 }
@@ -225,6 +369,103 @@ impl Timestamp {
     }
 }
 
+impl<'de, T: Deserialize<'de>> Deserialize<'de> for Pointer<T> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct PointerVisitor<T>(PhantomData<T>);
+
+        impl<'de, T: Deserialize<'de>> Visitor<'de> for PointerVisitor<T> {
+            type Value = Pointer<T>;
+
+            fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str("a pointer object or a [keyspace, key] pair")
+            }
+
+            fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Pointer<T>, A::Error> {
+                let keyspace: String = seq
+                    .next_element()?
+                    .ok_or_else(|| de::Error::invalid_length(0, &self))?;
+                let key: String = seq
+                    .next_element()?
+                    .ok_or_else(|| de::Error::invalid_length(1, &self))?;
+
+                Ok(Pointer {
+                    keyspace,
+                    key,
+                    value: None,
+                })
+            }
+
+            fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Pointer<T>, A::Error> {
+                let mut keyspace: Option<String> = None;
+                let mut key: Option<String> = None;
+                let mut value: Option<T> = None;
+
+                while let Some(field) = map.next_key::<String>()? {
+                    match field.as_str() {
+                        "keyspace" => keyspace = Some(map.next_value()?),
+                        "key" => key = Some(map.next_value()?),
+                        "value" => value = map.next_value()?,
+                        _ => {
+                            map.next_value::<de::IgnoredAny>()?;
+                        }
+                    }
+                }
+
+                Ok(Pointer {
+                    keyspace: keyspace.ok_or_else(|| de::Error::missing_field("keyspace"))?,
+                    key: key.ok_or_else(|| de::Error::missing_field("key"))?,
+                    value: value.map(Box::new),
+                })
+            }
+        }
+
+        deserializer.deserialize_any(PointerVisitor(PhantomData))
+    }
+}
+
+impl<'de> Deserialize<'de> for Timestamp {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct TimestampVisitor;
+
+        impl<'de> Visitor<'de> for TimestampVisitor {
+            type Value = Timestamp;
+
+            fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str("a timestamp object, a timestamp string or null")
+            }
+
+            fn visit_str<E: de::Error>(self, v: &str) -> Result<Timestamp, E> {
+                Ok(Timestamp::new(v))
+            }
+
+            fn visit_unit<E: de::Error>(self) -> Result<Timestamp, E> {
+                Ok(Timestamp::default())
+            }
+
+            fn visit_none<E: de::Error>(self) -> Result<Timestamp, E> {
+                Ok(Timestamp::default())
+            }
+
+            fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Timestamp, A::Error> {
+                let mut timestamp: Option<String> = None;
+
+                while let Some(field) = map.next_key::<String>()? {
+                    match field.as_str() {
+                        "timestamp" => timestamp = map.next_value()?,
+                        _ => {
+                            map.next_value::<de::IgnoredAny>()?;
+                        }
+                    }
+                }
+
+                Ok(Timestamp { timestamp })
+            }
+        }
+
+        deserializer.deserialize_any(TimestampVisitor)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -334,8 +575,60 @@ mod tests {
         assert_eq!(pointer.key, "key:with:colons");
     }
 
+    #[test]
+    fn test_typed_pointer_reads_compact_and_resolved_forms() {
+        #[derive(Debug, Clone, Deserialize)]
+        struct Customer {
+            name: String,
+        }
+
+        let compact: Pointer<Customer> = serde_json::from_str(r#"["customers","c1"]"#).unwrap();
+        let resolved: Pointer<Customer> =
+            serde_json::from_str(r#"{"keyspace":"customers","key":"c1","value":{"name":"Ann"}}"#)
+                .unwrap();
+
+        assert!(compact.value().is_none());
+        assert_eq!(compact, resolved);
+        assert_eq!(resolved.value().unwrap().name, "Ann");
+        assert_eq!(
+            serde_json::to_string(&resolved).unwrap(),
+            r#"{"keyspace":"customers","key":"c1"}"#
+        );
+        assert_eq!(resolved.cast::<Value>(), Pointer::new("customers", "c1"));
+    }
+
     // ===== Timestamp Tests =====
 
+    #[cfg(not(feature = "tls"))]
+    #[test]
+    fn test_pointer_resolves_from_in_memory_keyspace() {
+        tokio_test::block_on(async {
+            let server = crate::test_support::MockServer::payloads(3, |request| {
+                if request["raw"][0] == "get-structure-available" {
+                    return serde_json::json!({
+                        "store": { "persistent": ["orders"], "in_memory": ["sessions"] },
+                    });
+                }
+
+                Value::String(r#"{"user":"monty"}"#.to_string())
+            })
+            .await;
+
+            let engine = server.engine();
+            let session: Pointer<Value> = Pointer::typed("sessions", "s1");
+            let missing: Pointer<Value> = Pointer::typed("archive", "a1");
+
+            let value: Option<Value> = session.resolve(&engine).await.unwrap();
+            assert_eq!(value, Some(serde_json::json!({ "user": "monty" })));
+            assert_eq!(missing.resolve(&engine).await.unwrap(), None);
+
+            let requests: Vec<Value> = server.json_requests().await;
+            assert_eq!(requests[1]["keyspace"], "sessions");
+            assert_eq!(requests[1]["persistent"], false);
+            assert_eq!(requests[1]["key"], "s1");
+        });
+    }
+
     #[test]
     fn test_timestamp_new() {
         let ts = Timestamp::new("2024-01-01T00:00:00Z");
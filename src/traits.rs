@@ -7,3 +7,52 @@ pub trait RuntimeSchema {
     fn field_names_and_types(&self) -> Vec<(&'static str, &'static str)>;
     fn schema_params() -> (HashMap<&'static str, &'static str>, &'static str);
 }
+
+/// Trait for values stored under a custom key taken from one of their fields.
+///
+/// Implement it with the `montycat_entity!` macro. `Collection::save` uses it to insert new values
+/// under their custom key and to update existing ones.
+///
+/// # Examples
+///
+/// ```rust, ignore
+/// #[derive(Serialize, Deserialize, RuntimeSchema, Clone, Debug)]
+/// struct Employee {
+///     email: String,
+///     name: String,
+/// }
+///
+/// montycat_entity!(Employee, email);
+///
+/// employees.save(&employee).await?;
+/// ```
+///
+pub trait MontycatEntity {
+    /// Name of the field that holds the custom key.
+    const KEY_FIELD: &'static str;
+
+    /// Returns the custom key of this value.
+    fn custom_key(&self) -> String;
+}
+
+/// Implements `MontycatEntity` for a struct, using `field` as the custom key.
+/// The field must implement `Display`.
+///
+/// # Examples
+///
+/// ```rust, ignore
+/// montycat_entity!(Employee, email);
+/// ```
+///
+#[macro_export]
+macro_rules! montycat_entity {
+    ($ty:ty, $field:ident) => {
+        impl $crate::MontycatEntity for $ty {
+            const KEY_FIELD: &'static str = stringify!($field);
+
+            fn custom_key(&self) -> String {
+                self.$field.to_string()
+            }
+        }
+    };
+}